@@ -5,10 +5,15 @@ const baseUrl = 'http://127.0.0.1:8080';
 let currentView = 'catalog';
 let cartItems = [];
 let products = [];
+// Paginação do catálogo (uma página por vez, conforme page/total_pages da API)
+let catalogPage = 1;
+let catalogTotalPages = 1;
 // Persistência de formulário de checkout
 let checkoutFormState = {};
 // Sessão do usuário
 let currentUser = null;
+// Token anti-CSRF da sessão (enviado em X-CSRF-Token nas rotas protegidas que alteram estado)
+let csrfToken = '';
 
 // Inicialização
 document.addEventListener('DOMContentLoaded', () => {
@@ -56,6 +61,7 @@ async function ensureSession(){
         }
         const user = await r.json();
         currentUser = user;
+        csrfToken = user.csrf_token || '';
         injectUserHeader(user);
         return true;
     } catch (e) {
@@ -94,7 +100,7 @@ function injectUserHeader(user){
 
 async function doLogout(){
     try {
-        const r = await fetch(`${baseUrl}/api/logout`, { method: 'POST', credentials: 'include' });
+        const r = await fetch(`${baseUrl}/api/logout`, { method: 'POST', credentials: 'include', headers: { 'X-CSRF-Token': csrfToken } });
         if (r.ok) {
             try { sessionStorage.setItem('toast', 'Sessão encerrada'); } catch {}
             window.location.href = '/login';
@@ -248,18 +254,22 @@ function loadInitialData() {
 }
 
 // === API FUNCTIONS ===
-async function fetchProducts() {
+async function fetchProducts(page = catalogPage) {
     try {
         showSkeletonProducts();
         
-        const response = await fetch(`${baseUrl}/api/products`, { credentials: 'include' });
+        // A API pagina o catálogo: carregar só a página pedida (o navegador revalida pelo ETag)
+        const response = await fetch(`${baseUrl}/api/products?page=${page}`, { credentials: 'include' });
         if (response.status === 401) { window.location.href = '/login'; return; }
-        
+
         if (!response.ok) {
             throw new Error(`HTTP ${response.status}`);
         }
-        
-        products = await response.json();
+
+        const data = await response.json();
+        products = data.items || [];
+        catalogPage = data.page || page;
+        catalogTotalPages = data.total_pages || 1;
         renderProducts();
         hideSkeletonProducts();
     } catch (error) {
@@ -269,6 +279,12 @@ async function fetchProducts() {
     }
 }
 
+function goToCatalogPage(page) {
+    if (page < 1 || page > catalogTotalPages || page === catalogPage) return;
+    fetchProducts(page);
+    document.getElementById('view-catalog').scrollIntoView({ behavior: 'smooth' });
+}
+
 async function addToCart(productId, quantity) {
     try {
         const response = await fetch(`${baseUrl}/api/cart`, {
@@ -379,6 +395,7 @@ async function checkout(orderData) {
             method: 'POST',
             headers: {
                 'Content-Type': 'application/json',
+                'X-CSRF-Token': csrfToken,
             },
             credentials: 'include',
             body: JSON.stringify(orderData)
@@ -408,6 +425,8 @@ async function checkout(orderData) {
                 emailInput.focus();
             }
             // Usar mensagem inline abaixo do campo, sem toast
+        } else if (error.status === 403) {
+            toast('📧 Confirme seu e-mail (verifique sua caixa de entrada) antes de finalizar o pedido.', 'info');
         } else if (String(error.message || '').toLowerCase().includes('carrinho está vazio')) {
             toast('⚠️ O carrinho está vazio. Adicione produtos antes de finalizar.', 'info');
         } else {
@@ -440,7 +459,7 @@ function renderProducts() {
             ${products.map(product => `
                 <div class="card product-card">
                     <div class="product-image-container">
-                        <img src="${product.image_url || ''}" 
+                        <img src="${product.thumbnail_url || product.image_url || ''}" 
                              alt="${product.name}" 
                              class="product-image"
                              onerror="handleImageError(this)">
@@ -464,6 +483,21 @@ function renderProducts() {
                 </div>
             `).join('')}
         </div>
+        ${catalogTotalPages > 1 ? `
+            <nav class="catalog-pagination" aria-label="Páginas do catálogo">
+                <button class="btn btn-outline-primary btn-sm"
+                        onclick="goToCatalogPage(${catalogPage - 1})"
+                        ${catalogPage <= 1 ? 'disabled' : ''}>
+                    ← Anterior
+                </button>
+                <span class="text-secondary">Página ${catalogPage} de ${catalogTotalPages}</span>
+                <button class="btn btn-outline-primary btn-sm"
+                        onclick="goToCatalogPage(${catalogPage + 1})"
+                        ${catalogPage >= catalogTotalPages ? 'disabled' : ''}>
+                    Próxima →
+                </button>
+            </nav>
+        ` : ''}
     `;
 }
 
@@ -0,0 +1,363 @@
+use futures_util::StreamExt;
+use serde_json::Value;
+use sqlx::{Row, SqlitePool};
+use std::io::Write;
+use std::pin::pin;
+use std::time::Duration;
+
+use crate::catalogo::{self, ErroImportacao, Formato};
+use crate::config::Config;
+use crate::{bootstrap, format_brl, password_policy, pedidos_stream, profile, webhooks};
+
+// Binário `mercado-admin`: consultas e manutenção direto no banco, sem passar pela API.
+// Usa os mesmos modelos e migrações do servidor; o banco vem de --database ou DATABASE_PATH.
+const USO: &str = "Uso: mercado-admin [--database <arquivo>] <comando>\n\
+                   \n\
+                   pedidos list [--limit <n>] [--status <paid|shipped|cancelled>]\n\
+                   pedidos show <id>\n\
+                   pedidos watch                  acompanha pedidos novos (Ctrl+C para sair)\n\
+                   users list\n\
+                   users create-admin --email <email> [--nome <nome>] [--promote]\n\
+                                                  senha via ADMIN_PASSWORD ou entrada padrão;\n\
+                                                  --promote promove uma conta de cliente existente\n\
+                   users disable <id|email>       bloqueia o login e encerra sessões e tokens\n\
+                   users enable <id|email>\n\
+                   products export [--format <csv|json>] [--output <arquivo>]\n\
+                   products import <arquivo.csv|.json> [--dry-run]\n\
+                                                  cria ou atualiza produtos pelo SKU ou EAN\n\
+                   db migrate                     cria o banco ou aplica as migrações\n\
+                   db backup <destino>            cópia consistente do banco (VACUUM INTO)";
+
+// Erro de uso (código 2) ou de execução (código 1)
+enum Falha {
+    Uso,
+    Erro(String),
+}
+
+impl From<sqlx::Error> for Falha {
+    fn from(e: sqlx::Error) -> Self {
+        Falha::Erro(format!("Erro no banco: {}", e))
+    }
+}
+
+type Resultado = Result<(), Falha>;
+
+// Ponto de entrada do binário. Retorna o código de saída do processo.
+pub async fn run(args: Vec<String>) -> i32 {
+    let mut config = Config::from_env();
+    let mut resto = Vec::new();
+    let mut it = args.into_iter();
+    while let Some(arg) = it.next() {
+        if arg == "--database" {
+            match it.next() {
+                Some(v) => config.database_path = v,
+                None => return uso(),
+            }
+        } else if let Some(v) = arg.strip_prefix("--database=") {
+            config.database_path = v.to_string();
+        } else {
+            resto.push(arg);
+        }
+    }
+    let args: Vec<&str> = resto.iter().map(String::as_str).collect();
+
+    let resultado = match args.as_slice() {
+        ["db", "migrate"] => db_migrate(&config).await,
+        ["db", "backup", destino] => db_backup(&config, destino).await,
+        [grupo, ..] if matches!(*grupo, "pedidos" | "users" | "products") => match abrir(&config).await {
+            Ok(db) => executar(&db, &config, &args).await,
+            Err(f) => Err(f),
+        },
+        _ => Err(Falha::Uso),
+    };
+    match resultado {
+        Ok(()) => 0,
+        Err(Falha::Uso) => uso(),
+        Err(Falha::Erro(msg)) => {
+            eprintln!("Erro: {}", msg);
+            1
+        }
+    }
+}
+
+fn uso() -> i32 {
+    eprintln!("{}", USO);
+    2
+}
+
+async fn executar(db: &SqlitePool, config: &Config, args: &[&str]) -> Resultado {
+    match args {
+        ["pedidos", "list", opcoes @ ..] => pedidos_list(db, opcoes).await,
+        ["pedidos", "show", id] => pedidos_show(db, id).await,
+        ["pedidos", "watch"] => pedidos_watch(db).await,
+        ["users", "list"] => users_list(db).await,
+        ["users", "create-admin", opcoes @ ..] => users_create_admin(db, config, opcoes).await,
+        ["users", "disable", conta] => users_set_disabled(db, conta, true).await,
+        ["users", "enable", conta] => users_set_disabled(db, conta, false).await,
+        ["products", "export", opcoes @ ..] => products_export(db, opcoes).await,
+        ["products", "import", arquivo, opcoes @ ..] => products_import(db, config.low_stock_threshold, arquivo, opcoes).await,
+        _ => Err(Falha::Uso),
+    }
+}
+
+// Banco já existente: só `db migrate` cria o arquivo
+async fn abrir(config: &Config) -> Result<SqlitePool, Falha> {
+    if !std::path::Path::new(&config.database_path).exists() {
+        return Err(Falha::Erro(format!(
+            "Banco {} não encontrado (crie com `mercado-admin db migrate`)",
+            config.database_path
+        )));
+    }
+    Ok(crate::open_db(&config.database_path, false).await?)
+}
+
+// Só o esquema: nenhuma conta é criada, nem a de demonstração nem a de ADMIN_EMAIL
+async fn db_migrate(config: &Config) -> Resultado {
+    let db = crate::init_db(config).await?;
+    db.close().await;
+    println!("Banco {} atualizado", config.database_path);
+    Ok(())
+}
+
+async fn db_backup(config: &Config, destino: &str) -> Resultado {
+    if std::path::Path::new(destino).exists() {
+        return Err(Falha::Erro(format!("{} já existe; escolha outro destino", destino)));
+    }
+    let db = abrir(config).await?;
+    sqlx::query("VACUUM INTO ?").bind(destino).execute(&db).await?;
+    db.close().await;
+    println!("Backup de {} gravado em {}", config.database_path, destino);
+    Ok(())
+}
+
+async fn pedidos_list(db: &SqlitePool, opcoes: &[&str]) -> Resultado {
+    let mut limite = 50i64;
+    let mut status: Option<&str> = None;
+    let mut it = opcoes.iter();
+    while let Some(opcao) = it.next() {
+        match (*opcao, it.next()) {
+            ("--limit", Some(v)) => limite = v.parse().ok().filter(|n| *n > 0).ok_or(Falha::Uso)?,
+            ("--status", Some(v)) => status = Some(v),
+            _ => return Err(Falha::Uso),
+        }
+    }
+    // Pedidos anteriores aos juros gravam total_with_interest_cents = 0: vale o próprio total
+    let rows = sqlx::query(
+        r#"SELECT id, status, total_cents, payment_method, payment_installments, created_at,
+                  CASE WHEN total_with_interest_cents > 0 THEN total_with_interest_cents ELSE total_cents END AS total_com_juros
+           FROM pedidos WHERE (? IS NULL OR status = ?) ORDER BY created_at DESC, rowid DESC LIMIT ?"#,
+    )
+    .bind(status)
+    .bind(status)
+    .bind(limite)
+    .fetch_all(db)
+    .await?;
+
+    println!("id\tstatus\ttotal\ttotal_com_juros\tpagamento\tparcelas\tcriado_em");
+    for r in rows {
+        println!(
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            r.try_get::<String, _>("id").unwrap_or_default(),
+            r.try_get::<String, _>("status").unwrap_or_default(),
+            format_brl(r.try_get::<i64, _>("total_cents").unwrap_or(0)),
+            format_brl(r.try_get::<i64, _>("total_com_juros").unwrap_or(0)),
+            r.try_get::<String, _>("payment_method").unwrap_or_default(),
+            r.try_get::<Option<i64>, _>("payment_installments").unwrap_or(None).map(|n| n.to_string()).unwrap_or_default(),
+            r.try_get::<String, _>("created_at").unwrap_or_default(),
+        );
+    }
+    Ok(())
+}
+
+fn centavos(v: &Value) -> String {
+    format_brl(v.as_i64().unwrap_or(0))
+}
+
+// Mesmo resumo do webhook order.created e do stream do painel
+fn imprimir_pedido(p: &Value) {
+    // Pedidos anteriores aos juros gravam total_with_interest_cents = 0 (mesma regra do recibo)
+    let total = match p["total_with_interest_cents"].as_u64() {
+        Some(t) if t > 0 => &p["total_with_interest_cents"],
+        _ => &p["total_cents"],
+    };
+    println!(
+        "{} id={} status={} total={} metodo={}",
+        p["created_at"].as_str().unwrap_or(""),
+        p["order_id"].as_str().unwrap_or(""),
+        p["status"].as_str().unwrap_or(""),
+        centavos(total),
+        p["payment_method"].as_str().unwrap_or(""),
+    );
+    for item in p["items"].as_array().into_iter().flatten() {
+        println!(
+            "  - product_id={} qty={} unit={}",
+            item["product_id"],
+            item["qty"],
+            centavos(&item["unit_price_cents"]),
+        );
+    }
+}
+
+async fn pedidos_show(db: &SqlitePool, id: &str) -> Resultado {
+    let existe: Option<i64> = sqlx::query_scalar("SELECT 1 FROM pedidos WHERE id = ?").bind(id).fetch_optional(db).await?;
+    if existe.is_none() {
+        return Err(Falha::Erro(format!("Pedido {} não encontrado", id)));
+    }
+    let mut conn = db.acquire().await?;
+    imprimir_pedido(&webhooks::dados_pedido(&mut conn, id).await?);
+    Ok(())
+}
+
+// Consulta o banco a cada segundo a partir do pedido mais recente
+async fn pedidos_watch(db: &SqlitePool) -> Resultado {
+    let mut ultimo = pedidos_stream::posicao_atual(db).await?;
+    println!("Monitorando novos pedidos (Ctrl+C para sair)");
+    loop {
+        let novos = pedidos_stream::pedidos_apos(db, ultimo).await?;
+        let vazio = novos.is_empty();
+        for (seq, pedido) in novos {
+            ultimo = seq;
+            print!("[novo pedido] ");
+            imprimir_pedido(&pedido);
+        }
+        if vazio {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
+    }
+}
+
+async fn users_list(db: &SqlitePool) -> Resultado {
+    let rows = sqlx::query(
+        r#"SELECT id, nome, email, papel, verified_at IS NOT NULL AS verificado, disabled_at IS NOT NULL AS desativado, deleted_at IS NOT NULL AS excluido
+           FROM usuarios ORDER BY id"#,
+    )
+    .fetch_all(db)
+    .await?;
+
+    println!("id\tnome\temail\tpapel\tverificado\tsituacao");
+    for r in rows {
+        let situacao = if r.try_get::<bool, _>("excluido").unwrap_or(false) {
+            "excluida"
+        } else if r.try_get::<bool, _>("desativado").unwrap_or(false) {
+            "desativada"
+        } else {
+            "ativa"
+        };
+        println!(
+            "{}\t{}\t{}\t{}\t{}\t{}",
+            r.try_get::<i64, _>("id").unwrap_or(0),
+            r.try_get::<String, _>("nome").unwrap_or_default(),
+            r.try_get::<String, _>("email").unwrap_or_default(),
+            r.try_get::<String, _>("papel").unwrap_or_default(),
+            if r.try_get::<bool, _>("verificado").unwrap_or(false) { "sim" } else { "nao" },
+            situacao,
+        );
+    }
+    Ok(())
+}
+
+// Mesmas regras do `mercado-backend admin create`, no banco de --database
+async fn users_create_admin(db: &SqlitePool, config: &Config, opcoes: &[&str]) -> Resultado {
+    let mut email = None;
+    let mut nome = "Admin";
+    let mut promover = false;
+    let mut it = opcoes.iter();
+    while let Some(opcao) = it.next() {
+        if *opcao == "--promote" {
+            promover = true;
+            continue;
+        }
+        match (*opcao, it.next()) {
+            ("--email", Some(v)) => email = Some(*v),
+            ("--nome", Some(v)) => nome = v,
+            _ => return Err(Falha::Uso),
+        }
+    }
+    let email = email.ok_or(Falha::Uso)?;
+    let senha = bootstrap::ler_senha().ok_or_else(|| Falha::Erro("Não foi possível ler a senha".to_string()))?;
+    bootstrap::create_admin(db, config, nome, email, &senha, promover).await.map_err(Falha::Erro)?;
+    println!("Administrador {} pronto", password_policy::normalize_email(email));
+    Ok(())
+}
+
+async fn users_set_disabled(db: &SqlitePool, conta: &str, desativar: bool) -> Resultado {
+    let row = match conta.parse::<i64>() {
+        Ok(id) => sqlx::query("SELECT id, email FROM usuarios WHERE id = ?").bind(id).fetch_optional(db).await?,
+        Err(_) => sqlx::query("SELECT id, email FROM usuarios WHERE email = ?")
+            .bind(password_policy::normalize_email(conta))
+            .fetch_optional(db)
+            .await?,
+    };
+    let Some(row) = row else {
+        return Err(Falha::Erro(format!("Usuário {} não encontrado", conta)));
+    };
+    let id: i64 = row.try_get("id").unwrap_or(0);
+    let email: String = row.try_get("email").unwrap_or_default();
+    if !profile::set_disabled(db, id, desativar).await? {
+        return Err(Falha::Erro(format!("A conta {} foi excluída", email)));
+    }
+    if desativar {
+        println!("Conta {} ({}) desativada; sessões e tokens encerrados", id, email);
+    } else {
+        println!("Conta {} ({}) reativada", id, email);
+    }
+    Ok(())
+}
+
+// Mesmo conteúdo do GET /api/admin/products/export
+async fn products_export(db: &SqlitePool, opcoes: &[&str]) -> Resultado {
+    let mut formato = Formato::Csv;
+    let mut arquivo: Option<&str> = None;
+    let mut it = opcoes.iter();
+    while let Some(opcao) = it.next() {
+        match (*opcao, it.next()) {
+            ("--format", Some(v)) => formato = Formato::from_nome(v).ok_or(Falha::Uso)?,
+            ("--output", Some(v)) => arquivo = Some(v),
+            _ => return Err(Falha::Uso),
+        }
+    }
+
+    let mut saida: Box<dyn Write> = match arquivo {
+        Some(caminho) => Box::new(
+            std::fs::File::create(caminho).map_err(|e| Falha::Erro(format!("Não foi possível gravar {}: {}", caminho, e)))?,
+        ),
+        None => Box::new(std::io::stdout().lock()),
+    };
+    let mut blocos = pin!(catalogo::exportar(db.clone(), formato));
+    while let Some(bloco) = blocos.next().await {
+        saida.write_all(&bloco?).map_err(|e| Falha::Erro(format!("Erro ao gravar: {}", e)))?;
+    }
+    saida.flush().map_err(|e| Falha::Erro(format!("Erro ao gravar: {}", e)))?;
+    if let Some(caminho) = arquivo {
+        eprintln!("Catálogo exportado para {}", caminho);
+    }
+    Ok(())
+}
+
+// Mesmas regras do POST /api/admin/products/import; o formato vem da extensão do arquivo
+async fn products_import(db: &SqlitePool, limite_estoque: i64, arquivo: &str, opcoes: &[&str]) -> Resultado {
+    let dry_run = match opcoes {
+        [] => false,
+        ["--dry-run"] => true,
+        _ => return Err(Falha::Uso),
+    };
+    let extensao = std::path::Path::new(arquivo).extension().and_then(|e| e.to_str()).unwrap_or("");
+    let formato = Formato::from_nome(extensao).ok_or_else(|| Falha::Erro(format!("Use um arquivo .csv ou .json: {}", arquivo)))?;
+    let dados = std::fs::read(arquivo).map_err(|e| Falha::Erro(format!("Não foi possível ler {}: {}", arquivo, e)))?;
+
+    match catalogo::importar(db, formato, &dados, dry_run, limite_estoque).await {
+        Ok(r) => {
+            let prefixo = if dry_run { "Simulação (nada gravado): " } else { "" };
+            println!("{}{} produtos criados, {} atualizados", prefixo, r.criados, r.atualizados);
+            Ok(())
+        }
+        Err(ErroImportacao::Arquivo(msg)) => Err(Falha::Erro(msg)),
+        Err(ErroImportacao::Linhas(erros)) => {
+            for e in &erros {
+                eprintln!("linha {}: {}: {}", e.linha, e.campo, e.mensagem);
+            }
+            Err(Falha::Erro(format!("{} erro(s) de validação; nenhum produto foi gravado", erros.len())))
+        }
+        Err(ErroImportacao::Banco(e)) => Err(e.into()),
+    }
+}
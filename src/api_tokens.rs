@@ -0,0 +1,279 @@
+use axum::{
+    extract::{Extension, Path, State},
+    http::{Method, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sqlx::{Row, SqlitePool};
+use uuid::Uuid;
+
+use crate::password_reset::{hash_token, new_token};
+use crate::AppState;
+
+// Tokens pessoais de API (Authorization: Bearer) para scripts e integrações.
+// O token só é exibido na criação; no banco fica o SHA-256, com escopos, validade e último uso.
+const PREFIX: &str = "mkt_";
+const MAX_TOKENS_POR_USUARIO: i64 = 20;
+
+// Escopos disponíveis e o que liberam
+pub const SCOPES: &[(&str, &str)] = &[
+    ("orders:read", "Listar pedidos, itens e recibos"),
+    ("orders:write", "Finalizar e cancelar pedidos; despachar e emitir NFC-e (admin)"),
+    ("products:write", "Imagens, dados fiscais, estoque e importação/exportação do catálogo (admin)"),
+    ("reports:read", "Relatórios de vendas"),
+    ("users:read", "Listar usuários"),
+    ("webhooks:manage", "Assinaturas de webhooks, registro e reenvio de entregas (admin)"),
+    ("admin:read", "Consultas administrativas (bloqueios de login, solicitações LGPD, fila de e-mails)"),
+];
+
+// Autenticação da requisição atual por token (inserida pelo auth_middleware no lugar da sessão)
+#[derive(Clone)]
+pub struct ApiTokenAuth {
+    pub token_id: String,
+}
+
+// Escopo exigido por rota. Rotas fora da tabela (sessões, 2FA, tokens, logout, páginas)
+// não aceitam token: só a sessão do navegador gerencia a própria conta.
+pub fn required_scope(method: &Method, path: &str) -> Option<&'static str> {
+    let leitura = *method == Method::GET || *method == Method::HEAD;
+    match path {
+        "/api/pedidos" if leitura => Some("orders:read"),
+        p if p.starts_with("/api/pedidos/") && (p.ends_with("/itens") || p.contains("/recibo.")) && leitura => Some("orders:read"),
+        p if p.starts_with("/api/pedidos/") && p.ends_with("/cancelar") && *method == Method::POST => Some("orders:write"),
+        "/api/checkout" if *method == Method::POST => Some("orders:write"),
+        p if p.starts_with("/api/reports/") && leitura => Some("reports:read"),
+        "/api/users" if leitura => Some("users:read"),
+        p if p.starts_with("/api/admin/products/") => Some("products:write"),
+        p if p.starts_with("/api/admin/pedidos/") && leitura => Some("orders:read"),
+        p if p.starts_with("/api/admin/pedidos/") && *method == Method::POST => Some("orders:write"),
+        p if p.starts_with("/api/admin/webhook") => Some("webhooks:manage"),
+        "/api/admin/login-lockouts" | "/api/admin/lgpd-requests" | "/api/admin/email-outbox" if leitura => Some("admin:read"),
+        _ => None,
+    }
+}
+
+pub async fn init_api_tokens(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS api_tokens (
+            id TEXT PRIMARY KEY,
+            user_id INTEGER NOT NULL,
+            nome TEXT NOT NULL,
+            token_hash TEXT NOT NULL UNIQUE,
+            prefixo TEXT NOT NULL,
+            scopes TEXT NOT NULL,
+            expires_at TIMESTAMP NOT NULL,
+            last_used_at TIMESTAMP NULL,
+            created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            FOREIGN KEY(user_id) REFERENCES usuarios(id)
+        );
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_api_tokens_user ON api_tokens (user_id)")
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+// Valida o token (não expirado, de conta ativa) e registra o uso (no máximo uma escrita por
+// minuto). Retorna o dono, o id público e os escopos.
+pub async fn authenticate(db: &SqlitePool, token: &str) -> Result<Option<(i64, ApiTokenAuth, Vec<String>)>, sqlx::Error> {
+    if !token.starts_with(PREFIX) {
+        return Ok(None);
+    }
+    let hash = hash_token(token);
+    // Desativar ou excluir a conta já apaga os tokens; o JOIN cobre qualquer caminho que não apague
+    let row = sqlx::query(
+        r#"SELECT api_tokens.id, api_tokens.user_id, api_tokens.scopes FROM api_tokens
+           JOIN usuarios u ON u.id = api_tokens.user_id
+           WHERE api_tokens.token_hash = ? AND api_tokens.expires_at > CURRENT_TIMESTAMP
+             AND u.disabled_at IS NULL AND u.deleted_at IS NULL
+           LIMIT 1"#,
+    )
+    .bind(&hash)
+    .fetch_optional(db)
+    .await?;
+    let Some(row) = row else { return Ok(None) };
+    sqlx::query(
+        "UPDATE api_tokens SET last_used_at = CURRENT_TIMESTAMP WHERE token_hash = ? AND (last_used_at IS NULL OR last_used_at < datetime('now', '-60 seconds'))",
+    )
+    .bind(&hash)
+    .execute(db)
+    .await?;
+    let user_id: i64 = row.try_get("user_id").unwrap_or(0);
+    let token_id: String = row.try_get("id").unwrap_or_default();
+    let scopes: String = row.try_get("scopes").unwrap_or_default();
+    Ok(Some((user_id, ApiTokenAuth { token_id }, split_scopes(&scopes))))
+}
+
+fn split_scopes(raw: &str) -> Vec<String> {
+    raw.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect()
+}
+
+fn erro_interno() -> Response {
+    (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"erro": "Erro interno"}))).into_response()
+}
+
+fn invalido(campo: &str, msg: &str) -> Response {
+    (StatusCode::UNPROCESSABLE_ENTITY, Json(json!({"erro": "Dados inválidos", "campos": {campo: msg}}))).into_response()
+}
+
+#[derive(Serialize)]
+pub struct ApiTokenRow {
+    id: String,
+    nome: String,
+    prefixo: String,
+    scopes: Vec<String>,
+    created_at: String,
+    expires_at: String,
+    last_used_at: Option<String>,
+}
+
+// GET /api/me/tokens: tokens do usuário (sem o valor secreto)
+pub async fn list_tokens(State(app_state): State<AppState>, Extension(user_id): Extension<i64>) -> Response {
+    let rows = sqlx::query(
+        r#"SELECT id, nome, prefixo, scopes, created_at, expires_at, last_used_at
+           FROM api_tokens WHERE user_id = ? ORDER BY created_at DESC"#,
+    )
+    .bind(user_id)
+    .fetch_all(&app_state.db)
+    .await;
+    let rows = match rows {
+        Ok(r) => r,
+        Err(e) => {
+            eprintln!("[tokens] Erro ao listar tokens: {}", e);
+            return erro_interno();
+        }
+    };
+
+    let mut result = Vec::new();
+    for row in rows {
+        let scopes: String = row.try_get("scopes").unwrap_or_default();
+        result.push(ApiTokenRow {
+            id: row.try_get("id").unwrap_or_default(),
+            nome: row.try_get("nome").unwrap_or_default(),
+            prefixo: row.try_get("prefixo").unwrap_or_default(),
+            scopes: split_scopes(&scopes),
+            created_at: row.try_get("created_at").unwrap_or_default(),
+            expires_at: row.try_get("expires_at").unwrap_or_default(),
+            last_used_at: row.try_get("last_used_at").unwrap_or(None),
+        });
+    }
+    Json(result).into_response()
+}
+
+#[derive(Deserialize)]
+pub struct NovoTokenInput {
+    pub nome: String,
+    pub scopes: Vec<String>,
+    pub expira_em_dias: Option<i64>,
+}
+
+// POST /api/me/tokens: cria um token e devolve o valor uma única vez
+pub async fn create_token(
+    State(app_state): State<AppState>,
+    Extension(user_id): Extension<i64>,
+    Json(input): Json<NovoTokenInput>,
+) -> Response {
+    let nome = input.nome.trim();
+    if nome.is_empty() || nome.chars().count() > 100 {
+        return invalido("nome", "Informe um nome de até 100 caracteres");
+    }
+    let mut scopes: Vec<&str> = Vec::new();
+    for s in &input.scopes {
+        let Some((escopo, _)) = SCOPES.iter().find(|(nome, _)| *nome == s.trim()) else {
+            return invalido("scopes", &format!("Escopo desconhecido: {}", s));
+        };
+        if !scopes.contains(escopo) {
+            scopes.push(escopo);
+        }
+    }
+    if scopes.is_empty() {
+        return invalido("scopes", "Informe ao menos um escopo");
+    }
+    let max_dias = app_state.config.api_token_max_days;
+    let dias = input.expira_em_dias.unwrap_or(app_state.config.api_token_default_days.min(max_dias));
+    if !(1..=max_dias).contains(&dias) {
+        return invalido("expira_em_dias", &format!("Use entre 1 e {} dias", max_dias));
+    }
+
+    let total: i64 = match sqlx::query_scalar("SELECT COUNT(*) FROM api_tokens WHERE user_id = ? AND expires_at > CURRENT_TIMESTAMP")
+        .bind(user_id)
+        .fetch_one(&app_state.db)
+        .await
+    {
+        Ok(n) => n,
+        Err(e) => {
+            eprintln!("[tokens] Erro ao contar tokens: {}", e);
+            return erro_interno();
+        }
+    };
+    if total >= MAX_TOKENS_POR_USUARIO {
+        return (StatusCode::CONFLICT, Json(json!({"erro": "Limite de tokens atingido; revogue algum antes de criar outro"}))).into_response();
+    }
+
+    let token = format!("{}{}", PREFIX, new_token());
+    let id = Uuid::new_v4().simple().to_string();
+    let prefixo: String = token.chars().take(PREFIX.len() + 6).collect();
+    let scopes = scopes.join(",");
+    let row = sqlx::query(
+        r#"INSERT INTO api_tokens (id, user_id, nome, token_hash, prefixo, scopes, expires_at)
+           VALUES (?, ?, ?, ?, ?, ?, datetime('now', ?))
+           RETURNING created_at, expires_at"#,
+    )
+    .bind(&id)
+    .bind(user_id)
+    .bind(nome)
+    .bind(hash_token(&token))
+    .bind(&prefixo)
+    .bind(&scopes)
+    .bind(format!("+{} days", dias))
+    .fetch_one(&app_state.db)
+    .await;
+    match row {
+        Ok(r) => {
+            println!("[tokens] Usuário {} criou o token {} ({})", user_id, id, scopes);
+            (
+                StatusCode::CREATED,
+                Json(json!({
+                    "id": id,
+                    "nome": nome,
+                    "token": token,
+                    "prefixo": prefixo,
+                    "scopes": split_scopes(&scopes),
+                    "created_at": r.try_get::<String, _>("created_at").unwrap_or_default(),
+                    "expires_at": r.try_get::<String, _>("expires_at").unwrap_or_default(),
+                })),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            eprintln!("[tokens] Erro ao criar token: {}", e);
+            erro_interno()
+        }
+    }
+}
+
+// DELETE /api/me/tokens/:id: revoga um token do próprio usuário
+pub async fn revoke_token(
+    State(app_state): State<AppState>,
+    Extension(user_id): Extension<i64>,
+    Path(id): Path<String>,
+) -> Response {
+    match sqlx::query("DELETE FROM api_tokens WHERE id = ? AND user_id = ?")
+        .bind(&id)
+        .bind(user_id)
+        .execute(&app_state.db)
+        .await
+    {
+        Ok(r) if r.rows_affected() == 0 => (StatusCode::NOT_FOUND, Json(json!({"erro": "Token não encontrado"}))).into_response(),
+        Ok(_) => Json(json!({"status": "ok", "mensagem": "Token revogado"})).into_response(),
+        Err(e) => {
+            eprintln!("[tokens] Erro ao revogar token: {}", e);
+            erro_interno()
+        }
+    }
+}
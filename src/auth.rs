@@ -1,17 +1,19 @@
 use axum::{
-    extract::State,
+    extract::{ConnectInfo, State},
     response::{IntoResponse, Json, Response, Redirect},
     http::{StatusCode, HeaderMap},
 };
-use axum::http::header::SET_COOKIE;
+use axum::http::header::{self, RETRY_AFTER, SET_COOKIE, USER_AGENT};
 use axum::middleware::Next;
 use axum::body::Body;
 use axum::http::Request;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use sqlx::{Row};
-use uuid::Uuid;
+use std::net::SocketAddr;
+use std::sync::OnceLock;
 
+use crate::{api_tokens, email_verification, login_guard, password_policy, profile, sessions, two_factor};
 use crate::AppState;
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -19,6 +21,7 @@ pub struct Usuario {
     pub id: i64,
     pub nome: String,
     pub email: String,
+    pub papel: String,
 }
 
 #[derive(Deserialize)]
@@ -34,17 +37,23 @@ pub struct LoginInput {
     pub senha: String,
 }
 
-pub async fn register_user(State(app_state): State<AppState>, Json(input): Json<RegisterInput>) -> impl IntoResponse {
+pub async fn register_user(
+    State(app_state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Json(input): Json<RegisterInput>,
+) -> impl IntoResponse {
     let nome = input.nome.trim();
-    let email = input.email.trim();
-    let senha = input.senha.trim();
+    let email = password_policy::normalize_email(&input.email);
+    // A senha é usada exatamente como digitada (sem trim)
+    let senha = input.senha.as_str();
 
-    if nome.is_empty() || email.is_empty() || senha.is_empty() {
-        return (StatusCode::BAD_REQUEST, Json(json!({"erro": "Dados inválidos"}))).into_response();
+    let erros = password_policy::validate_registration(&app_state.config, nome, &email, senha);
+    if !erros.is_empty() {
+        return (StatusCode::UNPROCESSABLE_ENTITY, Json(json!({"erro": "Dados inválidos", "campos": erros}))).into_response();
     }
 
-    match sqlx::query("SELECT 1 FROM usuarios WHERE email = ? LIMIT 1")
-        .bind(email)
+    match sqlx::query("SELECT 1 FROM usuarios WHERE lower(email) = ? LIMIT 1")
+        .bind(&email)
         .fetch_optional(&app_state.db)
         .await
     {
@@ -66,28 +75,78 @@ pub async fn register_user(State(app_state): State<AppState>, Json(input): Json<
         }
     };
 
-    match sqlx::query("INSERT INTO usuarios (nome, email, senha_hash) VALUES (?, ?, ?)")
+    let user_id = match sqlx::query("INSERT INTO usuarios (nome, email, senha_hash) VALUES (?, ?, ?)")
         .bind(nome)
-        .bind(email)
+        .bind(&email)
         .bind(hash)
         .execute(&app_state.db)
         .await
     {
-        Ok(_) => (StatusCode::OK, Json(json!({"status": "ok", "mensagem": "Usuário cadastrado com sucesso"}))).into_response(),
+        Ok(r) => r.last_insert_rowid(),
         Err(e) => {
             eprintln!("[auth] Erro ao salvar usuário: {}", e);
-            (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"erro": "Erro interno"}))).into_response()
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"erro": "Erro interno"}))).into_response();
         }
+    };
+
+    // Conta criada sem verificação: o link segue por e-mail (falha no envio permite reenviar depois)
+    if let Err(e) = email_verification::send_verification(&app_state, user_id, &email, &addr.ip().to_string()).await {
+        eprintln!("[mail] Falha ao enviar verificação de e-mail: {}", e);
     }
+    (
+        StatusCode::OK,
+        Json(json!({"status": "ok", "mensagem": "Usuário cadastrado com sucesso. Confirme seu e-mail pelo link enviado"})),
+    )
+        .into_response()
+}
+
+// Resposta única para e-mail inexistente e senha incorreta (não revela quais contas existem)
+fn credenciais_invalidas() -> Response {
+    (StatusCode::UNAUTHORIZED, Json(json!({"autenticado": false, "erro": "E-mail ou senha inválidos"}))).into_response()
 }
 
-pub async fn login_user(State(app_state): State<AppState>, Json(input): Json<LoginInput>) -> impl IntoResponse {
-    let email = input.email.trim();
-    let senha = input.senha.trim();
+pub(crate) fn muitas_tentativas(segundos: i64) -> Response {
+    (
+        StatusCode::TOO_MANY_REQUESTS,
+        [(RETRY_AFTER, segundos.to_string())],
+        Json(json!({
+            "autenticado": false,
+            "erro": format!("Muitas tentativas. Tente novamente em {} segundos", segundos),
+            "retry_after": segundos
+        })),
+    )
+        .into_response()
+}
+
+// Hash descartável para que e-mails inexistentes custem o mesmo tempo que senhas erradas
+fn dummy_hash() -> &'static str {
+    static HASH: OnceLock<String> = OnceLock::new();
+    HASH.get_or_init(|| bcrypt::hash("senha-inexistente", 12).unwrap_or_default())
+}
+
+pub async fn login_user(
+    State(app_state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Json(input): Json<LoginInput>,
+) -> impl IntoResponse {
+    let email = password_policy::normalize_email(&input.email);
+    let senha = input.senha.as_str();
+    let ip = addr.ip().to_string();
+
+    // Conta ou IP bloqueados: nem verifica a senha
+    match login_guard::locked_for(&app_state.db, &app_state.config, &email, &ip).await {
+        Ok(Some(restante)) => return muitas_tentativas(restante),
+        Ok(None) => {}
+        Err(e) => {
+            eprintln!("[auth] Erro ao verificar bloqueio: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"erro": "Erro interno"}))).into_response();
+        }
+    }
 
     // Buscar usuário
-    let row = match sqlx::query("SELECT id, nome, email, senha_hash FROM usuarios WHERE email = ? LIMIT 1")
-        .bind(email)
+    let row = match sqlx::query("SELECT id, nome, email, senha_hash, papel FROM usuarios WHERE lower(email) = ? LIMIT 1")
+        .bind(&email)
         .fetch_optional(&app_state.db)
         .await
     {
@@ -98,47 +157,134 @@ pub async fn login_user(State(app_state): State<AppState>, Json(input): Json<Log
         }
     };
 
-    if row.is_none() {
-        return (StatusCode::NOT_FOUND, Json(json!({"erro": "Usuário não encontrado"}))).into_response();
-    }
-    let row = row.unwrap();
-    let id: i64 = row.try_get("id").unwrap_or(0);
-    let nome: String = row.try_get("nome").unwrap_or_default();
-    let email_db: String = row.try_get("email").unwrap_or_default();
-    let senha_hash: String = row.try_get("senha_hash").unwrap_or_default();
+    let senha_hash: String = match &row {
+        Some(r) => r.try_get("senha_hash").unwrap_or_default(),
+        None => dummy_hash().to_string(),
+    };
 
-    match bcrypt::verify(senha, &senha_hash) {
-        Ok(true) => {
-            // Limpar sessões expiradas
-            let _ = cleanup_sessions(&app_state).await;
-
-            // Criar sessão de 24h
-            let sid = Uuid::new_v4().to_string();
-            let ins = sqlx::query("INSERT INTO sessions (id, user_id, expires_at) VALUES (?, ?, datetime('now','+1 day'))")
-                .bind(&sid)
-                .bind(id)
-                .execute(&app_state.db)
-                .await;
-            if let Err(e) = ins {
-                eprintln!("[auth] Erro ao criar sessão: {}", e);
+    // Contas antigas tiveram a senha gravada após trim: aceitar também a forma aparada
+    let verified = match bcrypt::verify(senha, &senha_hash) {
+        Ok(false) if senha.trim() != senha => bcrypt::verify(senha.trim(), &senha_hash),
+        other => other,
+    };
+    let verified = match verified {
+        Ok(v) => v && row.is_some(),
+        Err(e) => {
+            eprintln!("[auth] Erro ao verificar hash: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"erro": "Erro interno"}))).into_response();
+        }
+    };
+
+    // Verificação em duas etapas ativa: a sessão só é criada em /api/login/2fa
+    let totp_ativo = match &row {
+        Some(r) if verified => match two_factor::is_enabled(&app_state.db, r.try_get("id").unwrap_or(0)).await {
+            Ok(v) => v,
+            Err(e) => {
+                eprintln!("[auth] Erro ao consultar 2FA: {}", e);
                 return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"erro": "Erro interno"}))).into_response();
             }
-            println!("Sessão criada para usuário {}: {}", id, sid);
+        },
+        _ => false,
+    };
+
+    // Com 2FA, a senha certa ainda não é um login bem-sucedido: o sucesso (que zera a contagem
+    // de falhas da conta e do IP) só é registrado quando o segundo fator confere
+    let bloqueio = if totp_ativo {
+        None
+    } else {
+        match login_guard::record_attempt(&app_state.db, &app_state.config, &email, &ip, verified).await {
+            Ok(b) => b,
+            Err(e) => {
+                eprintln!("[auth] Erro ao registrar tentativa de login: {}", e);
+                return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"erro": "Erro interno"}))).into_response();
+            }
+        }
+    };
+    let row = match row {
+        Some(r) if verified => r,
+        _ => {
+            return match bloqueio {
+                Some(segundos) => muitas_tentativas(segundos),
+                None => credenciais_invalidas(),
+            };
+        }
+    };
+
+    let usuario = Usuario {
+        id: row.try_get("id").unwrap_or(0),
+        nome: row.try_get("nome").unwrap_or_default(),
+        email: row.try_get("email").unwrap_or_default(),
+        papel: row.try_get("papel").unwrap_or_default(),
+    };
+
+    if totp_ativo {
+        return match two_factor::create_challenge(&app_state.db, usuario.id, &ip).await {
+            Ok(desafio) => (
+                StatusCode::OK,
+                Json(json!({"autenticado": false, "requer_2fa": true, "desafio": desafio})),
+            )
+                .into_response(),
+            Err(e) => {
+                eprintln!("[auth] Erro ao criar desafio 2FA: {}", e);
+                (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"erro": "Erro interno"}))).into_response()
+            }
+        };
+    }
+
+    iniciar_sessao(&app_state, &headers, &ip, usuario, false).await
+}
 
-            let cookie = format!("session_id={}; HttpOnly; SameSite=Strict; Path=/; Max-Age=86400", sid);
-            let usuario = Usuario { id, nome, email: email_db };
-            (StatusCode::OK, [(SET_COOKIE, cookie)], Json(json!({"autenticado": true, "usuario": usuario}))).into_response()
+// Cria a sessão de um usuário já autenticado (senha e, se houver, segundo fator) e monta a resposta do login
+pub(crate) async fn iniciar_sessao(app_state: &AppState, headers: &HeaderMap, ip: &str, usuario: Usuario, totp_ativo: bool) -> Response {
+    // Conta desativada pelo administrador: credenciais corretas, mas sem sessão
+    match profile::is_disabled(&app_state.db, usuario.id).await {
+        Ok(false) => {}
+        Ok(true) => {
+            return (StatusCode::FORBIDDEN, Json(json!({"autenticado": false, "erro": "Conta desativada"}))).into_response();
         }
-        Ok(false) => (StatusCode::UNAUTHORIZED, Json(json!({"autenticado": false, "erro": "Senha incorreta"}))).into_response(),
         Err(e) => {
-            eprintln!("[auth] Erro ao verificar hash: {}", e);
-            (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"erro": "Erro interno"}))).into_response()
+            eprintln!("[auth] Erro ao verificar conta: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"erro": "Erro interno"}))).into_response();
         }
     }
+
+    // Limpar sessões expiradas
+    let _ = cleanup_sessions(app_state).await;
+
+    // Rotação: uma sessão anterior enviada neste navegador é descartada, nunca reaproveitada
+    if let Some(anterior) = headers.get("cookie").and_then(|v| v.to_str().ok()).and_then(|c| extract_cookie(c, "session_id")) {
+        let _ = sessions::delete_session(&app_state.db, &app_state.config, &anterior).await;
+    }
+
+    // Criar sessão (expiração deslizante com teto absoluto), registrando dispositivo e IP
+    let user_agent = headers.get(USER_AGENT).and_then(|v| v.to_str().ok());
+    let sessao = match sessions::create_session(&app_state.db, &app_state.config, usuario.id, user_agent, ip).await {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("[auth] Erro ao criar sessão: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"erro": "Erro interno"}))).into_response();
+        }
+    };
+    println!("Sessão criada para usuário {}", usuario.id);
+
+    // Admin sem 2FA com cadastro obrigatório: entra, mas a área administrativa fica bloqueada até ativar
+    let cadastro_2fa_obrigatorio = app_state.config.admin_require_2fa && usuario.papel == "admin" && !totp_ativo;
+    let cookie = sessions::session_cookie(&app_state.config, headers, &sessao.cookie_value);
+    (
+        StatusCode::OK,
+        [(SET_COOKIE, cookie)],
+        Json(json!({
+            "autenticado": true,
+            "usuario": usuario,
+            "csrf_token": sessao.csrf_token,
+            "cadastro_2fa_obrigatorio": cadastro_2fa_obrigatorio
+        })),
+    )
+        .into_response()
 }
 
 pub async fn list_users(State(app_state): State<AppState>) -> impl IntoResponse {
-    let rows = match sqlx::query("SELECT id, nome, email FROM usuarios ORDER BY id")
+    let rows = match sqlx::query("SELECT id, nome, email, papel FROM usuarios WHERE deleted_at IS NULL ORDER BY id")
         .fetch_all(&app_state.db)
         .await
     {
@@ -154,7 +300,8 @@ pub async fn list_users(State(app_state): State<AppState>) -> impl IntoResponse
         let id: i64 = row.try_get("id").unwrap_or(0);
         let nome: String = row.try_get("nome").unwrap_or_default();
         let email: String = row.try_get("email").unwrap_or_default();
-        users.push(Usuario { id, nome, email });
+        let papel: String = row.try_get("papel").unwrap_or_default();
+        users.push(Usuario { id, nome, email, papel });
     }
     Json(users).into_response()
 }
@@ -172,6 +319,35 @@ pub async fn auth_middleware(State(app_state): State<AppState>, mut req: Request
         return next.run(req).await;
     }
 
+    // Token de API (Authorization: Bearer): substitui o cookie e vale só para as rotas do escopo
+    let bearer = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(|t| t.trim().to_string());
+    if let Some(token) = bearer {
+        return match api_tokens::authenticate(&app_state.db, &token).await {
+            Ok(Some((user_id, auth, scopes))) => {
+                let Some(escopo) = api_tokens::required_scope(req.method(), path) else {
+                    return (StatusCode::FORBIDDEN, Json(json!({"erro":"rota não disponível para tokens de API"}))).into_response();
+                };
+                if !scopes.iter().any(|s| s == escopo) {
+                    eprintln!("[auth] Token {} sem o escopo {}", auth.token_id, escopo);
+                    return (StatusCode::FORBIDDEN, Json(json!({"erro":"token sem permissão para esta rota","escopo_necessario":escopo}))).into_response();
+                }
+                req.extensions_mut().insert(user_id);
+                req.extensions_mut().insert(auth);
+                next.run(req).await
+            }
+            Ok(None) => (StatusCode::UNAUTHORIZED, Json(json!({"erro":"token inválido ou expirado"}))).into_response(),
+            Err(e) => {
+                eprintln!("[auth] Erro ao verificar token: {}", e);
+                (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"erro":"Erro interno"}))).into_response()
+            }
+        };
+    }
+
     // Ler cookie
     let cookie = req
         .headers()
@@ -182,15 +358,10 @@ pub async fn auth_middleware(State(app_state): State<AppState>, mut req: Request
 
     // Verificação de sessão
     if let Some(sid) = session_id {
-        let row = sqlx::query("SELECT user_id FROM sessions WHERE id = ? AND (expires_at IS NULL OR expires_at > CURRENT_TIMESTAMP) LIMIT 1")
-            .bind(&sid)
-            .fetch_optional(&app_state.db)
-            .await;
-
-        match row {
-            Ok(Some(r)) => {
-                let user_id: i64 = r.try_get("user_id").unwrap_or(0);
+        match sessions::touch_session(&app_state.db, &app_state.config, &sid).await {
+            Ok(Some((user_id, atual))) => {
                 req.extensions_mut().insert(user_id);
+                req.extensions_mut().insert(atual);
                 return next.run(req).await;
             }
             Ok(None) => {}
@@ -207,7 +378,38 @@ pub async fn auth_middleware(State(app_state): State<AppState>, mut req: Request
     Redirect::to("/login").into_response()
 }
 
-fn extract_cookie(cookie: &str, name: &str) -> Option<String> {
+// Middleware das rotas /api/admin: roda depois do auth_middleware (que injeta o user_id)
+// e exige papel admin
+pub async fn admin_middleware(State(app_state): State<AppState>, req: Request<Body>, next: Next) -> Response {
+    let Some(user_id) = req.extensions().get::<i64>().copied() else {
+        return (StatusCode::UNAUTHORIZED, Json(json!({"erro":"não autenticado"}))).into_response();
+    };
+
+    let papel = sqlx::query("SELECT papel, totp_enabled FROM usuarios WHERE id = ? LIMIT 1")
+        .bind(user_id)
+        .fetch_optional(&app_state.db)
+        .await;
+    match papel {
+        Ok(Some(r)) if r.try_get::<String, _>("papel").unwrap_or_default() == "admin" => {
+            // ADMIN_REQUIRE_2FA: administradores sem 2FA ativo precisam se cadastrar antes
+            if app_state.config.admin_require_2fa && r.try_get::<i64, _>("totp_enabled").unwrap_or(0) != 1 {
+                return (
+                    StatusCode::FORBIDDEN,
+                    Json(json!({"erro":"ative a verificação em duas etapas para acessar a área administrativa","requer_cadastro_2fa":true})),
+                )
+                    .into_response();
+            }
+            next.run(req).await
+        }
+        Ok(_) => (StatusCode::FORBIDDEN, Json(json!({"erro":"acesso restrito a administradores"}))).into_response(),
+        Err(e) => {
+            eprintln!("[auth] Erro ao verificar papel: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"erro":"Erro interno"}))).into_response()
+        }
+    }
+}
+
+pub(crate) fn extract_cookie(cookie: &str, name: &str) -> Option<String> {
     for part in cookie.split(';') {
         let kv = part.trim();
         if let Some((k, v)) = kv.split_once('=') {
@@ -221,7 +423,7 @@ fn extract_cookie(cookie: &str, name: &str) -> Option<String> {
 
 // Limpa sessões expiradas
 async fn cleanup_sessions(app_state: &AppState) -> Result<(), sqlx::Error> {
-    let _ = sqlx::query("DELETE FROM sessions WHERE expires_at IS NOT NULL AND expires_at <= CURRENT_TIMESTAMP")
+    let _ = sqlx::query("DELETE FROM sessions WHERE (expires_at IS NOT NULL AND expires_at <= CURRENT_TIMESTAMP) OR absolute_expires_at <= CURRENT_TIMESTAMP")
         .execute(&app_state.db)
         .await?;
     Ok(())
@@ -230,11 +432,11 @@ async fn cleanup_sessions(app_state: &AppState) -> Result<(), sqlx::Error> {
 // Retorna usuário autenticado
 pub async fn auth_me(State(app_state): State<AppState>, headers: HeaderMap) -> impl IntoResponse {
     let cookie = headers.get("cookie").and_then(|v| v.to_str().ok()).unwrap_or("");
-    let sid = match extract_cookie(cookie, "session_id") { Some(s) => s, None => {
+    let sid = match extract_cookie(cookie, "session_id").and_then(|c| sessions::session_key(&app_state.config, &c)) { Some(s) => s, None => {
         return (StatusCode::UNAUTHORIZED, Json(json!({"erro":"não autenticado"}))).into_response();
     }};
 
-    let row = match sqlx::query("SELECT u.id, u.nome, u.email FROM sessions s JOIN usuarios u ON u.id = s.user_id WHERE s.id = ? AND (s.expires_at IS NULL OR s.expires_at > CURRENT_TIMESTAMP) LIMIT 1")
+    let row = match sqlx::query("SELECT u.id, u.nome, u.email, u.papel, u.verified_at IS NOT NULL AS verificado, s.csrf_token FROM sessions s JOIN usuarios u ON u.id = s.user_id WHERE s.id = ? AND s.expires_at > CURRENT_TIMESTAMP AND (s.absolute_expires_at IS NULL OR s.absolute_expires_at > CURRENT_TIMESTAMP) LIMIT 1")
         .bind(&sid)
         .fetch_optional(&app_state.db)
         .await {
@@ -248,7 +450,14 @@ pub async fn auth_me(State(app_state): State<AppState>, headers: HeaderMap) -> i
         let id: i64 = r.try_get("id").unwrap_or(0);
         let nome: String = r.try_get("nome").unwrap_or_default();
         let email: String = r.try_get("email").unwrap_or_default();
-        return (StatusCode::OK, Json(json!({"usuario": Usuario{ id, nome, email }}))).into_response();
+        let papel: String = r.try_get("papel").unwrap_or_default();
+        let verificado: bool = r.try_get("verificado").unwrap_or(false);
+        let csrf_token: String = r.try_get("csrf_token").unwrap_or_default();
+        return (
+            StatusCode::OK,
+            Json(json!({"usuario": Usuario{ id, nome, email, papel }, "verificado": verificado, "csrf_token": csrf_token})),
+        )
+            .into_response();
     }
     (StatusCode::UNAUTHORIZED, Json(json!({"erro":"não autenticado"}))).into_response()
 }
@@ -257,12 +466,9 @@ pub async fn auth_me(State(app_state): State<AppState>, headers: HeaderMap) -> i
 pub async fn logout(State(app_state): State<AppState>, headers: HeaderMap) -> impl IntoResponse {
     let cookie = headers.get("cookie").and_then(|v| v.to_str().ok()).unwrap_or("");
     if let Some(sid) = extract_cookie(cookie, "session_id") {
-        let _ = sqlx::query("DELETE FROM sessions WHERE id = ?")
-            .bind(&sid)
-            .execute(&app_state.db)
-            .await;
-        println!("Sessão {} encerrada", sid);
+        let _ = sessions::delete_session(&app_state.db, &app_state.config, &sid).await;
+        println!("Sessão encerrada");
     }
-    let clear = "session_id=; HttpOnly; SameSite=Strict; Path=/; Max-Age=0".to_string();
+    let clear = sessions::clear_cookie(&app_state.config, &headers);
     (StatusCode::OK, [(SET_COOKIE, clear)], Json(json!({"status":"ok","mensagem":"Sessão encerrada com sucesso"}))).into_response()
 }
\ No newline at end of file
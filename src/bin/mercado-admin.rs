@@ -0,0 +1,5 @@
+// Ferramenta de administração pelo terminal (ver src/admin_cli.rs)
+#[tokio::main]
+async fn main() {
+    std::process::exit(mercado_backend::admin_cli::run(std::env::args().skip(1).collect()).await);
+}
@@ -0,0 +1,207 @@
+use sqlx::{Row, SqlitePool};
+use std::io::BufRead;
+
+use crate::config::Config;
+use crate::password_policy;
+
+// Criação do primeiro administrador. Três caminhos:
+// - `mercado-backend admin create --email ... [--nome ...]` ou `mercado-admin users create-admin`
+//   (senha via ADMIN_PASSWORD ou stdin);
+// - primeira execução com ADMIN_EMAIL e ADMIN_PASSWORD definidos (só se ainda não há admin);
+// - com SEED_DEMO_ADMIN=1 (fora de produção), a conta de demonstração admin@teste.com / 123456
+//   usada nos exemplos e nos testes. Sem nenhum deles, o banco começa sem administrador.
+// Em produção (APP_ENV=production) o servidor não sobe enquanto existirem credenciais padrão.
+pub const DEMO_ADMIN_EMAIL: &str = "admin@teste.com";
+const DEMO_ADMIN_SENHA: &str = "123456";
+
+// Cria um administrador com senha validada pela política. E-mail de administrador existente
+// só troca a senha; e-mail de cliente é recusado, a menos que `promover` seja pedido
+// explicitamente (--promote), e a promoção fica registrada no log.
+pub async fn create_admin(pool: &SqlitePool, config: &Config, nome: &str, email: &str, senha: &str, promover: bool) -> Result<(), String> {
+    let email = password_policy::normalize_email(email);
+    if !password_policy::is_valid_email(&email) {
+        return Err("E-mail inválido".to_string());
+    }
+    if let Some(msg) = password_policy::check_password(config, senha, &email) {
+        return Err(msg);
+    }
+    let db_err = |e: sqlx::Error| format!("Erro ao gravar administrador: {}", e);
+    // Conta existente com outro papel (cliente): só com promoção explícita
+    let promovida = sqlx::query("SELECT id, papel FROM usuarios WHERE email = ? AND papel != 'admin'")
+        .bind(&email)
+        .fetch_optional(pool)
+        .await
+        .map_err(db_err)?
+        .map(|r| (r.try_get::<i64, _>("id").unwrap_or(0), r.try_get::<String, _>("papel").unwrap_or_default()));
+    if let (Some((_, papel)), false) = (&promovida, promover) {
+        return Err(format!(
+            "{} já é uma conta com papel {}; use --promote para promovê-la a administrador (a senha será trocada)",
+            email, papel
+        ));
+    }
+
+    let hash = bcrypt::hash(senha, 12).map_err(|e| format!("Falha ao gerar hash da senha: {}", e))?;
+    sqlx::query(
+        r#"INSERT INTO usuarios (nome, email, senha_hash, papel, verified_at)
+           VALUES (?, ?, ?, 'admin', CURRENT_TIMESTAMP)
+           ON CONFLICT(email) DO UPDATE SET senha_hash = excluded.senha_hash, papel = 'admin',
+               verified_at = COALESCE(usuarios.verified_at, CURRENT_TIMESTAMP)"#,
+    )
+    .bind(nome)
+    .bind(&email)
+    .bind(&hash)
+    .execute(pool)
+    .await
+    .map_err(db_err)?;
+    if let Some((id, papel)) = promovida {
+        println!("[bootstrap] Conta {} ({}) promovida de {} a administrador; senha trocada", id, email, papel);
+    }
+    // Senha trocada: sessões antigas dessa conta deixam de valer
+    let _ = sqlx::query("DELETE FROM sessions WHERE user_id = (SELECT id FROM usuarios WHERE email = ?)")
+        .bind(&email)
+        .execute(pool)
+        .await;
+    Ok(())
+}
+
+// Primeira execução: ADMIN_EMAIL/ADMIN_PASSWORD (e ADMIN_NOME opcional) quando não há admin
+pub async fn admin_from_env(pool: &SqlitePool, config: &Config) -> Result<(), sqlx::Error> {
+    let (Ok(email), Ok(senha)) = (std::env::var("ADMIN_EMAIL"), std::env::var("ADMIN_PASSWORD")) else {
+        return Ok(());
+    };
+    let existe = sqlx::query("SELECT 1 FROM usuarios WHERE papel = 'admin' LIMIT 1")
+        .fetch_optional(pool)
+        .await?;
+    if existe.is_some() {
+        return Ok(());
+    }
+    let nome = std::env::var("ADMIN_NOME").unwrap_or_else(|_| "Admin".to_string());
+    match create_admin(pool, config, &nome, &email, &senha, false).await {
+        Ok(()) => println!("Administrador {} criado a partir de ADMIN_EMAIL", password_policy::normalize_email(&email)),
+        Err(msg) => eprintln!("[bootstrap] ADMIN_EMAIL/ADMIN_PASSWORD ignorados: {}", msg),
+    }
+    Ok(())
+}
+
+// Administrador inicial ao subir o servidor: ADMIN_EMAIL/ADMIN_PASSWORD e, só com
+// SEED_DEMO_ADMIN fora de produção, a conta de demonstração
+pub async fn contas_iniciais(pool: &SqlitePool, config: &Config) -> Result<(), sqlx::Error> {
+    admin_from_env(pool, config).await?;
+    if config.seed_demo_admin {
+        if config.production {
+            eprintln!("[bootstrap] SEED_DEMO_ADMIN ignorado com APP_ENV=production");
+        } else {
+            seed_demo_admin(pool).await?;
+        }
+    }
+    Ok(())
+}
+
+// Conta de demonstração (SEED_DEMO_ADMIN=1, somente fora de produção)
+async fn seed_demo_admin(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    let exists_admin = sqlx::query("SELECT 1 FROM usuarios WHERE email = ? LIMIT 1")
+        .bind(DEMO_ADMIN_EMAIL)
+        .fetch_optional(pool)
+        .await?;
+    if exists_admin.is_none() {
+        let hash = match bcrypt::hash(DEMO_ADMIN_SENHA, 12) {
+            Ok(h) => h,
+            Err(e) => {
+                eprintln!("[bootstrap] Falha ao gerar hash bcrypt para admin de demonstração: {}", e);
+                return Ok(());
+            }
+        };
+        sqlx::query("INSERT INTO usuarios (nome, email, senha_hash, papel, verified_at) VALUES (?, ?, ?, 'admin', CURRENT_TIMESTAMP)")
+            .bind("Admin")
+            .bind(DEMO_ADMIN_EMAIL)
+            .bind(&hash)
+            .execute(pool)
+            .await?;
+        println!("Usuário {} criado automaticamente (ambiente de desenvolvimento)", DEMO_ADMIN_EMAIL);
+    } else {
+        // Bancos criados antes da coluna papel: promover o admin fixo
+        sqlx::query("UPDATE usuarios SET papel = 'admin' WHERE email = ? AND papel != 'admin'")
+            .bind(DEMO_ADMIN_EMAIL)
+            .execute(pool)
+            .await?;
+    }
+    Ok(())
+}
+
+// Credenciais padrão ainda ativas: admin de demonstração com a senha conhecida ou
+// senhas gravadas sem hash (fallback antigo do seed). Contas excluídas não têm senha.
+pub async fn default_credentials(pool: &SqlitePool) -> Result<Vec<String>, sqlx::Error> {
+    let rows = sqlx::query("SELECT email, senha_hash FROM usuarios WHERE deleted_at IS NULL").fetch_all(pool).await?;
+    let mut encontrados = Vec::new();
+    for row in rows {
+        let email: String = row.try_get("email").unwrap_or_default();
+        let hash: String = row.try_get("senha_hash").unwrap_or_default();
+        let sem_hash = !hash.starts_with("$2");
+        let demo = email == DEMO_ADMIN_EMAIL && bcrypt::verify(DEMO_ADMIN_SENHA, &hash).unwrap_or(false);
+        if sem_hash || demo {
+            encontrados.push(email);
+        }
+    }
+    Ok(encontrados)
+}
+
+// Senha do administrador pela linha de comando: ADMIN_PASSWORD ou uma linha da entrada padrão
+pub fn ler_senha() -> Option<String> {
+    if let Ok(s) = std::env::var("ADMIN_PASSWORD") {
+        return Some(s);
+    }
+    eprintln!("Senha do administrador (uma linha):");
+    let mut linha = String::new();
+    std::io::stdin().lock().read_line(&mut linha).ok()?;
+    Some(linha.trim_end_matches(['\r', '\n']).to_string())
+}
+
+const USO: &str = "Uso: mercado-backend admin create --email <email> [--nome <nome>] [--promote]\n\
+                   A senha é lida de ADMIN_PASSWORD ou da entrada padrão.\n\
+                   --promote promove (e troca a senha de) uma conta de cliente já existente.";
+
+// Subcomando `admin create`. Retorna o código de saída do processo.
+pub async fn run_admin_cli(pool: &SqlitePool, config: &Config, args: &[String]) -> i32 {
+    if args.first().map(String::as_str) != Some("create") {
+        eprintln!("{}", USO);
+        return 2;
+    }
+    let mut email = None;
+    let mut nome = "Admin".to_string();
+    let mut promover = false;
+    let mut it = args[1..].iter();
+    while let Some(arg) = it.next() {
+        if arg == "--promote" {
+            promover = true;
+            continue;
+        }
+        match (arg.as_str(), it.next()) {
+            ("--email", Some(v)) => email = Some(v.clone()),
+            ("--nome", Some(v)) => nome = v.clone(),
+            _ => {
+                eprintln!("{}", USO);
+                return 2;
+            }
+        }
+    }
+    let Some(email) = email else {
+        eprintln!("{}", USO);
+        return 2;
+    };
+
+    let Some(senha) = ler_senha() else {
+        eprintln!("Não foi possível ler a senha");
+        return 1;
+    };
+
+    match create_admin(pool, config, &nome, &email, &senha, promover).await {
+        Ok(()) => {
+            println!("Administrador {} pronto", password_policy::normalize_email(&email));
+            0
+        }
+        Err(msg) => {
+            eprintln!("Erro: {}", msg);
+            1
+        }
+    }
+}
@@ -0,0 +1,578 @@
+use axum::{
+    body::{Body, Bytes},
+    extract::{Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use futures_util::stream::{self, Stream};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use sqlx::sqlite::SqliteRow;
+use sqlx::{Row, SqliteConnection, SqlitePool};
+use std::collections::HashMap;
+
+use crate::{products, ApiError, AppState};
+
+// Catálogo em lote (admin): importação de produtos por CSV ou JSON e exportação do catálogo.
+// Cada linha é casada com um produto existente pelo SKU ou, sem ele, pelo EAN; sem correspondência
+// o produto é criado. A importação é tudo ou nada: com qualquer linha inválida nada é gravado.
+// Com dry_run o arquivo passa pelas mesmas consultas numa transação desfeita ao final.
+
+// Colunas da exportação; a importação aceita as mesmas (id é ignorado: o casamento é por SKU/EAN)
+pub const COLUNAS: &[&str] = &["id", "sku", "ean", "name", "price_cents", "stock", "category", "brand", "unit", "unit_size", "image_url"];
+const OBRIGATORIAS: &[&str] = &["name", "price_cents", "stock", "category", "brand", "unit", "unit_size"];
+const UNIDADES: &[&str] = &["un", "kg", "g", "L", "ml"];
+// Limite do corpo da importação e de linhas por arquivo
+pub const MAX_BYTES: usize = 10 * 1024 * 1024;
+const MAX_LINHAS: usize = 20_000;
+const LOTE_EXPORTACAO: i64 = 500;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Formato {
+    Csv,
+    Json,
+}
+
+impl Formato {
+    pub fn from_nome(nome: &str) -> Option<Self> {
+        match nome.trim().to_lowercase().as_str() {
+            "csv" => Some(Formato::Csv),
+            "json" => Some(Formato::Json),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ErroLinha {
+    pub linha: usize,
+    pub campo: String,
+    pub mensagem: String,
+}
+
+// Problema no arquivo inteiro (formato, colunas), em linhas específicas ou no banco
+pub enum ErroImportacao {
+    Arquivo(String),
+    Linhas(Vec<ErroLinha>),
+    Banco(sqlx::Error),
+}
+
+impl From<sqlx::Error> for ErroImportacao {
+    fn from(e: sqlx::Error) -> Self {
+        ErroImportacao::Banco(e)
+    }
+}
+
+#[derive(Serialize)]
+pub struct ItemImportado {
+    pub linha: usize,
+    pub acao: &'static str,
+    pub id: i64,
+    pub sku: Option<String>,
+    pub ean: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct ResultadoImportacao {
+    pub dry_run: bool,
+    pub total: usize,
+    pub criados: usize,
+    pub atualizados: usize,
+    pub itens: Vec<ItemImportado>,
+}
+
+// Linha já validada
+struct Linha {
+    numero: usize,
+    sku: Option<String>,
+    ean: Option<String>,
+    name: String,
+    price_cents: i64,
+    stock: i64,
+    category: String,
+    brand: String,
+    unit: String,
+    unit_size: f64,
+    image_url: Option<String>,
+}
+
+type Campos = HashMap<String, String>;
+
+// GTIN-8, 12, 13 ou 14 com dígito verificador (pesos 3 e 1 a partir da direita)
+pub fn ean_valido(ean: &str) -> bool {
+    if ![8, 12, 13, 14].contains(&ean.len()) || !ean.bytes().all(|b| b.is_ascii_digit()) {
+        return false;
+    }
+    let digitos: Vec<u32> = ean.bytes().map(|b| (b - b'0') as u32).collect();
+    let (corpo, dv) = digitos.split_at(digitos.len() - 1);
+    let soma: u32 = corpo.iter().rev().enumerate().map(|(i, d)| if i % 2 == 0 { d * 3 } else { *d }).sum();
+    (10 - soma % 10) % 10 == dv[0]
+}
+
+fn verificar_colunas(colunas: &[String], exigir_todas: bool) -> Result<(), ErroImportacao> {
+    for (i, c) in colunas.iter().enumerate() {
+        if !COLUNAS.contains(&c.as_str()) {
+            return Err(ErroImportacao::Arquivo(format!("Coluna desconhecida: {} (use {})", c, COLUNAS.join(", "))));
+        }
+        if colunas[..i].contains(c) {
+            return Err(ErroImportacao::Arquivo(format!("Coluna repetida: {}", c)));
+        }
+    }
+    if exigir_todas {
+        let faltando: Vec<&str> = OBRIGATORIAS.iter().copied().filter(|c| !colunas.iter().any(|x| x == c)).collect();
+        if !faltando.is_empty() {
+            return Err(ErroImportacao::Arquivo(format!("Colunas obrigatórias ausentes: {}", faltando.join(", "))));
+        }
+        if !colunas.iter().any(|c| c == "sku" || c == "ean") {
+            return Err(ErroImportacao::Arquivo("Informe a coluna sku ou ean".to_string()));
+        }
+    }
+    Ok(())
+}
+
+// CSV com cabeçalho, separado por vírgula ou ponto e vírgula (planilhas em português); a linha
+// de cada registro é a do arquivo (o cabeçalho é a linha 1)
+fn ler_csv(dados: &[u8]) -> Result<Vec<(usize, Campos)>, ErroImportacao> {
+    let texto = std::str::from_utf8(dados).map_err(|_| ErroImportacao::Arquivo("O arquivo deve estar em UTF-8".to_string()))?;
+    let texto = texto.strip_prefix('\u{feff}').unwrap_or(texto);
+    let cabecalho = texto.lines().next().unwrap_or("");
+    let delimitador = if cabecalho.contains(';') && !cabecalho.contains(',') { b';' } else { b',' };
+
+    let mut leitor = csv::ReaderBuilder::new()
+        .delimiter(delimitador)
+        .trim(csv::Trim::All)
+        .from_reader(texto.as_bytes());
+    let colunas: Vec<String> = leitor
+        .headers()
+        .map_err(|e| ErroImportacao::Arquivo(format!("CSV inválido: {}", e)))?
+        .iter()
+        .map(|c| c.to_lowercase())
+        .collect();
+    verificar_colunas(&colunas, true)?;
+
+    let mut linhas = Vec::new();
+    for registro in leitor.records() {
+        let registro = registro.map_err(|e| ErroImportacao::Arquivo(format!("CSV inválido: {}", e)))?;
+        let numero = registro.position().map(|p| p.line() as usize).unwrap_or(0);
+        linhas.push((numero, colunas.iter().cloned().zip(registro.iter().map(str::to_string)).collect()));
+    }
+    Ok(linhas)
+}
+
+// Lista de objetos com as mesmas chaves do CSV; a "linha" é a posição na lista (a partir de 1)
+fn ler_json(dados: &[u8]) -> Result<Vec<(usize, Campos)>, ErroImportacao> {
+    let valor: Value = serde_json::from_slice(dados).map_err(|e| ErroImportacao::Arquivo(format!("JSON inválido: {}", e)))?;
+    let Value::Array(itens) = valor else {
+        return Err(ErroImportacao::Arquivo("Envie uma lista de produtos".to_string()));
+    };
+    let mut linhas = Vec::new();
+    for (i, item) in itens.into_iter().enumerate() {
+        let Value::Object(obj) = item else {
+            return Err(ErroImportacao::Arquivo(format!("Item {} não é um objeto", i + 1)));
+        };
+        let chaves: Vec<String> = obj.keys().cloned().collect();
+        verificar_colunas(&chaves, false)?;
+        let mut campos = Campos::new();
+        for (chave, v) in obj {
+            let texto = match v {
+                Value::String(s) => s,
+                Value::Number(n) => n.to_string(),
+                Value::Null => String::new(),
+                _ => return Err(ErroImportacao::Arquivo(format!("Item {}: {} deve ser texto ou número", i + 1, chave))),
+            };
+            campos.insert(chave, texto);
+        }
+        linhas.push((i + 1, campos));
+    }
+    Ok(linhas)
+}
+
+fn validar(numero: usize, campos: &Campos) -> Result<Linha, Vec<ErroLinha>> {
+    let mut erros = Vec::new();
+    let mut erro = |campo: &str, mensagem: &str| {
+        erros.push(ErroLinha { linha: numero, campo: campo.to_string(), mensagem: mensagem.to_string() })
+    };
+    let texto = |c: &str| campos.get(c).map(|v| v.trim()).filter(|v| !v.is_empty());
+
+    let sku = texto("sku").map(str::to_uppercase);
+    if let Some(s) = &sku {
+        if s.chars().count() > 64 || !s.chars().all(|c| c.is_ascii_alphanumeric() || "-_.".contains(c)) {
+            erro("sku", "SKU deve ter até 64 caracteres: letras, números, '-', '_' ou '.'");
+        }
+    }
+    let ean = texto("ean").map(str::to_string);
+    if let Some(e) = &ean {
+        if !ean_valido(e) {
+            erro("ean", "EAN/GTIN inválido (8, 12, 13 ou 14 dígitos com dígito verificador)");
+        }
+    }
+    if sku.is_none() && ean.is_none() {
+        erro("sku", "Informe o SKU ou o EAN");
+    }
+
+    let mut obrigatorio = |campo: &str, max: usize| -> String {
+        match texto(campo) {
+            Some(v) if v.chars().count() <= max => v.to_string(),
+            Some(_) => {
+                erro(campo, &format!("Use até {} caracteres", max));
+                String::new()
+            }
+            None => {
+                erro(campo, "Campo obrigatório");
+                String::new()
+            }
+        }
+    };
+    let name = obrigatorio("name", 200);
+    let category = obrigatorio("category", 100);
+    let brand = obrigatorio("brand", 100);
+    let unit = obrigatorio("unit", 10);
+
+    let inteiro = |campo: &str, max: i64| texto(campo).and_then(|v| v.parse::<i64>().ok()).filter(|n| (0..=max).contains(n));
+    let price_cents = inteiro("price_cents", 100_000_000);
+    if price_cents.is_none() {
+        erro("price_cents", "Preço em centavos: inteiro entre 0 e 100.000.000");
+    }
+    let stock = inteiro("stock", 1_000_000);
+    if stock.is_none() {
+        erro("stock", "Estoque deve ser inteiro entre 0 e 1.000.000");
+    }
+    if !unit.is_empty() && !UNIDADES.contains(&unit.as_str()) {
+        erro("unit", &format!("Unidade deve ser {}", UNIDADES.join(", ")));
+    }
+    let unit_size = texto("unit_size")
+        .and_then(|v| v.replace(',', ".").parse::<f64>().ok())
+        .filter(|n| n.is_finite() && *n > 0.0);
+    if unit_size.is_none() {
+        erro("unit_size", "Quantidade da embalagem deve ser maior que zero");
+    }
+    let image_url = texto("image_url").map(str::to_string);
+    if image_url.as_ref().is_some_and(|u| u.chars().count() > 500) {
+        erro("image_url", "Use até 500 caracteres");
+    }
+
+    if !erros.is_empty() {
+        return Err(erros);
+    }
+    Ok(Linha {
+        numero,
+        sku,
+        ean,
+        name,
+        price_cents: price_cents.unwrap_or(0),
+        stock: stock.unwrap_or(0),
+        category,
+        brand,
+        unit,
+        unit_size: unit_size.unwrap_or(0.0),
+        image_url,
+    })
+}
+
+async fn id_por(conn: &mut SqliteConnection, coluna: &str, valor: Option<&str>) -> Result<Option<i64>, sqlx::Error> {
+    let Some(valor) = valor else { return Ok(None) };
+    sqlx::query_scalar(&format!("SELECT id FROM produtos WHERE {} = ?", coluna))
+        .bind(valor)
+        .fetch_optional(conn)
+        .await
+}
+
+async fn atualizar(conn: &mut SqliteConnection, id: i64, l: &Linha, limite_estoque: i64) -> Result<(), sqlx::Error> {
+    let anterior: i64 = sqlx::query_scalar("SELECT stock FROM produtos WHERE id = ?").bind(id).fetch_one(&mut *conn).await?;
+    // Imagem: a do arquivo, se houver; sem ela, mantém a enviada por upload
+    sqlx::query(
+        r#"UPDATE produtos SET sku = COALESCE(?, sku), ean = COALESCE(?, ean), name = ?, price_cents = ?, stock = ?,
+               category = ?, brand = ?, unit = ?, unit_size = ?, image_url = COALESCE(?, image_url)
+           WHERE id = ?"#,
+    )
+    .bind(&l.sku)
+    .bind(&l.ean)
+    .bind(&l.name)
+    .bind(l.price_cents)
+    .bind(l.stock)
+    .bind(&l.category)
+    .bind(&l.brand)
+    .bind(&l.unit)
+    .bind(l.unit_size)
+    .bind(&l.image_url)
+    .bind(id)
+    .execute(&mut *conn)
+    .await?;
+    products::avisar_estoque_baixo(conn, id, &l.name, anterior, l.stock, limite_estoque).await
+}
+
+async fn inserir(conn: &mut SqliteConnection, l: &Linha) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar(
+        r#"INSERT INTO produtos (sku, ean, name, price_cents, stock, category, brand, unit, unit_size, image_url)
+           VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?) RETURNING id"#,
+    )
+    .bind(&l.sku)
+    .bind(&l.ean)
+    .bind(&l.name)
+    .bind(l.price_cents)
+    .bind(l.stock)
+    .bind(&l.category)
+    .bind(&l.brand)
+    .bind(&l.unit)
+    .bind(l.unit_size)
+    .bind(&l.image_url)
+    .fetch_one(conn)
+    .await
+}
+
+// Valida e aplica (ou simula, com dry_run) a importação. Usada pela API e pelo mercado-admin.
+pub async fn importar(
+    db: &SqlitePool,
+    formato: Formato,
+    dados: &[u8],
+    dry_run: bool,
+    limite_estoque: i64,
+) -> Result<ResultadoImportacao, ErroImportacao> {
+    let brutas = match formato {
+        Formato::Csv => ler_csv(dados)?,
+        Formato::Json => ler_json(dados)?,
+    };
+    if brutas.is_empty() {
+        return Err(ErroImportacao::Arquivo("Nenhum produto no arquivo".to_string()));
+    }
+    if brutas.len() > MAX_LINHAS {
+        return Err(ErroImportacao::Arquivo(format!("Use até {} produtos por arquivo", MAX_LINHAS)));
+    }
+
+    // Validação de cada linha e de códigos repetidos dentro do próprio arquivo
+    let mut erros = Vec::new();
+    let mut linhas = Vec::new();
+    let mut vistos: HashMap<(&str, String), usize> = HashMap::new();
+    for (numero, campos) in &brutas {
+        match validar(*numero, campos) {
+            Ok(l) => linhas.push(l),
+            Err(e) => erros.extend(e),
+        }
+        for campo in ["sku", "ean"] {
+            let Some(valor) = campos.get(campo).map(|v| v.trim().to_uppercase()).filter(|v| !v.is_empty()) else { continue };
+            if let Some(primeira) = vistos.get(&(campo, valor.clone())) {
+                erros.push(ErroLinha {
+                    linha: *numero,
+                    campo: campo.to_string(),
+                    mensagem: format!("{} {} repetido (linha {})", campo.to_uppercase(), valor, primeira),
+                });
+            } else {
+                vistos.insert((campo, valor), *numero);
+            }
+        }
+    }
+    if !erros.is_empty() {
+        return Err(ErroImportacao::Linhas(erros));
+    }
+
+    let mut tx = db.begin().await?;
+    let mut itens = Vec::new();
+    for l in &linhas {
+        let por_sku = id_por(&mut tx, "sku", l.sku.as_deref()).await?;
+        let por_ean = id_por(&mut tx, "ean", l.ean.as_deref()).await?;
+        let existente = match (por_sku, por_ean) {
+            (Some(a), Some(b)) if a != b => {
+                erros.push(ErroLinha {
+                    linha: l.numero,
+                    campo: "ean".to_string(),
+                    mensagem: format!("EAN já pertence a outro produto (id {})", b),
+                });
+                continue;
+            }
+            (a, b) => a.or(b),
+        };
+        // Casado só pelo EAN: o SKU da linha não renomeia o SKU que o produto já tem
+        if let (None, Some(id), Some(sku)) = (por_sku, por_ean, l.sku.as_deref()) {
+            let atual: Option<String> = sqlx::query_scalar("SELECT sku FROM produtos WHERE id = ?").bind(id).fetch_one(&mut *tx).await?;
+            if let Some(atual) = atual.filter(|a| a != sku) {
+                erros.push(ErroLinha {
+                    linha: l.numero,
+                    campo: "sku".to_string(),
+                    mensagem: format!("O produto com este EAN já tem o SKU {}", atual),
+                });
+                continue;
+            }
+        }
+        let (acao, id) = match existente {
+            Some(id) => {
+                atualizar(&mut tx, id, l, limite_estoque).await?;
+                ("atualizado", id)
+            }
+            None => ("criado", inserir(&mut tx, l).await?),
+        };
+        itens.push(ItemImportado { linha: l.numero, acao, id, sku: l.sku.clone(), ean: l.ean.clone() });
+    }
+    if !erros.is_empty() {
+        return Err(ErroImportacao::Linhas(erros));
+    }
+    if dry_run {
+        tx.rollback().await?;
+    } else {
+        tx.commit().await?;
+    }
+
+    let criados = itens.iter().filter(|i| i.acao == "criado").count();
+    Ok(ResultadoImportacao { dry_run, total: itens.len(), criados, atualizados: itens.len() - criados, itens })
+}
+
+fn valores(row: &SqliteRow) -> Vec<Value> {
+    vec![
+        json!(row.try_get::<i64, _>("id").unwrap_or(0)),
+        json!(row.try_get::<Option<String>, _>("sku").unwrap_or(None)),
+        json!(row.try_get::<Option<String>, _>("ean").unwrap_or(None)),
+        json!(row.try_get::<String, _>("name").unwrap_or_default()),
+        json!(row.try_get::<i64, _>("price_cents").unwrap_or(0)),
+        json!(row.try_get::<i64, _>("stock").unwrap_or(0)),
+        json!(row.try_get::<String, _>("category").unwrap_or_default()),
+        json!(row.try_get::<String, _>("brand").unwrap_or_default()),
+        json!(row.try_get::<String, _>("unit").unwrap_or_default()),
+        json!(row.try_get::<f64, _>("unit_size").unwrap_or(0.0)),
+        json!(row.try_get::<Option<String>, _>("image_url").unwrap_or(None)),
+    ]
+}
+
+fn linha_csv(campos: &[String]) -> Vec<u8> {
+    let mut escritor = csv::Writer::from_writer(Vec::new());
+    let _ = escritor.write_record(campos);
+    escritor.into_inner().unwrap_or_default()
+}
+
+fn lote(formato: Formato, rows: &[SqliteRow], primeiro: bool) -> Vec<u8> {
+    let mut saida = Vec::new();
+    for (i, row) in rows.iter().enumerate() {
+        let valores = valores(row);
+        match formato {
+            Formato::Csv => {
+                let campos: Vec<String> = valores
+                    .iter()
+                    .map(|v| match v {
+                        Value::String(s) => s.clone(),
+                        Value::Null => String::new(),
+                        outro => outro.to_string(),
+                    })
+                    .collect();
+                saida.extend(linha_csv(&campos));
+            }
+            Formato::Json => {
+                if !(primeiro && i == 0) {
+                    saida.push(b',');
+                }
+                let obj: Map<String, Value> = COLUNAS.iter().map(|c| c.to_string()).zip(valores).collect();
+                saida.extend(b"\n  ");
+                saida.extend(Value::Object(obj).to_string().into_bytes());
+            }
+        }
+    }
+    saida
+}
+
+enum Etapa {
+    Inicio,
+    Lote { ultimo: i64, primeiro: bool },
+    Fim,
+}
+
+// Catálogo em blocos de LOTE_EXPORTACAO produtos (por id), sem carregar tudo em memória
+pub fn exportar(db: SqlitePool, formato: Formato) -> impl Stream<Item = Result<Bytes, sqlx::Error>> {
+    stream::unfold(Etapa::Inicio, move |etapa| {
+        let db = db.clone();
+        async move {
+            match etapa {
+                Etapa::Inicio => {
+                    let cabecalho = match formato {
+                        Formato::Csv => linha_csv(&COLUNAS.iter().map(|c| c.to_string()).collect::<Vec<_>>()),
+                        Formato::Json => b"[".to_vec(),
+                    };
+                    Some((Ok(Bytes::from(cabecalho)), Etapa::Lote { ultimo: 0, primeiro: true }))
+                }
+                Etapa::Lote { ultimo, primeiro } => {
+                    let rows = sqlx::query(&format!("SELECT {} FROM produtos WHERE id > ? ORDER BY id LIMIT ?", COLUNAS.join(", ")))
+                        .bind(ultimo)
+                        .bind(LOTE_EXPORTACAO)
+                        .fetch_all(&db)
+                        .await;
+                    match rows {
+                        Err(e) => Some((Err(e), Etapa::Fim)),
+                        Ok(rows) if rows.is_empty() => match formato {
+                            Formato::Csv => None,
+                            Formato::Json => Some((Ok(Bytes::from_static(b"\n]\n")), Etapa::Fim)),
+                        },
+                        Ok(rows) => {
+                            let ultimo = rows.last().and_then(|r| r.try_get::<i64, _>("id").ok()).unwrap_or(ultimo);
+                            Some((Ok(Bytes::from(lote(formato, &rows, primeiro))), Etapa::Lote { ultimo, primeiro: false }))
+                        }
+                    }
+                }
+                Etapa::Fim => None,
+            }
+        }
+    })
+}
+
+#[derive(Deserialize)]
+pub struct ExportQuery {
+    pub formato: Option<String>,
+}
+
+// GET /api/admin/products/export?formato=csv|json: catálogo completo como anexo (padrão CSV)
+pub async fn export_products(State(app_state): State<AppState>, Query(q): Query<ExportQuery>) -> Result<Response, ApiError> {
+    let formato = match q.formato.as_deref() {
+        None => Formato::Csv,
+        Some(f) => Formato::from_nome(f).ok_or_else(|| ApiError::validation_error("formato", "Use csv ou json"))?,
+    };
+    let (tipo, arquivo) = match formato {
+        Formato::Csv => ("text/csv; charset=utf-8", "produtos.csv"),
+        Formato::Json => ("application/json", "produtos.json"),
+    };
+    Ok((
+        [
+            (header::CONTENT_TYPE, tipo.to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", arquivo)),
+        ],
+        Body::from_stream(exportar(app_state.db.clone(), formato)),
+    )
+        .into_response())
+}
+
+#[derive(Deserialize)]
+pub struct ImportQuery {
+    pub formato: Option<String>,
+    pub dry_run: Option<bool>,
+}
+
+// POST /api/admin/products/import?dry_run=true: corpo CSV (text/csv) ou JSON (application/json)
+pub async fn import_products(
+    State(app_state): State<AppState>,
+    Query(q): Query<ImportQuery>,
+    headers: HeaderMap,
+    corpo: Bytes,
+) -> Result<Response, ApiError> {
+    let tipo = headers.get(header::CONTENT_TYPE).and_then(|v| v.to_str().ok()).unwrap_or("").to_lowercase();
+    let formato = match q.formato.as_deref() {
+        Some(f) => Formato::from_nome(f).ok_or_else(|| ApiError::validation_error("formato", "Use csv ou json"))?,
+        None if tipo.contains("csv") => Formato::Csv,
+        None if tipo.contains("json") => Formato::Json,
+        None => return Err(ApiError::new(415, "Unsupported Media Type", "Envie text/csv ou application/json (ou use ?formato=)")),
+    };
+    let dry_run = q.dry_run.unwrap_or(false);
+
+    match importar(&app_state.db, formato, &corpo, dry_run, app_state.config.low_stock_threshold).await {
+        Ok(r) => {
+            if !dry_run {
+                println!("[catalogo] Importação: {} criados, {} atualizados", r.criados, r.atualizados);
+            }
+            Ok(Json(r).into_response())
+        }
+        Err(ErroImportacao::Arquivo(msg)) => Err(ApiError::validation_error("arquivo", &msg)),
+        Err(ErroImportacao::Linhas(erros)) => Ok((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(json!({
+                "error": format!("{} erro(s) de validação; nenhum produto foi gravado", erros.len()),
+                "field": "linhas",
+                "erros": erros,
+            })),
+        )
+            .into_response()),
+        Err(ErroImportacao::Banco(e)) => Err(ApiError::internal_server_error(&format!("Erro ao importar produtos: {}", e))),
+    }
+}
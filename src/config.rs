@@ -0,0 +1,323 @@
+use std::env;
+
+// Configuração da aplicação lida de variáveis de ambiente (com valores padrão)
+#[derive(Clone, Debug)]
+pub struct Config {
+    // APP_ENV=production: recusa subir com credenciais padrão (e ignora SEED_DEMO_ADMIN)
+    pub production: bool,
+    // SEED_DEMO_ADMIN=1: cria a conta de demonstração admin@teste.com (desligado por padrão)
+    pub seed_demo_admin: bool,
+    // Arquivo do banco SQLite (relativo ao diretório atual); o mercado-admin aceita --database
+    pub database_path: String,
+    // Janelas de entrega oferecidas em cada dia, no formato ("HH:MM", "HH:MM")
+    pub delivery_windows: Vec<(String, String)>,
+    // Quantidade máxima de pedidos por janela
+    pub delivery_slot_capacity: i64,
+    // Quantos dias à frente (incluindo hoje) ficam disponíveis para agendamento
+    pub delivery_days_ahead: i64,
+    // Tamanho máximo (bytes) de uma imagem enviada por upload
+    pub image_max_bytes: usize,
+    // Diretório (relativo à raiz servida) onde ficam as imagens enviadas
+    pub upload_dir: String,
+    // Política de senha do cadastro
+    pub password_min_length: usize,
+    pub password_reject_common: bool,
+    // Proteção do login: falhas permitidas por conta e por IP, janela de contagem e duração
+    // do bloqueio (dobra a cada falha além do limite). IPs isentos (ex.: o proxy reverso)
+    // não são contados nem bloqueados por IP; a conta continua protegida.
+    pub login_max_failures: i64,
+    pub login_max_failures_ip: i64,
+    pub login_ip_exempt: Vec<String>,
+    pub login_failure_window_secs: i64,
+    pub login_lockout_base_secs: i64,
+    pub login_lockout_max_secs: i64,
+    // Envio de e-mails: "arquivo" (grava .eml em mail_dir), "console" ou "smtp"
+    pub mail_transport: String,
+    pub mail_dir: String,
+    pub smtp: SmtpConfig,
+    // Fila de e-mails (outbox): intervalo de leitura (também usado pelos webhooks),
+    // tentativas por mensagem e espera inicial entre tentativas (dobra a cada falha, até 1 hora)
+    pub outbox_poll_secs: u64,
+    pub outbox_max_attempts: i64,
+    pub outbox_backoff_base_secs: i64,
+    // Webhooks: tentativas por entrega, espera inicial entre tentativas e timeout da requisição
+    pub webhook_max_attempts: i64,
+    pub webhook_backoff_base_secs: i64,
+    pub webhook_timeout_secs: u64,
+    // Hosts liberados como destino de webhook mesmo resolvendo para endereço interno
+    // (loopback, rede privada, link-local); vazio em produção, para desenvolvimento local
+    pub webhook_allow_private_hosts: Vec<String>,
+    // Estoque igual ou abaixo deste valor dispara product.low_stock
+    pub low_stock_threshold: i64,
+    // Endereço público da aplicação, usado nos links enviados por e-mail
+    pub app_base_url: String,
+    // Validade (minutos) do link de redefinição de senha
+    pub password_reset_ttl_mins: i64,
+    // Verificação de e-mail: validade do link (horas), intervalo mínimo entre reenvios
+    // para o mesmo e-mail e máximo de envios por IP a cada hora
+    pub email_verify_ttl_hours: i64,
+    pub verify_resend_interval_secs: i64,
+    pub verify_resend_max_per_hour: i64,
+    // Sessões: expiração por inatividade (renovada a cada uso) e duração máxima desde o login
+    pub session_idle_secs: i64,
+    pub session_max_secs: i64,
+    // Chave HMAC que assina o cookie de sessão (SESSION_SECRET ou data/session.key)
+    pub session_secret: Vec<u8>,
+    // Flag Secure do cookie: Some(true/false) fixo ou None para detectar HTTPS (X-Forwarded-Proto)
+    pub cookie_secure: Option<bool>,
+    // Origens aceitas em requisições que alteram estado (além da própria origem do servidor)
+    pub allowed_origins: Vec<String>,
+    // Exige verificação em duas etapas (TOTP) para acessar a área administrativa
+    pub admin_require_2fa: bool,
+    // Tokens de API: validade padrão e máxima (dias)
+    pub api_token_default_days: i64,
+    pub api_token_max_days: i64,
+    // Emitente e parâmetros da NFC-e
+    pub nfce: NfceConfig,
+}
+
+// Dados do emitente da NFC-e (padrão: ambiente de homologação com dados de teste)
+#[derive(Clone, Debug)]
+pub struct NfceConfig {
+    pub cnpj: String,
+    pub ie: String,
+    pub razao_social: String,
+    pub logradouro: String,
+    pub numero: String,
+    pub bairro: String,
+    pub municipio: String,
+    // Código IBGE do município
+    pub municipio_codigo: String,
+    pub uf: String,
+    pub cep: String,
+    pub serie: i64,
+    // 1 = produção, 2 = homologação
+    pub ambiente: i64,
+    // Regime tributário: 1 = Simples Nacional, 3 = regime normal
+    pub crt: i64,
+    // Fuso do emitente na data de emissão (ex.: -03:00)
+    pub utc_offset: String,
+    // Transmissor SEFAZ: "stub" (local, não envia nada)
+    pub transmissor: String,
+}
+
+// Servidor SMTP (MAIL_TRANSPORT=smtp)
+#[derive(Clone, Debug)]
+pub struct SmtpConfig {
+    pub host: String,
+    pub port: u16,
+    pub user: String,
+    pub password: String,
+    // Remetente (ex.: "Mercado Online <pedidos@mercado.com.br>")
+    pub from: String,
+    // "starttls" (padrão), "tls" (conexão já cifrada, porta 465) ou "nenhum" (relay local)
+    pub tls: String,
+}
+
+impl SmtpConfig {
+    fn from_env() -> Self {
+        let var = |name: &str, default: &str| env::var(name).map(|v| v.trim().to_string()).unwrap_or_else(|_| default.to_string());
+        Self {
+            host: var("SMTP_HOST", "localhost"),
+            port: env_i64("SMTP_PORT", 587).clamp(1, 65535) as u16,
+            user: var("SMTP_USER", ""),
+            password: env::var("SMTP_PASSWORD").unwrap_or_default(),
+            from: var("SMTP_FROM", "Mercado Online <nao-responda@mercado.local>"),
+            tls: var("SMTP_TLS", "starttls").to_lowercase(),
+        }
+    }
+}
+
+impl NfceConfig {
+    fn from_env() -> Self {
+        let var = |name: &str, default: &str| env::var(name).map(|v| v.trim().to_string()).unwrap_or_else(|_| default.to_string());
+        Self {
+            cnpj: var("NFCE_CNPJ", "11222333000181"),
+            ie: var("NFCE_IE", "111111111111"),
+            razao_social: var("NFCE_RAZAO_SOCIAL", "Mercado Online LTDA"),
+            logradouro: var("NFCE_LOGRADOURO", "Rua das Flores"),
+            numero: var("NFCE_NUMERO", "123"),
+            bairro: var("NFCE_BAIRRO", "Centro"),
+            municipio: var("NFCE_MUNICIPIO", "São Paulo"),
+            municipio_codigo: var("NFCE_MUNICIPIO_CODIGO", "3550308"),
+            uf: var("NFCE_UF", "SP").to_uppercase(),
+            cep: var("NFCE_CEP", "01001000"),
+            serie: env_i64("NFCE_SERIE", 1).clamp(0, 999),
+            ambiente: if env_i64("NFCE_AMBIENTE", 2) == 1 { 1 } else { 2 },
+            crt: if env_i64("NFCE_CRT", 1) == 3 { 3 } else { 1 },
+            utc_offset: utc_offset(&var("NFCE_UTC_OFFSET", "-03:00")),
+            transmissor: var("NFCE_TRANSMISSOR", "stub").to_lowercase(),
+        }
+    }
+}
+
+// NFCE_UTC_OFFSET no formato ±HH:MM; valor inválido volta ao padrão de Brasília
+fn utc_offset(valor: &str) -> String {
+    if crate::nfce::offset_modifier(valor).is_some() {
+        return valor.to_string();
+    }
+    eprintln!("[config] Valor inválido para NFCE_UTC_OFFSET: {:?}, usando -03:00", valor);
+    "-03:00".to_string()
+}
+
+impl Config {
+    pub fn from_env() -> Self {
+        let app_base_url = env::var("APP_BASE_URL")
+            .unwrap_or_else(|_| "http://127.0.0.1:8080".to_string())
+            .trim_end_matches('/')
+            .to_string();
+        let windows_raw = env::var("DELIVERY_WINDOWS")
+            .unwrap_or_else(|_| "08:00-10:00,10:00-12:00,14:00-16:00,16:00-18:00".to_string());
+
+        Self {
+            production: matches!(
+                env::var("APP_ENV").map(|v| v.trim().to_lowercase()).as_deref(),
+                Ok("production") | Ok("producao") | Ok("produção") | Ok("prod")
+            ),
+            seed_demo_admin: env_bool("SEED_DEMO_ADMIN", false),
+            database_path: env::var("DATABASE_PATH")
+                .map(|v| v.trim().to_string())
+                .ok()
+                .filter(|v| !v.is_empty())
+                .unwrap_or_else(|| "data/mercado.db".to_string()),
+            delivery_windows: parse_windows(&windows_raw),
+            delivery_slot_capacity: env_i64("DELIVERY_SLOT_CAPACITY", 10).max(1),
+            delivery_days_ahead: env_i64("DELIVERY_DAYS_AHEAD", 7).max(1),
+            image_max_bytes: env_i64("IMAGE_MAX_BYTES", 5 * 1024 * 1024).max(1) as usize,
+            upload_dir: env::var("UPLOAD_DIR").unwrap_or_else(|_| "images/produtos".to_string()),
+            password_min_length: env_i64("PASSWORD_MIN_LENGTH", 8).max(1) as usize,
+            password_reject_common: env_bool("PASSWORD_REJECT_COMMON", true),
+            login_max_failures: env_i64("LOGIN_MAX_FAILURES", 5).max(1),
+            login_max_failures_ip: env_i64("LOGIN_MAX_FAILURES_IP", 20).max(1),
+            login_ip_exempt: env::var("LOGIN_IP_EXEMPT")
+                .unwrap_or_default()
+                .split(',')
+                .map(|ip| ip.trim().to_string())
+                .filter(|ip| !ip.is_empty())
+                .collect(),
+            login_failure_window_secs: env_i64("LOGIN_FAILURE_WINDOW_SECS", 15 * 60).max(1),
+            login_lockout_base_secs: env_i64("LOGIN_LOCKOUT_BASE_SECS", 60).max(1),
+            login_lockout_max_secs: env_i64("LOGIN_LOCKOUT_MAX_SECS", 60 * 60).max(1),
+            mail_transport: env::var("MAIL_TRANSPORT").unwrap_or_else(|_| "arquivo".to_string()).trim().to_lowercase(),
+            mail_dir: env::var("MAIL_DIR").unwrap_or_else(|_| "data/mail".to_string()),
+            smtp: SmtpConfig::from_env(),
+            outbox_poll_secs: env_i64("OUTBOX_POLL_SECS", 2).max(1) as u64,
+            outbox_max_attempts: env_i64("OUTBOX_MAX_ATTEMPTS", 8).max(1),
+            outbox_backoff_base_secs: env_i64("OUTBOX_BACKOFF_BASE_SECS", 30).max(1),
+            webhook_max_attempts: env_i64("WEBHOOK_MAX_ATTEMPTS", 8).max(1),
+            webhook_backoff_base_secs: env_i64("WEBHOOK_BACKOFF_BASE_SECS", 30).max(1),
+            webhook_timeout_secs: env_i64("WEBHOOK_TIMEOUT_SECS", 10).max(1) as u64,
+            webhook_allow_private_hosts: env::var("WEBHOOK_ALLOW_PRIVATE_HOSTS")
+                .unwrap_or_default()
+                .split(',')
+                .map(|h| h.trim().to_lowercase())
+                .filter(|h| !h.is_empty())
+                .collect(),
+            low_stock_threshold: env_i64("LOW_STOCK_THRESHOLD", 10).max(0),
+            allowed_origins: parse_origins(&env::var("ALLOWED_ORIGINS").unwrap_or_default(), &app_base_url),
+            app_base_url,
+            admin_require_2fa: env_bool("ADMIN_REQUIRE_2FA", false),
+            api_token_default_days: env_i64("API_TOKEN_DEFAULT_DAYS", 90).max(1),
+            api_token_max_days: env_i64("API_TOKEN_MAX_DAYS", 365).max(1),
+            nfce: NfceConfig::from_env(),
+            password_reset_ttl_mins: env_i64("PASSWORD_RESET_TTL_MINS", 30).max(1),
+            email_verify_ttl_hours: env_i64("EMAIL_VERIFY_TTL_HOURS", 48).max(1),
+            verify_resend_interval_secs: env_i64("VERIFY_RESEND_INTERVAL_SECS", 60).max(1),
+            verify_resend_max_per_hour: env_i64("VERIFY_RESEND_MAX_PER_HOUR", 10).max(1),
+            session_idle_secs: env_i64("SESSION_IDLE_SECS", 24 * 60 * 60).max(60),
+            session_max_secs: env_i64("SESSION_MAX_SECS", 30 * 24 * 60 * 60).max(60),
+            session_secret: load_session_secret(),
+            cookie_secure: match env::var("COOKIE_SECURE").map(|v| v.trim().to_lowercase()) {
+                Ok(v) if v == "auto" || v.is_empty() => None,
+                Ok(_) => Some(env_bool("COOKIE_SECURE", false)),
+                Err(_) => None,
+            },
+        }
+    }
+}
+
+// Chave de assinatura das sessões: SESSION_SECRET (ao menos 32 caracteres) ou, na ausência,
+// uma chave aleatória gerada uma vez e guardada em data/session.key
+fn load_session_secret() -> Vec<u8> {
+    if let Ok(v) = env::var("SESSION_SECRET") {
+        if v.len() >= 32 {
+            return v.into_bytes();
+        }
+        eprintln!("[config] SESSION_SECRET deve ter ao menos 32 caracteres; usando data/session.key");
+    }
+    let path = std::path::Path::new("data").join("session.key");
+    if let Ok(hex_key) = std::fs::read_to_string(&path) {
+        if let Ok(key) = hex::decode(hex_key.trim()) {
+            if key.len() >= 32 {
+                return key;
+            }
+        }
+    }
+    let key: [u8; 32] = rand::random();
+    let _ = std::fs::create_dir_all("data");
+    if let Err(e) = std::fs::write(&path, hex::encode(key)) {
+        eprintln!("[config] Não foi possível gravar {}: {} (sessões não sobrevivem a reinícios)", path.display(), e);
+    }
+    key.to_vec()
+}
+
+// ALLOWED_ORIGINS separado por vírgulas; APP_BASE_URL sempre incluído
+fn parse_origins(raw: &str, app_base_url: &str) -> Vec<String> {
+    let mut origins: Vec<String> = raw
+        .split(',')
+        .map(|o| o.trim().trim_end_matches('/').to_string())
+        .filter(|o| !o.is_empty())
+        .collect();
+    origins.push(app_base_url.to_string());
+    origins
+}
+
+// Lê um inteiro do ambiente, usando o padrão se ausente ou inválido
+fn env_i64(name: &str, default: i64) -> i64 {
+    match env::var(name) {
+        Ok(v) => v.trim().parse().unwrap_or_else(|_| {
+            eprintln!("[config] Valor inválido para {}: {:?}, usando {}", name, v, default);
+            default
+        }),
+        Err(_) => default,
+    }
+}
+
+// Lê um booleano do ambiente (true/false, 1/0, sim/não)
+fn env_bool(name: &str, default: bool) -> bool {
+    match env::var(name).map(|v| v.trim().to_lowercase()) {
+        Ok(v) if ["1", "true", "sim", "yes"].contains(&v.as_str()) => true,
+        Ok(v) if ["0", "false", "nao", "não", "no"].contains(&v.as_str()) => false,
+        Ok(v) => {
+            eprintln!("[config] Valor inválido para {}: {:?}, usando {}", name, v, default);
+            default
+        }
+        Err(_) => default,
+    }
+}
+
+// Converte "08:00-10:00,10:00-12:00" em pares (início, fim), ignorando faixas mal formadas
+fn parse_windows(raw: &str) -> Vec<(String, String)> {
+    let mut windows = Vec::new();
+    for part in raw.split(',') {
+        let part = part.trim();
+        if part.is_empty() {
+            continue;
+        }
+        match part.split_once('-') {
+            Some((start, end)) if is_hhmm(start.trim()) && is_hhmm(end.trim()) && start.trim() < end.trim() => {
+                windows.push((start.trim().to_string(), end.trim().to_string()));
+            }
+            _ => eprintln!("[config] Janela de entrega inválida ignorada: {:?}", part),
+        }
+    }
+    windows
+}
+
+fn is_hhmm(s: &str) -> bool {
+    let Some((h, m)) = s.split_once(':') else { return false };
+    if h.len() != 2 || m.len() != 2 {
+        return false;
+    }
+    matches!((h.parse::<u8>(), m.parse::<u8>()), (Ok(h), Ok(m)) if h < 24 && m < 60)
+}
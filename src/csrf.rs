@@ -0,0 +1,76 @@
+use axum::{
+    body::Body,
+    extract::State,
+    http::{header, Method, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Json, Response},
+};
+use serde_json::json;
+
+use crate::api_tokens::ApiTokenAuth;
+use crate::sessions::CurrentSession;
+use crate::AppState;
+
+// Proteção CSRF das rotas autenticadas por cookie. Em métodos que alteram estado exige:
+// - Origin (ou Referer, na falta dele) de uma origem permitida, quando o navegador enviar;
+// - cabeçalho X-CSRF-Token igual ao token da sessão (entregue por /api/login e /api/auth/me).
+// Roda depois do auth_middleware, que injeta a sessão atual (ou a marca de token de API).
+pub const CSRF_HEADER: &str = "x-csrf-token";
+
+fn proibido(msg: &str) -> Response {
+    (StatusCode::FORBIDDEN, Json(json!({"erro": msg}))).into_response()
+}
+
+// "scheme://host[:porta]" de uma URL (Referer)
+fn origin_of(url: &str) -> Option<&str> {
+    let (scheme, rest) = url.split_once("://")?;
+    let end = rest.find(['/', '?', '#']).unwrap_or(rest.len());
+    Some(&url[..scheme.len() + 3 + end])
+}
+
+fn origin_allowed(app_state: &AppState, req: &Request<Body>, origin: &str) -> bool {
+    let origin = origin.trim_end_matches('/');
+    if app_state.config.allowed_origins.iter().any(|o| o.eq_ignore_ascii_case(origin)) {
+        return true;
+    }
+    // Mesma origem do próprio servidor (Host da requisição)
+    let Some(host) = req.headers().get(header::HOST).and_then(|v| v.to_str().ok()) else {
+        return false;
+    };
+    ["http://", "https://"].iter().any(|scheme| origin.eq_ignore_ascii_case(&format!("{}{}", scheme, host)))
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+pub async fn csrf_middleware(State(app_state): State<AppState>, req: Request<Body>, next: Next) -> Response {
+    // Token de API não é enviado automaticamente pelo navegador: não há CSRF a evitar
+    if matches!(*req.method(), Method::GET | Method::HEAD | Method::OPTIONS) || req.extensions().get::<ApiTokenAuth>().is_some() {
+        return next.run(req).await;
+    }
+
+    let headers = req.headers();
+    let origem = match headers.get(header::ORIGIN).and_then(|v| v.to_str().ok()) {
+        Some(o) => Some(o.to_string()),
+        None => headers
+            .get(header::REFERER)
+            .and_then(|v| v.to_str().ok())
+            .map(|r| origin_of(r).unwrap_or(r).to_string()),
+    };
+    if let Some(origem) = origem {
+        if !origin_allowed(&app_state, &req, &origem) {
+            eprintln!("[csrf] Origem recusada: {}", origem);
+            return proibido("origem não permitida");
+        }
+    }
+
+    let Some(sessao) = req.extensions().get::<CurrentSession>() else {
+        return proibido("token CSRF ausente ou inválido");
+    };
+    let enviado = req.headers().get(CSRF_HEADER).and_then(|v| v.to_str().ok()).unwrap_or("");
+    if sessao.csrf_token.is_empty() || !constant_time_eq(enviado.as_bytes(), sessao.csrf_token.as_bytes()) {
+        return proibido("token CSRF ausente ou inválido");
+    }
+    next.run(req).await
+}
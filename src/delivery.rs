@@ -0,0 +1,120 @@
+use axum::{
+    extract::State,
+    response::Json,
+};
+use serde::Serialize;
+use sqlx::{Row, SqliteConnection, SqlitePool};
+
+use crate::config::Config;
+use crate::{ApiError, AppState};
+
+// Janela de entrega com a ocupação atual
+#[derive(Serialize)]
+pub struct DeliverySlot {
+    pub id: String,
+    pub date: String,
+    pub start_time: String,
+    pub end_time: String,
+    pub capacity: i64,
+    pub reserved: i64,
+    pub available: i64,
+}
+
+// Resultado da tentativa de reservar uma janela
+pub enum Reserva {
+    Reservada,
+    Esgotada,
+    Inexistente,
+}
+
+// Garante que as janelas dos próximos dias existam na tabela (idempotente).
+// A capacidade é sempre sincronizada com a configuração atual.
+pub async fn ensure_slots(db: &SqlitePool, config: &Config) -> Result<(), sqlx::Error> {
+    for day in 0..config.delivery_days_ahead {
+        let modifier = format!("+{} day", day);
+        for (start, end) in &config.delivery_windows {
+            sqlx::query(
+                r#"INSERT INTO delivery_slots (id, slot_date, start_time, end_time, capacity)
+                   VALUES (date('now','localtime', ?1) || 'T' || ?2, date('now','localtime', ?1), ?2, ?3, ?4)
+                   ON CONFLICT(id) DO UPDATE SET capacity = excluded.capacity"#,
+            )
+            .bind(&modifier)
+            .bind(start)
+            .bind(end)
+            .bind(config.delivery_slot_capacity)
+            .execute(db)
+            .await?;
+        }
+    }
+    Ok(())
+}
+
+// Reserva uma vaga na janela. O UPDATE condicional é atômico no SQLite,
+// portanto dois checkouts concorrentes nunca ultrapassam a capacidade.
+pub async fn reserve_slot(conn: &mut SqliteConnection, slot_id: &str) -> Result<Reserva, sqlx::Error> {
+    let updated = sqlx::query(
+        r#"UPDATE delivery_slots SET reserved = reserved + 1
+           WHERE id = ? AND reserved < capacity
+             AND datetime(slot_date || ' ' || start_time) > datetime('now','localtime')"#,
+    )
+    .bind(slot_id)
+    .execute(&mut *conn)
+    .await?;
+    if updated.rows_affected() == 1 {
+        return Ok(Reserva::Reservada);
+    }
+
+    let exists = sqlx::query(
+        "SELECT 1 FROM delivery_slots WHERE id = ? AND datetime(slot_date || ' ' || start_time) > datetime('now','localtime') LIMIT 1",
+    )
+    .bind(slot_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+    Ok(if exists.is_some() { Reserva::Esgotada } else { Reserva::Inexistente })
+}
+
+// Devolve a vaga de uma janela (ex.: pedido cancelado)
+pub async fn release_slot(conn: &mut SqliteConnection, slot_id: &str) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE delivery_slots SET reserved = reserved - 1 WHERE id = ? AND reserved > 0")
+        .bind(slot_id)
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+// GET /api/delivery-slots: janelas futuras que ainda têm vagas
+pub async fn list_delivery_slots(State(app_state): State<AppState>) -> Result<Json<Vec<DeliverySlot>>, ApiError> {
+    ensure_slots(&app_state.db, &app_state.config)
+        .await
+        .map_err(|e| ApiError::internal_server_error(&format!("Erro ao preparar janelas de entrega: {}", e)))?;
+
+    let rows = sqlx::query(
+        r#"SELECT id, slot_date, start_time, end_time, capacity, reserved
+           FROM delivery_slots
+           WHERE reserved < capacity
+             AND datetime(slot_date || ' ' || start_time) > datetime('now','localtime')
+             AND slot_date < date('now','localtime', ?)
+           ORDER BY slot_date, start_time"#,
+    )
+    .bind(format!("+{} day", app_state.config.delivery_days_ahead))
+    .fetch_all(&app_state.db)
+    .await
+    .map_err(|e| ApiError::internal_server_error(&format!("Erro ao listar janelas de entrega: {}", e)))?;
+
+    let mut result = Vec::new();
+    for row in rows {
+        let capacity: i64 = row.try_get("capacity").unwrap_or(0);
+        let reserved: i64 = row.try_get("reserved").unwrap_or(0);
+        result.push(DeliverySlot {
+            id: row.try_get("id").unwrap_or_default(),
+            date: row.try_get("slot_date").unwrap_or_default(),
+            start_time: row.try_get("start_time").unwrap_or_default(),
+            end_time: row.try_get("end_time").unwrap_or_default(),
+            capacity,
+            reserved,
+            available: capacity - reserved,
+        });
+    }
+
+    Ok(Json(result))
+}
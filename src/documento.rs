@@ -0,0 +1,90 @@
+// CPF e CNPJ: validação dos dígitos verificadores, limpeza (sem máscara) e formatação.
+// Os documentos são gravados sem máscara; a formatação é só para exibição.
+// O CNPJ aceita o formato alfanumérico da Receita Federal (12 primeiras posições com
+// letras ou números, 2 dígitos verificadores numéricos), que usa o mesmo cálculo.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TipoDocumento {
+    Cpf,
+    Cnpj,
+}
+
+impl TipoDocumento {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TipoDocumento::Cpf => "cpf",
+            TipoDocumento::Cnpj => "cnpj",
+        }
+    }
+}
+
+// Remove máscara e espaços: "529.982.247-25" → "52998224725", "12.abc.345/01de-35" → "12ABC34501DE35"
+pub fn limpar(doc: &str) -> String {
+    doc.chars().filter(|c| c.is_ascii_alphanumeric()).map(|c| c.to_ascii_uppercase()).collect()
+}
+
+// Valor de cada posição no cálculo: dígitos valem 0..9 e letras, o código ASCII menos 48
+fn valores(doc: &str) -> Vec<u32> {
+    doc.bytes().map(|b| (b - b'0') as u32).collect()
+}
+
+// Dígito verificador módulo 11 com os pesos informados
+fn digito_mod11(valores: &[u32], pesos: &[u32]) -> u32 {
+    let soma: u32 = valores.iter().zip(pesos).map(|(v, p)| v * p).sum();
+    match soma % 11 {
+        0 | 1 => 0,
+        r => 11 - r,
+    }
+}
+
+// CPF sem máscara: 11 dígitos, verificadores corretos e não repetidos (111.111.111-11)
+pub fn cpf_valido(cpf: &str) -> bool {
+    if cpf.len() != 11 || !cpf.bytes().all(|b| b.is_ascii_digit()) {
+        return false;
+    }
+    let v = valores(cpf);
+    if v.iter().all(|&x| x == v[0]) {
+        return false;
+    }
+    let pesos: Vec<u32> = (2..=11).rev().collect();
+    digito_mod11(&v[..9], &pesos[1..]) == v[9] && digito_mod11(&v[..10], &pesos) == v[10]
+}
+
+// CNPJ sem máscara (numérico ou alfanumérico): 14 posições, verificadores corretos
+pub fn cnpj_valido(cnpj: &str) -> bool {
+    let b = cnpj.as_bytes();
+    if b.len() != 14
+        || !b[..12].iter().all(|c| c.is_ascii_digit() || c.is_ascii_uppercase())
+        || !b[12..].iter().all(|c| c.is_ascii_digit())
+    {
+        return false;
+    }
+    let v = valores(cnpj);
+    if v.iter().all(|&x| x == v[0]) {
+        return false;
+    }
+    const PESOS: [u32; 13] = [6, 5, 4, 3, 2, 9, 8, 7, 6, 5, 4, 3, 2];
+    digito_mod11(&v[..12], &PESOS[1..]) == v[12] && digito_mod11(&v[..13], &PESOS) == v[13]
+}
+
+// Identifica e valida um CPF ou CNPJ digitado com ou sem máscara; devolve o tipo e o valor limpo
+pub fn validar(doc: &str) -> Option<(TipoDocumento, String)> {
+    let limpo = limpar(doc);
+    if cpf_valido(&limpo) {
+        Some((TipoDocumento::Cpf, limpo))
+    } else if cnpj_valido(&limpo) {
+        Some((TipoDocumento::Cnpj, limpo))
+    } else {
+        None
+    }
+}
+
+// "52998224725" → "529.982.247-25"; "11222333000181" → "11.222.333/0001-81"
+pub fn formatar(doc: &str) -> String {
+    let d = limpar(doc);
+    match d.len() {
+        11 => format!("{}.{}.{}-{}", &d[..3], &d[3..6], &d[6..9], &d[9..]),
+        14 => format!("{}.{}.{}/{}-{}", &d[..2], &d[2..5], &d[5..8], &d[8..12], &d[12..]),
+        _ => d,
+    }
+}
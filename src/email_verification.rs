@@ -0,0 +1,266 @@
+use axum::{
+    extract::{ConnectInfo, Query, State},
+    http::{header::RETRY_AFTER, HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use serde::Deserialize;
+use serde_json::json;
+use sqlx::{Row, SqlitePool};
+use std::net::SocketAddr;
+
+use crate::mail::Email;
+use crate::password_reset::{hash_token, new_token};
+use crate::{password_policy, sessions, AppState};
+
+// Verificação de e-mail: o token segue por e-mail (no banco só o hash) e, ao ser usado,
+// preenche usuarios.verified_at. Contas não verificadas não podem finalizar pedidos.
+pub async fn init_email_verification(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS email_verifications (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            user_id INTEGER NOT NULL,
+            token_hash TEXT NOT NULL UNIQUE,
+            expires_at TIMESTAMP NOT NULL,
+            used_at TIMESTAMP,
+            created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            FOREIGN KEY(user_id) REFERENCES usuarios(id)
+        );
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    // Envios registrados por e-mail e IP (limite de reenvio), existindo a conta ou não
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS verification_sends (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            email TEXT NOT NULL,
+            ip TEXT NOT NULL,
+            created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+        );
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    // Tipo do envio: verificação de e-mail ou redefinição de senha (limites contados em separado)
+    crate::ensure_column(pool, "verification_sends", "tipo", "ALTER TABLE verification_sends ADD COLUMN tipo TEXT NOT NULL DEFAULT 'verificacao'").await?;
+    Ok(())
+}
+
+pub const ENVIO_VERIFICACAO: &str = "verificacao";
+pub const ENVIO_REDEFINICAO: &str = "redefinicao";
+
+pub async fn is_verified(db: &SqlitePool, user_id: i64) -> Result<bool, sqlx::Error> {
+    let row = sqlx::query("SELECT verified_at IS NOT NULL AS verificado FROM usuarios WHERE id = ? LIMIT 1")
+        .bind(user_id)
+        .fetch_optional(db)
+        .await?;
+    Ok(row.map(|r| r.try_get::<bool, _>("verificado").unwrap_or(false)).unwrap_or(false))
+}
+
+pub(crate) async fn record_send(db: &SqlitePool, tipo: &str, email: &str, ip: &str) -> Result<(), sqlx::Error> {
+    sqlx::query("INSERT INTO verification_sends (tipo, email, ip) VALUES (?, ?, ?)")
+        .bind(tipo)
+        .bind(email)
+        .bind(ip)
+        .execute(db)
+        .await?;
+    Ok(())
+}
+
+// Registra o envio (limite de reenvio) e envia o link de verificação
+pub async fn send_verification(app_state: &AppState, user_id: i64, email: &str, ip: &str) -> Result<(), String> {
+    record_send(&app_state.db, ENVIO_VERIFICACAO, email, ip)
+        .await
+        .map_err(|e| format!("Erro ao registrar envio: {}", e))?;
+    send_verification_link(app_state, user_id, email).await
+}
+
+// Gera um novo token (invalidando os anteriores) e envia o link
+async fn send_verification_link(app_state: &AppState, user_id: i64, email: &str) -> Result<(), String> {
+    let token = new_token();
+    let gravado = async {
+        let mut tx = app_state.db.begin().await?;
+        sqlx::query("UPDATE email_verifications SET used_at = CURRENT_TIMESTAMP WHERE user_id = ? AND used_at IS NULL")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("INSERT INTO email_verifications (user_id, token_hash, expires_at) VALUES (?, ?, datetime('now', ?))")
+            .bind(user_id)
+            .bind(hash_token(&token))
+            .bind(format!("+{} hours", app_state.config.email_verify_ttl_hours))
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await
+    }
+    .await;
+    gravado.map_err(|e| format!("Erro ao gravar token de verificação: {}", e))?;
+
+    // O link abre a página de login, que chama GET /api/verify-email e mostra o resultado
+    let link = format!("{}/login?verify={}", app_state.config.app_base_url, token);
+    let mensagem = Email {
+        para: email.to_string(),
+        assunto: "Confirme seu e-mail - Mercado Online".to_string(),
+        corpo: format!(
+            "Bem-vindo ao Mercado Online!\n\nConfirme seu e-mail em até {} horas pelo link abaixo:\n{}\n\nSe você não criou esta conta, ignore este e-mail.",
+            app_state.config.email_verify_ttl_hours, link
+        ),
+    };
+    app_state.mailer.send(&mensagem).await
+}
+
+#[derive(Deserialize)]
+pub struct VerifyQuery {
+    #[serde(default)]
+    pub token: String,
+}
+
+// GET /api/verify-email?token=
+pub async fn verify_email(State(app_state): State<AppState>, headers: HeaderMap, Query(q): Query<VerifyQuery>) -> Response {
+    let invalido = || (StatusCode::BAD_REQUEST, Json(json!({"erro": "Link de verificação inválido ou expirado"}))).into_response();
+    let token = q.token.trim();
+    if token.is_empty() {
+        return invalido();
+    }
+
+    let resultado = async {
+        let mut tx = app_state.db.begin().await?;
+        let row = sqlx::query(
+            r#"UPDATE email_verifications SET used_at = CURRENT_TIMESTAMP
+               WHERE token_hash = ? AND used_at IS NULL AND expires_at > CURRENT_TIMESTAMP
+               RETURNING user_id"#,
+        )
+        .bind(hash_token(token))
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(row) = row else { return Ok(None) };
+        let user_id: i64 = row.try_get("user_id").unwrap_or(0);
+        sqlx::query("UPDATE usuarios SET verified_at = COALESCE(verified_at, CURRENT_TIMESTAMP) WHERE id = ?")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok::<Option<i64>, sqlx::Error>(Some(user_id))
+    }
+    .await;
+
+    match resultado {
+        Ok(Some(user_id)) => {
+            println!("E-mail verificado para usuário {}", user_id);
+            let body = Json(json!({"status": "ok", "mensagem": "E-mail verificado com sucesso"}));
+            // A conta ganhou permissão de comprar: a sessão aberta neste navegador recebe novo identificador
+            sessions::rotate_in_response(&app_state, &headers, user_id, (StatusCode::OK, body).into_response()).await
+        }
+        Ok(None) => invalido(),
+        Err(e) => {
+            eprintln!("[auth] Erro ao verificar e-mail: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"erro": "Erro interno"}))).into_response()
+        }
+    }
+}
+
+// Segundos até um novo envio do tipo ser permitido: intervalo mínimo por e-mail e teto por IP na última hora
+pub(crate) async fn resend_wait(app_state: &AppState, tipo: &str, email: &str, ip: &str) -> Result<Option<i64>, sqlx::Error> {
+    let config = &app_state.config;
+    let row = sqlx::query(
+        r#"SELECT ?2 - (CAST(strftime('%s', 'now') AS INTEGER) - CAST(strftime('%s', MAX(created_at)) AS INTEGER)) AS espera
+           FROM verification_sends WHERE email = ?1 AND tipo = ?3"#,
+    )
+    .bind(email)
+    .bind(config.verify_resend_interval_secs)
+    .bind(tipo)
+    .fetch_one(&app_state.db)
+    .await?;
+    let espera: Option<i64> = row.try_get("espera").unwrap_or(None);
+    if let Some(s) = espera.filter(|s| *s > 0) {
+        return Ok(Some(s));
+    }
+
+    let row = sqlx::query(
+        r#"SELECT COUNT(*) AS n, 3600 - (CAST(strftime('%s', 'now') AS INTEGER) - CAST(strftime('%s', MIN(created_at)) AS INTEGER)) AS espera
+           FROM verification_sends WHERE ip = ? AND tipo = ? AND created_at > datetime('now', '-1 hour')"#,
+    )
+    .bind(ip)
+    .bind(tipo)
+    .fetch_one(&app_state.db)
+    .await?;
+    let n: i64 = row.try_get("n").unwrap_or(0);
+    if n >= config.verify_resend_max_per_hour {
+        let espera: Option<i64> = row.try_get("espera").unwrap_or(None);
+        return Ok(Some(espera.unwrap_or(60).max(1)));
+    }
+    Ok(None)
+}
+
+// 429 dos limites de envio, com Retry-After
+pub(crate) fn muitos_envios(segundos: i64) -> Response {
+    (
+        StatusCode::TOO_MANY_REQUESTS,
+        [(RETRY_AFTER, segundos.to_string())],
+        Json(json!({
+            "erro": format!("Aguarde {} segundos para solicitar um novo e-mail", segundos),
+            "retry_after": segundos
+        })),
+    )
+        .into_response()
+}
+
+#[derive(Deserialize)]
+pub struct ResendInput {
+    pub email: String,
+}
+
+// POST /api/verify-email/resend: mesma resposta para qualquer e-mail; limitado por e-mail e por IP
+pub async fn resend_verification(
+    State(app_state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Json(input): Json<ResendInput>,
+) -> Response {
+    let email = password_policy::normalize_email(&input.email);
+    let ip = addr.ip().to_string();
+    let erro_interno = || (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"erro": "Erro interno"}))).into_response();
+
+    match resend_wait(&app_state, ENVIO_VERIFICACAO, &email, &ip).await {
+        Ok(Some(segundos)) => return muitos_envios(segundos),
+        Ok(None) => {}
+        Err(e) => {
+            eprintln!("[auth] Erro ao verificar limite de reenvio: {}", e);
+            return erro_interno();
+        }
+    }
+
+    let user = match sqlx::query("SELECT id, email FROM usuarios WHERE lower(email) = ? AND verified_at IS NULL LIMIT 1")
+        .bind(&email)
+        .fetch_optional(&app_state.db)
+        .await
+    {
+        Ok(r) => r,
+        Err(e) => {
+            eprintln!("[auth] Erro ao buscar usuário para verificação: {}", e);
+            return erro_interno();
+        }
+    };
+    // O pedido conta para o limite exista ou não a conta; o envio sai da requisição para o
+    // tempo de resposta não revelar quais e-mails têm conta pendente
+    if let Err(e) = record_send(&app_state.db, ENVIO_VERIFICACAO, &email, &ip).await {
+        eprintln!("[auth] Erro ao registrar envio: {}", e);
+        return erro_interno();
+    }
+    if let Some(user) = user {
+        let user_id: i64 = user.try_get("id").unwrap_or(0);
+        let email_db: String = user.try_get("email").unwrap_or_default();
+        let app_state = app_state.clone();
+        tokio::spawn(async move {
+            if let Err(e) = send_verification_link(&app_state, user_id, &email_db).await {
+                eprintln!("[mail] Falha ao reenviar verificação: {}", e);
+            }
+        });
+    }
+    (
+        StatusCode::OK,
+        Json(json!({"status": "ok", "mensagem": "Se houver uma conta pendente de verificação, enviaremos um novo link"})),
+    )
+        .into_response()
+}
@@ -0,0 +1,9 @@
+// Escape de texto para XML e HTML: os mesmos cinco caracteres servem aos dois
+// (XML da NFC-e, recibo em HTML e destaques da busca).
+pub fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
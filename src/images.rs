@@ -0,0 +1,145 @@
+use axum::{
+    extract::{Multipart, Path, State},
+    response::Json,
+};
+use image::{imageops::FilterType, ImageFormat};
+use std::path::PathBuf;
+use uuid::Uuid;
+
+use crate::products::{self, Product};
+use crate::{ApiError, AppState};
+
+// Lado máximo (px) de cada variante gerada
+const THUMB_SIZE: u32 = 200;
+const FULL_SIZE: u32 = 800;
+
+// Tipos aceitos: o content-type declarado precisa bater com o formato real dos bytes
+const ALLOWED: &[(&str, ImageFormat)] = &[
+    ("image/png", ImageFormat::Png),
+    ("image/jpeg", ImageFormat::Jpeg),
+    ("image/webp", ImageFormat::WebP),
+];
+
+// Arquivos gerados para um upload (caminhos relativos servidos pelo ServeDir)
+struct Variants {
+    full_url: String,
+    thumb_url: String,
+}
+
+// Decodifica, redimensiona e grava as variantes full e thumb em PNG
+fn render_variants(bytes: &[u8], format: ImageFormat, dir: &str, product_id: u32) -> Result<Variants, String> {
+    let img = image::load_from_memory_with_format(bytes, format).map_err(|e| format!("Imagem inválida: {}", e))?;
+
+    let full = if img.width() > FULL_SIZE || img.height() > FULL_SIZE {
+        img.resize(FULL_SIZE, FULL_SIZE, FilterType::Lanczos3)
+    } else {
+        img.clone()
+    };
+    let thumb = img.thumbnail(THUMB_SIZE, THUMB_SIZE);
+
+    // Nome seguro: id do produto + UUID (sem espaços, acentos ou nome original)
+    let base = format!("{}-{}", product_id, Uuid::new_v4().simple());
+    let full_url = format!("{}/{}-full.png", dir, base);
+    let thumb_url = format!("{}/{}-thumb.png", dir, base);
+
+    std::fs::create_dir_all(dir).map_err(|e| format!("Erro ao criar diretório: {}", e))?;
+    full.save_with_format(&full_url, ImageFormat::Png).map_err(|e| format!("Erro ao salvar imagem: {}", e))?;
+    if let Err(e) = thumb.save_with_format(&thumb_url, ImageFormat::Png) {
+        let _ = std::fs::remove_file(&full_url);
+        return Err(format!("Erro ao salvar miniatura: {}", e));
+    }
+    Ok(Variants { full_url, thumb_url })
+}
+
+// Remove um arquivo antigo somente se ele foi gerado por upload (dentro do diretório de uploads)
+fn remove_previous(dir: &str, url: Option<&str>) {
+    if let Some(url) = url {
+        let path = PathBuf::from(url);
+        if path.parent() == Some(std::path::Path::new(dir)) {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+// POST /api/admin/products/:id/image (multipart, campo "image")
+pub async fn upload_product_image(
+    State(app_state): State<AppState>,
+    Path(product_id): Path<u32>,
+    mut multipart: Multipart,
+) -> Result<Json<Product>, ApiError> {
+    let config = app_state.config.clone();
+    let product = products::get_product_by_id(&app_state.db, product_id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("Produto não encontrado"))?;
+
+    // Localizar o campo "image"
+    let mut upload = None;
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| ApiError::bad_request(&format!("Multipart inválido: {}", e)))?
+    {
+        if field.name() == Some("image") {
+            let content_type = field.content_type().unwrap_or_default().to_string();
+            let bytes = field.bytes().await.map_err(|e| {
+                if e.status() == axum::http::StatusCode::PAYLOAD_TOO_LARGE {
+                    ApiError::new(413, "Payload Too Large", "Imagem excede o tamanho máximo")
+                } else {
+                    ApiError::bad_request(&format!("Falha ao ler upload: {}", e))
+                }
+            })?;
+            upload = Some((content_type, bytes));
+            break;
+        }
+    }
+    let (content_type, bytes) = upload.ok_or_else(|| ApiError::validation_error("image", "Campo image ausente"))?;
+
+    if bytes.is_empty() {
+        return Err(ApiError::validation_error("image", "Arquivo vazio"));
+    }
+    if bytes.len() > config.image_max_bytes {
+        return Err(ApiError::new(413, "Payload Too Large", "Imagem excede o tamanho máximo"));
+    }
+    let declared = ALLOWED
+        .iter()
+        .find(|(mime, _)| *mime == content_type)
+        .map(|(_, f)| *f)
+        .ok_or_else(|| ApiError::new(415, "Unsupported Media Type", "Envie PNG, JPEG ou WebP"))?;
+    if image::guess_format(&bytes).ok() != Some(declared) {
+        return Err(ApiError::new(415, "Unsupported Media Type", "Conteúdo do arquivo não corresponde ao tipo informado"));
+    }
+
+    // Decodificação e redimensionamento são CPU-bound: fora do runtime async
+    let dir = config.upload_dir.clone();
+    let variants = tokio::task::spawn_blocking(move || render_variants(&bytes, declared, &dir, product_id))
+        .await
+        .map_err(|e| ApiError::internal_server_error(&format!("Falha ao processar imagem: {}", e)))?
+        .map_err(|e| ApiError::validation_error("image", &e))?;
+
+    let previous_thumb: Option<String> = sqlx::query_scalar("SELECT thumbnail_url FROM produtos WHERE id = ?")
+        .bind(product_id as i64)
+        .fetch_one(&app_state.db)
+        .await
+        .unwrap_or(None);
+
+    if let Err(e) = sqlx::query("UPDATE produtos SET image_url = ?, thumbnail_url = ? WHERE id = ?")
+        .bind(&variants.full_url)
+        .bind(&variants.thumb_url)
+        .bind(product_id as i64)
+        .execute(&app_state.db)
+        .await
+    {
+        let _ = std::fs::remove_file(&variants.full_url);
+        let _ = std::fs::remove_file(&variants.thumb_url);
+        return Err(ApiError::internal_server_error(&format!("Erro ao atualizar produto: {}", e)));
+    }
+
+    remove_previous(&config.upload_dir, product.image_url.as_deref());
+    remove_previous(&config.upload_dir, previous_thumb.as_deref());
+    println!("Imagem do produto {} atualizada: {}", product_id, variants.full_url);
+
+    products::get_product_by_id(&app_state.db, product_id)
+        .await?
+        .map(Json)
+        .ok_or_else(|| ApiError::not_found("Produto não encontrado"))
+}
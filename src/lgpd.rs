@@ -0,0 +1,273 @@
+use axum::{
+    extract::{Extension, Path, State},
+    http::header::CONTENT_DISPOSITION,
+    response::{IntoResponse, Json, Response},
+};
+use serde::Serialize;
+use serde_json::{json, Value};
+use sqlx::{Row, SqlitePool};
+
+use crate::{profile, ApiError, AppState};
+
+// Atendimento a titulares de dados (LGPD): exportação dos dados pessoais e eliminação
+// acionada por um administrador. Cada pedido fica registrado em lgpd_requests (sem dados
+// pessoais: só ids, tipo e data), inclusive a exclusão feita pelo próprio usuário.
+pub async fn init_lgpd(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS lgpd_requests (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            user_id INTEGER NOT NULL,
+            tipo TEXT NOT NULL,
+            solicitado_por INTEGER NOT NULL,
+            detalhes TEXT NULL,
+            created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+        );
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// tipo: "exportacao", "exclusao_conta" (pelo titular) ou "eliminacao" (por um administrador)
+pub async fn registrar(db: &SqlitePool, user_id: i64, tipo: &str, solicitado_por: i64, detalhes: Option<&str>) -> Result<(), sqlx::Error> {
+    sqlx::query("INSERT INTO lgpd_requests (user_id, tipo, solicitado_por, detalhes) VALUES (?, ?, ?, ?)")
+        .bind(user_id)
+        .bind(tipo)
+        .bind(solicitado_por)
+        .bind(detalhes)
+        .execute(db)
+        .await?;
+    Ok(())
+}
+
+// Campo CSV com aspas quando necessário (vírgula, aspas ou quebra de linha)
+fn csv_campo(v: &Value) -> String {
+    let s = match v {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        outro => outro.to_string(),
+    };
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s
+    }
+}
+
+fn to_csv(colunas: &[&str], linhas: &[Value]) -> String {
+    let mut out = colunas.join(",");
+    out.push_str("\r\n");
+    for linha in linhas {
+        let campos: Vec<String> = colunas.iter().map(|c| csv_campo(&linha[*c])).collect();
+        out.push_str(&campos.join(","));
+        out.push_str("\r\n");
+    }
+    out
+}
+
+fn db_err(e: sqlx::Error) -> ApiError {
+    ApiError::internal_server_error(&format!("Erro ao exportar dados: {}", e))
+}
+
+// GET /api/me/export: dados pessoais do titular em JSON, com as tabelas também em CSV
+pub async fn export_my_data(State(app_state): State<AppState>, Extension(user_id): Extension<i64>) -> Result<Response, ApiError> {
+    let db = &app_state.db;
+    let u = sqlx::query(
+        "SELECT id, nome, email, papel, telefone, cpf, cnpj, verified_at, totp_enabled FROM usuarios WHERE id = ? AND deleted_at IS NULL",
+    )
+    .bind(user_id)
+    .fetch_optional(db)
+    .await
+    .map_err(db_err)?
+    .ok_or_else(|| ApiError::not_found("Usuário não encontrado"))?;
+    let perfil = json!({
+        "id": u.try_get::<i64, _>("id").unwrap_or(0),
+        "nome": u.try_get::<String, _>("nome").unwrap_or_default(),
+        "email": u.try_get::<String, _>("email").unwrap_or_default(),
+        "papel": u.try_get::<String, _>("papel").unwrap_or_default(),
+        "telefone": u.try_get::<Option<String>, _>("telefone").unwrap_or(None),
+        "cpf": u.try_get::<Option<String>, _>("cpf").unwrap_or(None),
+        "cnpj": u.try_get::<Option<String>, _>("cnpj").unwrap_or(None),
+        "email_verificado_em": u.try_get::<Option<String>, _>("verified_at").unwrap_or(None),
+        "verificacao_duas_etapas": u.try_get::<i64, _>("totp_enabled").unwrap_or(0) == 1,
+    });
+
+    let sessoes: Vec<Value> = sqlx::query(
+        "SELECT public_id, user_agent, ip, created_at, last_seen_at, expires_at FROM sessions WHERE user_id = ? ORDER BY created_at",
+    )
+    .bind(user_id)
+    .fetch_all(db)
+    .await
+    .map_err(db_err)?
+    .iter()
+    .map(|r| {
+        json!({
+            "id": r.try_get::<String, _>("public_id").unwrap_or_default(),
+            "user_agent": r.try_get::<Option<String>, _>("user_agent").unwrap_or(None),
+            "ip": r.try_get::<Option<String>, _>("ip").unwrap_or(None),
+            "created_at": r.try_get::<String, _>("created_at").unwrap_or_default(),
+            "last_seen_at": r.try_get::<Option<String>, _>("last_seen_at").unwrap_or(None),
+            "expires_at": r.try_get::<Option<String>, _>("expires_at").unwrap_or(None),
+        })
+    })
+    .collect();
+
+    let tokens: Vec<Value> = sqlx::query("SELECT nome, prefixo, scopes, created_at, expires_at, last_used_at FROM api_tokens WHERE user_id = ? ORDER BY created_at")
+        .bind(user_id)
+        .fetch_all(db)
+        .await
+        .map_err(db_err)?
+        .iter()
+        .map(|r| {
+            json!({
+                "nome": r.try_get::<String, _>("nome").unwrap_or_default(),
+                "prefixo": r.try_get::<String, _>("prefixo").unwrap_or_default(),
+                "scopes": r.try_get::<String, _>("scopes").unwrap_or_default(),
+                "created_at": r.try_get::<String, _>("created_at").unwrap_or_default(),
+                "expires_at": r.try_get::<String, _>("expires_at").unwrap_or_default(),
+                "last_used_at": r.try_get::<Option<String>, _>("last_used_at").unwrap_or(None),
+            })
+        })
+        .collect();
+
+    let pedidos: Vec<Value> = sqlx::query(
+        r#"SELECT id, total_cents, payment_method, payment_installments, interest_cents, total_with_interest_cents,
+                  status, delivery_slot_id, documento, documento_tipo, created_at
+           FROM pedidos WHERE user_id = ? ORDER BY created_at"#,
+    )
+    .bind(user_id)
+    .fetch_all(db)
+    .await
+    .map_err(db_err)?
+    .iter()
+    .map(|r| {
+        json!({
+            "id": r.try_get::<String, _>("id").unwrap_or_default(),
+            "total_cents": r.try_get::<i64, _>("total_cents").unwrap_or(0),
+            "payment_method": r.try_get::<String, _>("payment_method").unwrap_or_default(),
+            "payment_installments": r.try_get::<Option<i64>, _>("payment_installments").unwrap_or(None),
+            "interest_cents": r.try_get::<i64, _>("interest_cents").unwrap_or(0),
+            "total_with_interest_cents": r.try_get::<i64, _>("total_with_interest_cents").unwrap_or(0),
+            "status": r.try_get::<String, _>("status").unwrap_or_default(),
+            "delivery_slot_id": r.try_get::<Option<String>, _>("delivery_slot_id").unwrap_or(None),
+            "documento": r.try_get::<Option<String>, _>("documento").unwrap_or(None),
+            "documento_tipo": r.try_get::<Option<String>, _>("documento_tipo").unwrap_or(None),
+            "created_at": r.try_get::<String, _>("created_at").unwrap_or_default(),
+        })
+    })
+    .collect();
+
+    let itens: Vec<Value> = sqlx::query(
+        r#"SELECT i.pedido_id, i.product_id, i.product_name AS produto, i.qty, i.unit_price_cents
+           FROM itens_pedido i
+           JOIN pedidos pe ON pe.id = i.pedido_id
+           WHERE pe.user_id = ? ORDER BY pe.created_at, i.id"#,
+    )
+    .bind(user_id)
+    .fetch_all(db)
+    .await
+    .map_err(db_err)?
+    .iter()
+    .map(|r| {
+        json!({
+            "pedido_id": r.try_get::<String, _>("pedido_id").unwrap_or_default(),
+            "product_id": r.try_get::<i64, _>("product_id").unwrap_or(0),
+            "produto": r.try_get::<Option<String>, _>("produto").unwrap_or(None),
+            "qty": r.try_get::<i64, _>("qty").unwrap_or(0),
+            "unit_price_cents": r.try_get::<i64, _>("unit_price_cents").unwrap_or(0),
+        })
+    })
+    .collect();
+
+    let csv = json!({
+        "perfil.csv": to_csv(&["id", "nome", "email", "papel", "telefone", "cpf", "cnpj", "email_verificado_em"], std::slice::from_ref(&perfil)),
+        "sessoes.csv": to_csv(&["id", "user_agent", "ip", "created_at", "last_seen_at", "expires_at"], &sessoes),
+        "pedidos.csv": to_csv(
+            &["id", "created_at", "status", "payment_method", "payment_installments", "total_cents", "interest_cents", "total_with_interest_cents", "delivery_slot_id", "documento"],
+            &pedidos,
+        ),
+        "itens_pedido.csv": to_csv(&["pedido_id", "product_id", "produto", "qty", "unit_price_cents"], &itens),
+    });
+
+    let gerado_em: String = sqlx::query_scalar("SELECT datetime('now')").fetch_one(db).await.map_err(db_err)?;
+    let pacote = json!({
+        "gerado_em": gerado_em,
+        "perfil": perfil,
+        "sessoes": sessoes,
+        // O sistema não guarda endereços (a entrega usa apenas janelas de horário)
+        "enderecos": [],
+        "pedidos": pedidos,
+        "itens_pedido": itens,
+        "tokens_api": tokens,
+        "csv": csv,
+    });
+
+    registrar(db, user_id, "exportacao", user_id, None).await.map_err(db_err)?;
+    println!("[lgpd] Exportação de dados do usuário {}", user_id);
+    let disposition = format!("attachment; filename=\"meus-dados-{}.json\"", user_id);
+    Ok(([(CONTENT_DISPOSITION, disposition)], Json(pacote)).into_response())
+}
+
+// POST /api/admin/users/:id/erase: elimina os dados pessoais de um titular. A conta vira um
+// registro anônimo; pedidos e itens ficam com valores, datas e pagamento (obrigação fiscal).
+pub async fn erase_user(
+    State(app_state): State<AppState>,
+    Extension(admin_id): Extension<i64>,
+    Path(user_id): Path<i64>,
+) -> Result<Json<Value>, ApiError> {
+    let db_err = |e: sqlx::Error| ApiError::internal_server_error(&format!("Erro ao eliminar dados: {}", e));
+    let row = sqlx::query("SELECT email, papel FROM usuarios WHERE id = ? AND deleted_at IS NULL")
+        .bind(user_id)
+        .fetch_optional(&app_state.db)
+        .await
+        .map_err(db_err)?
+        .ok_or_else(|| ApiError::not_found("Usuário não encontrado ou já eliminado"))?;
+    let email: String = row.try_get("email").unwrap_or_default();
+    let papel: String = row.try_get("papel").unwrap_or_default();
+    if user_id == admin_id || papel == "admin" {
+        return Err(ApiError::new(409, "Conflict", "Administradores não podem ser eliminados por esta rota"));
+    }
+
+    profile::anonymize_user(&app_state.db, user_id, &email).await.map_err(db_err)?;
+    let pedidos: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM pedidos WHERE user_id = ?")
+        .bind(user_id)
+        .fetch_one(&app_state.db)
+        .await
+        .map_err(db_err)?;
+    let detalhes = format!("{} pedidos mantidos sem dados pessoais", pedidos);
+    registrar(&app_state.db, user_id, "eliminacao", admin_id, Some(&detalhes)).await.map_err(db_err)?;
+    println!("[lgpd] Usuário {} eliminado pelo admin {}", user_id, admin_id);
+    Ok(Json(json!({"status": "ok", "user_id": user_id, "pedidos_mantidos": pedidos})))
+}
+
+#[derive(Serialize)]
+pub struct LgpdRequestRow {
+    id: i64,
+    user_id: i64,
+    tipo: String,
+    solicitado_por: i64,
+    detalhes: Option<String>,
+    created_at: String,
+}
+
+// GET /api/admin/lgpd-requests: registro de auditoria dos pedidos de titulares
+pub async fn list_requests(State(app_state): State<AppState>) -> Result<Json<Vec<LgpdRequestRow>>, ApiError> {
+    let rows = sqlx::query("SELECT id, user_id, tipo, solicitado_por, detalhes, created_at FROM lgpd_requests ORDER BY id DESC LIMIT 500")
+        .fetch_all(&app_state.db)
+        .await
+        .map_err(|e| ApiError::internal_server_error(&format!("Erro ao listar solicitações: {}", e)))?;
+    let mut result = Vec::new();
+    for row in rows {
+        result.push(LgpdRequestRow {
+            id: row.try_get("id").unwrap_or(0),
+            user_id: row.try_get("user_id").unwrap_or(0),
+            tipo: row.try_get("tipo").unwrap_or_default(),
+            solicitado_por: row.try_get("solicitado_por").unwrap_or(0),
+            detalhes: row.try_get("detalhes").unwrap_or(None),
+            created_at: row.try_get("created_at").unwrap_or_default(),
+        });
+    }
+    Ok(Json(result))
+}
@@ -0,0 +1,1193 @@
+/*
+DEMONSTRAÇÃO DE PROGRAMAÇÃO ORIENTADA A OBJETOS EM RUST
+
+Este projeto demonstra os principais conceitos de OOP adaptados para Rust:
+
+1. ENCAPSULAMENTO:
+   - Método `line_total()` em `CartItem` encapsula a lógica de cálculo
+   - Dados e comportamentos ficam juntos na mesma estrutura
+
+2. POLIMORFISMO:
+   - Trait `Pagamento` define um contrato comum
+   - Structs `Pix` e `Cartao` implementam o trait de formas diferentes
+   - Permite tratar diferentes tipos de pagamento de forma uniforme
+
+3. HERANÇA (Simulada via Traits):
+   - Rust não tem herança clássica, mas traits oferecem funcionalidade similar
+   - Múltiplos tipos podem implementar o mesmo trait
+   - Permite reutilização de código e polimorfismo
+
+4. COESÃO:
+   - Cada struct tem uma responsabilidade única e bem definida
+   - `Pix` e `Cartao` focam apenas em processamento de pagamento
+*/
+
+use axum::{
+    extract::{DefaultBodyLimit, Extension, Path, State},
+    http::StatusCode,
+    response::{Json, IntoResponse, Response},
+    routing::{get, post, patch, delete},
+    Router,
+    middleware,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::Arc,
+};
+use tokio::sync::RwLock;
+use tower_http::cors::{Any, CorsLayer};
+use tower_http::services::ServeDir;
+use uuid::Uuid;
+use sqlx::{SqlitePool, Row};
+use sqlx::sqlite::{SqlitePoolOptions, SqliteConnectOptions};
+use std::str::FromStr;
+use std::{fs, env};
+use regex::Regex;
+
+// Taxa de juros simples mensal para parcelamento a partir da 3ª parcela
+const JUROS_A_M: f64 = 0.02; // 2% a.m.
+
+// Estado global do carrinho
+type CartState = Arc<RwLock<HashMap<u32, CartItem>>>;
+
+// Estado da aplicação incluindo carrinho e pool do banco
+#[derive(Clone)]
+pub(crate) struct AppState {
+    cart: CartState,
+    db: SqlitePool,
+    config: Arc<config::Config>,
+    mailer: Arc<dyn mail::Mailer>,
+    sefaz: Arc<dyn nfce::TransmissorSefaz>,
+    // Aviso de pedido gravado (painel em tempo real)
+    novos_pedidos: tokio::sync::broadcast::Sender<()>,
+}
+
+// Abre o arquivo do banco (relativo ao diretório atual); com `criar`, cria o arquivo e o diretório
+async fn open_db(path: &str, criar: bool) -> Result<SqlitePool, sqlx::Error> {
+    let base = env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
+    let db_path = base.join(path);
+    if criar {
+        if let Some(dir) = db_path.parent() {
+            let _ = fs::create_dir_all(dir);
+        }
+    }
+
+    // Caminho absoluto para o arquivo do banco com barras para compatibilidade
+    let db_url = format!("sqlite://{}", db_path.to_string_lossy().replace('\\', "/"));
+    let connect_opts = SqliteConnectOptions::from_str(&db_url)
+        .map_err(|e| sqlx::Error::Configuration(Box::new(e)))?
+        .create_if_missing(criar);
+
+    SqlitePoolOptions::new()
+        .max_connections(5)
+        .connect_with(connect_opts)
+        .await
+}
+
+// Inicializa o banco SQLite (DATABASE_PATH, padrão data/mercado.db) e cria tabelas se não existirem
+async fn init_db(config: &config::Config) -> Result<SqlitePool, sqlx::Error> {
+    let pool = open_db(&config.database_path, true).await?;
+
+    // Criar tabelas mínimas
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS pedidos (
+            id TEXT PRIMARY KEY,
+            total_cents INTEGER NOT NULL,
+            payment_method TEXT NOT NULL,
+            created_at TIMESTAMP NOT NULL DEFAULT (datetime('now'))
+        );
+        "#,
+    )
+    .execute(&pool)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS itens_pedido (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            pedido_id TEXT NOT NULL,
+            product_id INTEGER NOT NULL,
+            qty INTEGER NOT NULL,
+            unit_price_cents INTEGER NOT NULL
+        );
+        "#,
+    )
+    .execute(&pool)
+    .await?;
+
+    // Migração leve: adicionar colunas se não existirem (idempotente)
+    ensure_column(&pool, "pedidos", "payment_installments", "ALTER TABLE pedidos ADD COLUMN payment_installments INTEGER NULL").await?;
+    ensure_column(&pool, "pedidos", "interest_cents", "ALTER TABLE pedidos ADD COLUMN interest_cents INTEGER NOT NULL DEFAULT 0").await?;
+    ensure_column(&pool, "pedidos", "total_with_interest_cents", "ALTER TABLE pedidos ADD COLUMN total_with_interest_cents INTEGER NOT NULL DEFAULT 0").await?;
+    ensure_column(&pool, "pedidos", "status", "ALTER TABLE pedidos ADD COLUMN status TEXT NOT NULL DEFAULT 'paid'").await?;
+    ensure_column(&pool, "pedidos", "delivery_slot_id", "ALTER TABLE pedidos ADD COLUMN delivery_slot_id TEXT NULL").await?;
+    ensure_column(&pool, "pedidos", "user_id", "ALTER TABLE pedidos ADD COLUMN user_id INTEGER NULL").await?;
+    // CPF/CNPJ do comprador (sem máscara) para emissão de documentos fiscais
+    ensure_column(&pool, "pedidos", "documento", "ALTER TABLE pedidos ADD COLUMN documento TEXT NULL").await?;
+    ensure_column(&pool, "pedidos", "documento_tipo", "ALTER TABLE pedidos ADD COLUMN documento_tipo TEXT NULL").await?;
+    // Posição do pedido no stream do painel (id dos eventos SSE), atribuída no checkout;
+    // pedidos antigos herdam o rowid, que era o id usado antes
+    if ensure_column(&pool, "pedidos", "seq", "ALTER TABLE pedidos ADD COLUMN seq INTEGER NULL").await? {
+        sqlx::query("UPDATE pedidos SET seq = rowid").execute(&pool).await?;
+    }
+    sqlx::query("CREATE UNIQUE INDEX IF NOT EXISTS idx_pedidos_seq ON pedidos(seq)").execute(&pool).await?;
+    // Pedido cujo checkout baixou o estoque; só esses devolvem os itens ao cancelar
+    // (pedidos anteriores à baixa no checkout ficam com 0)
+    ensure_column(&pool, "pedidos", "estoque_baixado", "ALTER TABLE pedidos ADD COLUMN estoque_baixado INTEGER NOT NULL DEFAULT 0").await?;
+
+    // Janelas de entrega (agendamento) com capacidade por janela
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS delivery_slots (
+            id TEXT PRIMARY KEY,
+            slot_date TEXT NOT NULL,
+            start_time TEXT NOT NULL,
+            end_time TEXT NOT NULL,
+            capacity INTEGER NOT NULL,
+            reserved INTEGER NOT NULL DEFAULT 0 CHECK (reserved >= 0)
+        );
+        "#,
+    )
+    .execute(&pool)
+    .await?;
+
+    // Catálogo de produtos (categorias, marcas e unidade de medida)
+    products::init_products(&pool).await?;
+    ensure_column(&pool, "produtos", "thumbnail_url", "ALTER TABLE produtos ADD COLUMN thumbnail_url TEXT NULL").await?;
+    search::init_search(&pool).await?;
+    // Nome do produto no momento da compra: recibo, nota e e-mails não mudam se o produto for
+    // renomeado ou excluído depois; itens antigos herdam o nome atual
+    if ensure_column(&pool, "itens_pedido", "product_name", "ALTER TABLE itens_pedido ADD COLUMN product_name TEXT NULL").await? {
+        sqlx::query("UPDATE itens_pedido SET product_name = (SELECT name FROM produtos WHERE produtos.id = itens_pedido.product_id)")
+            .execute(&pool)
+            .await?;
+    }
+
+    // Tabela de usuários (autenticação)
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS usuarios (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            nome TEXT NOT NULL,
+            email TEXT UNIQUE NOT NULL,
+            senha_hash TEXT NOT NULL,
+            papel TEXT NOT NULL DEFAULT 'cliente'
+        );
+        "#,
+    )
+    .execute(&pool)
+    .await?;
+    ensure_column(&pool, "usuarios", "papel", "ALTER TABLE usuarios ADD COLUMN papel TEXT NOT NULL DEFAULT 'cliente'").await?;
+
+    // E-mails passam a ser armazenados em minúsculas; contas antigas são normalizadas
+    // quando isso não colide com outra conta já existente
+    sqlx::query(
+        r#"UPDATE usuarios SET email = lower(trim(email))
+           WHERE email != lower(trim(email))
+             AND NOT EXISTS (SELECT 1 FROM usuarios u2 WHERE u2.id != usuarios.id AND lower(trim(u2.email)) = lower(trim(usuarios.email)))"#,
+    )
+    .execute(&pool)
+    .await?;
+
+    // Tabela de sessões (persistência server-side)
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS sessions (
+            id TEXT PRIMARY KEY,
+            user_id INTEGER NOT NULL,
+            created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+            expires_at TIMESTAMP
+        );
+        "#,
+    )
+    .execute(&pool)
+    .await?;
+    sessions::init_sessions(&pool).await?;
+
+    // Tentativas e bloqueios de login
+    login_guard::init_login_guard(&pool).await?;
+    password_reset::init_password_reset(&pool).await?;
+
+    // Verificação de e-mail: contas já existentes ao criar a coluna ficam como verificadas
+    if ensure_column(&pool, "usuarios", "verified_at", "ALTER TABLE usuarios ADD COLUMN verified_at TIMESTAMP NULL").await? {
+        sqlx::query("UPDATE usuarios SET verified_at = CURRENT_TIMESTAMP").execute(&pool).await?;
+    }
+    email_verification::init_email_verification(&pool).await?;
+    two_factor::init_two_factor(&pool).await?;
+    api_tokens::init_api_tokens(&pool).await?;
+    profile::init_profile(&pool).await?;
+    lgpd::init_lgpd(&pool).await?;
+    nfce::init_nfce(&pool).await?;
+    notificacoes::init_notificacoes(&pool).await?;
+    webhooks::init_webhooks(&pool).await?;
+
+    // Só o esquema: contas iniciais ficam com bootstrap::contas_iniciais, chamado pelo servidor
+    // (o `mercado-admin db migrate` nunca cria contas)
+    Ok(pool)
+}
+
+// Verifica se a coluna existe e adiciona se estiver ausente (retorna true se adicionou)
+async fn ensure_column(pool: &SqlitePool, table: &str, column: &str, alter_sql: &str) -> Result<bool, sqlx::Error> {
+    let rows = sqlx::query(&format!("PRAGMA table_info({});", table))
+        .fetch_all(pool)
+        .await?;
+    let mut exists = false;
+    for row in rows {
+        let name: String = row.try_get("name").unwrap_or_default();
+        if name == column { exists = true; break; }
+    }
+    if !exists {
+        let _ = sqlx::query(alter_sql).execute(pool).await?;
+    }
+    Ok(!exists)
+}
+
+// Sobe o servidor HTTP (binário mercado-backend)
+pub async fn run_server() {
+    // Estado compartilhado do carrinho
+    let cart_state: CartState = Arc::new(RwLock::new(HashMap::new()));
+
+    let config = config::Config::from_env();
+
+    // Inicializar banco SQLite
+    let db_pool = init_db(&config)
+        .await
+        .expect("Falha ao inicializar banco SQLite");
+
+    // Subcomando de administração: `mercado-backend admin create --email ...`
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("admin") {
+        std::process::exit(bootstrap::run_admin_cli(&db_pool, &config, &args[1..]).await);
+    }
+    bootstrap::contas_iniciais(&db_pool, &config)
+        .await
+        .expect("Falha ao criar o administrador inicial");
+
+    // Produção não sobe com credenciais padrão
+    if config.production {
+        let padrao = bootstrap::default_credentials(&db_pool)
+            .await
+            .expect("Falha ao verificar credenciais padrão");
+        if !padrao.is_empty() {
+            eprintln!(
+                "APP_ENV=production: credenciais padrão ou senhas sem hash em {}. Troque-as com `mercado-backend admin create --email ...` ou remova as contas.",
+                padrao.join(", ")
+            );
+            std::process::exit(1);
+        }
+    }
+    let app_state = AppState {
+        cart: cart_state.clone(),
+        db: db_pool.clone(),
+        mailer: mail::from_config(&config),
+        sefaz: nfce::from_config(&config),
+        novos_pedidos: pedidos_stream::canal(),
+        config: Arc::new(config),
+    };
+
+    // Envio dos e-mails de pedidos gravados na fila
+    notificacoes::iniciar_worker(
+        db_pool.clone(),
+        app_state.mailer.clone(),
+        app_state.config.outbox_poll_secs,
+        app_state.config.outbox_max_attempts,
+        app_state.config.outbox_backoff_base_secs,
+    );
+    webhooks::iniciar_worker(
+        db_pool.clone(),
+        app_state.config.outbox_poll_secs,
+        app_state.config.webhook_timeout_secs,
+        app_state.config.webhook_max_attempts,
+        app_state.config.webhook_backoff_base_secs,
+        app_state.config.webhook_allow_private_hosts.clone(),
+    );
+
+    // Configuração CORS
+    let cors = CorsLayer::new()
+        .allow_origin(Any)
+        .allow_methods(Any)
+        .allow_headers(Any);
+
+    // Rotas públicas (sem autenticação)
+    let public = Router::new()
+        .route("/health", get(health_check))
+        .route("/api/register", post(auth::register_user))
+        .route("/api/login", post(auth::login_user))
+        .route("/api/login/2fa", post(two_factor::login_second_step))
+        .route("/api/password/forgot", post(password_reset::forgot_password))
+        .route("/api/password/reset", post(password_reset::reset_password))
+        .route("/api/verify-email", get(email_verification::verify_email))
+        .route("/api/verify-email/resend", post(email_verification::resend_verification))
+        .route("/api/products", get(products::get_products))
+        .route("/api/products/search", get(search::search_products))
+        .route("/api/cart", post(add_to_cart).get(get_cart))
+        .route("/api/cart/:product_id", patch(update_cart_item))
+        .route("/api/cart/clear", delete(clear_cart))
+        .route("/api/delivery-slots", get(delivery::list_delivery_slots))
+        .route("/login", get(login_page));
+
+    // Rotas administrativas (exigem papel admin, além da sessão)
+    let upload_limit = app_state.config.image_max_bytes + 64 * 1024;
+    let admin = Router::new()
+        .route(
+            "/api/admin/products/:id/image",
+            post(images::upload_product_image).layer(DefaultBodyLimit::max(upload_limit)),
+        )
+        .route(
+            "/api/admin/products/import",
+            post(catalogo::import_products).layer(DefaultBodyLimit::max(catalogo::MAX_BYTES)),
+        )
+        .route("/api/admin/products/export", get(catalogo::export_products))
+        .route("/api/admin/products/:id/fiscal", patch(nfce::update_fiscal))
+        .route("/api/admin/products/:id/stock", patch(products::update_stock))
+        .route("/api/admin/pedidos/stream", get(pedidos_stream::stream_pedidos))
+        .route("/api/admin/pedidos/:id/nfce", post(nfce::emitir_nfce).get(nfce::obter_nfce_xml))
+        .route("/api/admin/pedidos/:id/enviar", post(ship_pedido))
+        .route("/api/admin/email-outbox", get(notificacoes::list_outbox))
+        .route("/api/admin/webhooks", get(webhooks::list_webhooks).post(webhooks::create_webhook))
+        .route("/api/admin/webhooks/:id", delete(webhooks::delete_webhook))
+        .route("/api/admin/webhooks/:id/deliveries", get(webhooks::list_deliveries))
+        .route("/api/admin/webhook-deliveries/:id/replay", post(webhooks::replay_delivery))
+        .route("/api/admin/login-lockouts", get(login_guard::list_lockouts))
+        .route("/api/admin/users/:id/erase", post(lgpd::erase_user))
+        .route("/api/admin/lgpd-requests", get(lgpd::list_requests))
+        .route("/api/reports/daily", get(reports_daily))
+        .route_layer(middleware::from_fn_with_state(app_state.clone(), auth::admin_middleware));
+
+    // Rotas protegidas (middleware de autenticação)
+    let protected = Router::new()
+        .route("/api/users", get(auth::list_users))
+        .route("/api/checkout", post(checkout))
+        .route("/api/pedidos", get(list_pedidos))
+        .route("/api/pedidos/:id/itens", get(list_itens_do_pedido))
+        .route("/api/pedidos/:id/cancelar", post(cancel_pedido))
+        .route("/api/pedidos/:id/recibo.html", get(recibo::recibo_html))
+        .route("/api/pedidos/:id/recibo.pdf", get(recibo::recibo_pdf))
+        .route("/api/auth/me", get(auth::auth_me))
+        .route("/api/logout", post(auth::logout))
+        .route("/api/me", get(profile::get_profile).patch(profile::update_profile).delete(profile::delete_account))
+        .route("/api/me/password", post(profile::change_password))
+        .route("/api/me/export", get(lgpd::export_my_data))
+        .route("/api/me/sessions", get(sessions::list_my_sessions).delete(sessions::revoke_all_sessions))
+        .route("/api/me/sessions/:id", delete(sessions::revoke_session))
+        .route("/api/me/2fa", get(two_factor::status_2fa))
+        .route("/api/me/2fa/setup", post(two_factor::setup_2fa))
+        .route("/api/me/2fa/enable", post(two_factor::enable_2fa))
+        .route("/api/me/2fa/disable", post(two_factor::disable_2fa))
+        .route("/api/me/tokens", get(api_tokens::list_tokens).post(api_tokens::create_token))
+        .route("/api/me/tokens/:id", delete(api_tokens::revoke_token))
+        .merge(admin)
+        // Todas páginas estáticas protegidas
+        .nest_service("/", ServeDir::new("."))
+        // CSRF roda dentro do middleware de autenticação (precisa da sessão atual)
+        .layer(middleware::from_fn_with_state(app_state.clone(), csrf::csrf_middleware))
+        .layer(middleware::from_fn_with_state(app_state.clone(), auth::auth_middleware));
+
+    let app = public
+        .merge(protected)
+        .with_state(app_state)
+        .layer(cors);
+
+    // Servidor
+    let addr = SocketAddr::from(([127, 0, 0, 1], 8080));
+    println!("Servidor rodando em http://127.0.0.1:8080");
+    
+    let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
+    // ConnectInfo: IP de origem usado na proteção contra força bruta do login
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>()).await.unwrap();
+}
+
+pub mod admin_cli;
+mod api_tokens;
+mod auth;
+mod bootstrap;
+mod catalogo;
+mod config;
+mod csrf;
+mod delivery;
+mod documento;
+mod email_verification;
+mod escape;
+mod images;
+mod lgpd;
+mod login_guard;
+mod mail;
+mod nfce;
+mod notificacoes;
+mod password_policy;
+mod password_reset;
+mod pedidos_stream;
+mod products;
+mod profile;
+mod recibo;
+mod search;
+mod sessions;
+mod two_factor;
+mod webhooks;
+
+// Chave de acesso da NFC-e, conferida pelos testes contra valores publicados
+pub use nfce::{digito_chave, montar_chave};
+
+// Modelos
+#[derive(Serialize, Deserialize, Clone)]
+struct CartItem {
+    product_id: u32,
+    name: String,
+    unit_price_cents: u32,
+    qty: u32,
+    line_total_cents: u32,
+}
+
+// ENCAPSULAMENTO: Método encapsulado para calcular total da linha
+impl CartItem {
+    /// Calcula o total da linha (preço unitário × quantidade)
+    /// Demonstra encapsulamento: lógica de cálculo fica dentro da struct
+    fn line_total(&self) -> u32 {
+        self.unit_price_cents * self.qty
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct CartSummary {
+    items: Vec<CartItem>,
+    subtotal_cents: u32,
+    shipping_cents: u32,
+    total_cents: u32,
+}
+
+#[derive(Deserialize)]
+struct AddToCartRequest {
+    product_id: u32,
+    qty: u32,
+}
+
+#[derive(Deserialize)]
+struct UpdateCartRequest {
+    qty: u32,
+}
+
+#[derive(Serialize)]
+struct CheckoutResponse {
+    order_id: String,
+    status: String,
+    total_cents: u32,
+    message: String,
+    items: Vec<CartItem>,
+    mensagem_pagamento: Option<String>, // Novo campo para mensagem de pagamento
+    // Campos estendidos para parcelamento/juros (compatíveis com front)
+    payment_method: Option<String>,
+    installments: Option<u8>,
+    interest_cents: Option<u64>,
+    total_with_interest_cents: Option<u64>,
+    installment_value_cents: Option<u64>,
+    delivery_slot_id: Option<String>,
+    // Documento do comprador formatado (ex.: 529.982.247-25)
+    documento: Option<String>,
+}
+
+#[derive(Serialize)]
+struct ApiError {
+    error: String,
+    message: String,
+    code: u16,
+    field: Option<String>,
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = StatusCode::from_u16(self.code).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        // Para casos específicos, retornar payload minimalista conforme requisito
+        if self.code == 400 {
+            return (status, Json(json!({"error": self.message}))).into_response();
+        }
+        if self.code == 422 {
+            return (status, Json(json!({"error": self.message, "field": self.field}))).into_response();
+        }
+        (status, Json(self)).into_response()
+    }
+}
+
+impl ApiError {
+    fn new(code: u16, error: &str, message: &str) -> Self {
+        Self {
+            error: error.to_string(),
+            message: message.to_string(),
+            code,
+            field: None,
+        }
+    }
+    
+    fn bad_request(message: &str) -> Self {
+        Self::new(400, "Bad Request", message)
+    }
+    
+    fn not_found(message: &str) -> Self {
+        Self::new(404, "Not Found", message)
+    }
+    
+    fn internal_server_error(message: &str) -> Self {
+        Self::new(500, "Internal Server Error", message)
+    }
+
+    fn validation_error(field: &str, message: &str) -> Self {
+        Self { error: "Unprocessable Entity".to_string(), message: message.to_string(), code: 422, field: Some(field.to_string()) }
+    }
+}
+
+// Resultado do processamento de pagamento com breakdown
+#[derive(Clone, Debug)]
+struct PaymentResult {
+    installments: Option<u8>,
+    interest_cents: u64,
+    total_with_interest_cents: u64,
+    installment_value_cents: Option<u64>,
+    message: String,
+}
+
+// POLIMORFISMO: Trait que define contrato comum para diferentes métodos de pagamento
+// Em Rust, traits substituem herança clássica, permitindo polimorfismo
+trait Pagamento: Send {
+    fn processar(&self, valor_cents: u64, installments: Option<u8>) -> PaymentResult;
+}
+
+// Utilitário simples para formatar valores em BRL a partir de centavos
+// (pagamentos, e-mails de pedido, recibo e CLI)
+pub(crate) fn format_brl(cents: i64) -> String {
+    format!("R$ {},{:02}", cents / 100, cents % 100)
+}
+
+// Nome do meio de pagamento para exibição (e-mails de pedido e recibo)
+pub(crate) fn metodo_label(metodo: &str) -> &'static str {
+    match metodo {
+        "pix" => "PIX",
+        "credit" => "Cartão de crédito",
+        "debit" => "Cartão de débito",
+        _ => "Outro meio",
+    }
+}
+
+// COESÃO: Struct com responsabilidade única - pagamento via PIX
+struct Pix;
+
+// POLIMORFISMO: Implementação específica para PIX
+impl Pagamento for Pix {
+    fn processar(&self, valor_cents: u64, _installments: Option<u8>) -> PaymentResult {
+        PaymentResult {
+            installments: None,
+            interest_cents: 0,
+            total_with_interest_cents: valor_cents,
+            installment_value_cents: None,
+            message: format!("Pago {} via PIX", format_brl(valor_cents as i64)),
+        }
+    }
+}
+
+// COESÃO: Struct com responsabilidade única - pagamento via Cartão
+struct Cartao;
+
+// POLIMORFISMO: Implementação específica para Cartão
+impl Pagamento for Cartao {
+    fn processar(&self, valor_cents: u64, installments: Option<u8>) -> PaymentResult {
+        let n = installments.unwrap_or(1);
+        let mut total_final = valor_cents as f64;
+        // Juros simples somente a partir da 3ª parcela
+        if n >= 3 {
+            let meses_com_juros = (n - 2) as f64;
+            total_final = (valor_cents as f64) * (1.0 + JUROS_A_M * meses_com_juros);
+        }
+        // Arredondar para centavos corretamente
+        let total_with_interest_cents = total_final.round() as u64;
+        let interest_cents = total_with_interest_cents.saturating_sub(valor_cents);
+        // ceil(total/n) em centavos
+        let installment_value_cents = Some(total_with_interest_cents.div_ceil(n as u64));
+
+        PaymentResult {
+            installments: Some(n),
+            interest_cents,
+            total_with_interest_cents,
+            installment_value_cents,
+            message: format!("Pago {} via Cartão", format_brl(valor_cents as i64)),
+        }
+    }
+}
+
+// Função auxiliar para escolher método de pagamento
+fn escolher_pagamento(metodo: &str) -> Box<dyn Pagamento + Send> {
+    match metodo {
+        "pix" => Box::new(Pix),
+        "credit" => Box::new(Cartao),
+        _ => Box::new(Pix), // Default para PIX
+    }
+}
+
+// Endpoint de health check
+async fn health_check() -> Json<Value> {
+    Json(json!({"ok": true}))
+}
+
+// Página pública de login simples
+async fn login_page() -> Response {
+    match fs::read_to_string("login.html") {
+        Ok(contents) => (StatusCode::OK, [("content-type", "text/html; charset=utf-8")], contents).into_response(),
+        Err(_) => (StatusCode::NOT_FOUND, "login.html não encontrado").into_response(),
+    }
+}
+
+// Endpoint para adicionar item ao carrinho
+async fn add_to_cart(
+    State(app_state): State<AppState>,
+    Json(request): Json<AddToCartRequest>,
+) -> Result<Json<Value>, ApiError> {
+    let cart_state = app_state.cart.clone();
+    if request.qty == 0 {
+        return Err(ApiError::bad_request("Quantidade deve ser maior que zero"));
+    }
+
+    let product = products::get_product_by_id(&app_state.db, request.product_id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("Produto não encontrado"))?;
+
+    // Verificar se há estoque suficiente
+    if product.stock < request.qty {
+        return Err(ApiError::bad_request("Estoque insuficiente"));
+    }
+
+    let mut cart = cart_state.write().await;
+    
+    let line_total = product.price_cents * request.qty;
+    
+    if let Some(existing_item) = cart.get_mut(&request.product_id) {
+        existing_item.qty += request.qty;
+        existing_item.line_total_cents = existing_item.unit_price_cents * existing_item.qty;
+    } else {
+        let cart_item = CartItem {
+            product_id: product.id,
+            name: product.name,
+            unit_price_cents: product.price_cents,
+            qty: request.qty,
+            line_total_cents: line_total,
+        };
+        cart.insert(request.product_id, cart_item);
+    }
+
+    Ok(Json(json!({"message": "Item adicionado ao carrinho"})))
+}
+
+// Endpoint para obter resumo do carrinho
+async fn get_cart(State(app_state): State<AppState>) -> Json<CartSummary> {
+    let cart_state = app_state.cart.clone();
+    let cart = cart_state.read().await;
+    let items: Vec<CartItem> = cart.values().cloned().collect();
+    
+    let subtotal_cents: u32 = items.iter().map(|item| item.line_total_cents).sum();
+    let shipping_cents = 0;
+    let total_cents = subtotal_cents + shipping_cents;
+    
+    let summary = CartSummary {
+        items,
+        subtotal_cents,
+        shipping_cents,
+        total_cents,
+    };
+    
+    Json(summary)
+}
+
+// Endpoint para atualizar quantidade de item no carrinho
+async fn update_cart_item(
+    State(app_state): State<AppState>,
+    Path(product_id): Path<u32>,
+    Json(request): Json<UpdateCartRequest>,
+) -> Result<Json<Value>, ApiError> {
+    let cart_state = app_state.cart.clone();
+    let mut cart = cart_state.write().await;
+    
+    if request.qty == 0 {
+        // Remove o item se quantidade for 0
+        cart.remove(&product_id);
+        return Ok(Json(json!({"message": "Item removido do carrinho"})));
+    }
+    
+    // Verificar se o produto existe e tem estoque suficiente
+    let product = products::get_product_by_id(&app_state.db, product_id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("Produto não encontrado"))?;
+    
+    if product.stock < request.qty {
+        return Err(ApiError::bad_request("Estoque insuficiente"));
+    }
+    
+    if let Some(item) = cart.get_mut(&product_id) {
+        item.qty = request.qty;
+        item.line_total_cents = item.unit_price_cents * item.qty;
+        Ok(Json(json!({"message": "Quantidade atualizada"})))
+    } else {
+        Err(ApiError::not_found("Produto não encontrado no carrinho"))
+    }
+}
+
+// Endpoint para limpar carrinho
+async fn clear_cart(State(app_state): State<AppState>) -> Json<Value> {
+    let cart_state = app_state.cart.clone();
+    let mut cart = cart_state.write().await;
+    cart.clear();
+    Json(json!({"message": "Carrinho limpo com sucesso"}))
+}
+
+#[derive(Deserialize, Debug)]
+struct CheckoutPaymentInput {
+    method: String,
+    installments: Option<u8>,
+}
+
+#[derive(Deserialize, Debug)]
+struct CheckoutInput {
+    payment_method: Option<String>,
+    payment: Option<CheckoutPaymentInput>,
+    customer_email: Option<String>,
+    delivery_slot_id: Option<String>,
+    // CPF ou CNPJ (com ou sem máscara); ausente usa o documento do cadastro
+    documento: Option<String>,
+}
+
+// Endpoint de checkout (estendido)
+async fn checkout(
+    State(app_state): State<AppState>,
+    Extension(user_id): Extension<i64>,
+    Json(input): Json<CheckoutInput>,
+) -> Result<Json<CheckoutResponse>, ApiError> {
+    let cart_state = app_state.cart.clone();
+    let db = app_state.db.clone();
+
+    // Só contas com e-mail verificado podem finalizar pedidos
+    let verificado = email_verification::is_verified(&db, user_id)
+        .await
+        .map_err(|e| ApiError::internal_server_error(&format!("Erro ao verificar conta: {}", e)))?;
+    if !verificado {
+        return Err(ApiError::new(403, "Forbidden", "Confirme seu e-mail antes de finalizar pedidos"));
+    }
+
+    let mut cart = cart_state.write().await;
+    
+    // Validar se o carrinho não está vazio
+    if cart.is_empty() {
+        return Err(ApiError::bad_request("Carrinho está vazio"));
+    }
+
+    // Validar formato de e-mail (backend) com regex simples
+    if let Some(email) = input.customer_email.as_ref() {
+        let trimmed = email.trim();
+        let email_regex = Regex::new(r"^[^\s@]+@[^\s@]+\.[^\s@]+$").unwrap();
+        if !email_regex.is_match(trimmed) {
+            return Err(ApiError::validation_error("email", "Formato de e-mail inválido"));
+        }
+    } else {
+        // Ausência de e-mail: tratar como inválido conforme requisito
+        return Err(ApiError::validation_error("email", "Formato de e-mail inválido"));
+    }
+
+    // CPF/CNPJ do comprador: o informado no pedido (validado) ou o do cadastro, se houver
+    let doc_comprador = match input.documento.as_deref().map(str::trim).filter(|d| !d.is_empty()) {
+        Some(d) => Some(documento::validar(d).ok_or_else(|| ApiError::validation_error("documento", "CPF ou CNPJ inválido"))?),
+        None => {
+            let row = sqlx::query("SELECT cpf, cnpj FROM usuarios WHERE id = ?")
+                .bind(user_id)
+                .fetch_optional(&db)
+                .await
+                .map_err(|e| ApiError::internal_server_error(&format!("Erro ao buscar documento: {}", e)))?;
+            row.and_then(|r| {
+                let cpf: Option<String> = r.try_get("cpf").unwrap_or(None);
+                let cnpj: Option<String> = r.try_get("cnpj").unwrap_or(None);
+                cpf.or(cnpj).and_then(|d| documento::validar(&d))
+            })
+        }
+    };
+    
+    // Capturar itens do carrinho antes de limpar
+    let items: Vec<CartItem> = cart.values().cloned().collect();
+    
+    // ENCAPSULAMENTO: Usar método line_total() em vez de acessar campo diretamente
+    let total_cents: u32 = cart.values().map(|item| item.line_total()).sum();
+    
+    // Determinar método e parcelas a partir do request (compatibilidade com contrato antigo)
+    let metodo_pagamento = input.payment.as_ref().map(|p| p.method.clone())
+        .or_else(|| input.payment_method.clone())
+        .unwrap_or_else(|| if total_cents > 5000 { "credit".to_string() } else { "pix".to_string() });
+
+    let mut installments: Option<u8> = input.payment.as_ref().and_then(|p| p.installments);
+
+    // Validação de parcelas
+    if metodo_pagamento == "credit" {
+        let n = installments.unwrap_or(1);
+        if n == 0 || n > 12 {
+            return Err(ApiError::new(422, "Unprocessable Entity", "Parcelas inválidas para cartão (1..=12)"));
+        }
+        installments = Some(n);
+    } else {
+        // PIX ignora parcelas
+        installments = None;
+    }
+
+    let processador_pagamento = escolher_pagamento(&metodo_pagamento);
+    let pr = processador_pagamento.processar(total_cents as u64, installments);
+    let mensagem_pagamento = pr.message.clone();
+    
+    // Gerar UUID para o pedido
+    let order_id = Uuid::new_v4().to_string();
+
+    // Persistir pedido, itens, baixa do estoque e reserva da janela de entrega em uma única transação:
+    // se a janela estiver esgotada ou faltar estoque nada é gravado e o carrinho permanece intacto
+    let delivery_slot_id = input.delivery_slot_id.as_ref().map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+    if delivery_slot_id.is_some() {
+        delivery::ensure_slots(&db, &app_state.config)
+            .await
+            .map_err(|e| ApiError::internal_server_error(&format!("Erro ao preparar janelas de entrega: {}", e)))?;
+    }
+
+    let db_err = |e: sqlx::Error| {
+        eprintln!("Erro ao salvar pedido: {}", e);
+        ApiError::internal_server_error("Erro ao salvar pedido")
+    };
+    let mut tx = db.begin().await.map_err(db_err)?;
+
+    if let Some(slot_id) = delivery_slot_id.as_deref() {
+        match delivery::reserve_slot(&mut tx, slot_id).await.map_err(db_err)? {
+            delivery::Reserva::Reservada => {}
+            delivery::Reserva::Esgotada => {
+                return Err(ApiError::new(409, "Conflict", "Janela de entrega esgotada, escolha outro horário"));
+            }
+            delivery::Reserva::Inexistente => {
+                return Err(ApiError::validation_error("delivery_slot_id", "Janela de entrega inválida"));
+            }
+        }
+    }
+
+    sqlx::query(
+        "INSERT INTO pedidos (id, total_cents, payment_method, payment_installments, interest_cents, total_with_interest_cents, status, delivery_slot_id, user_id, documento, documento_tipo, seq, estoque_baixado, created_at) VALUES (?, ?, ?, ?, ?, ?, 'paid', ?, ?, ?, ?, (SELECT COALESCE(MAX(seq), 0) + 1 FROM pedidos), 1, datetime('now'))",
+    )
+    .bind(&order_id)
+    .bind(total_cents as i64)
+    .bind(&metodo_pagamento)
+    .bind(pr.installments.map(|x| x as i64))
+    .bind(pr.interest_cents as i64)
+    .bind(pr.total_with_interest_cents as i64)
+    .bind(delivery_slot_id.as_deref())
+    .bind(user_id)
+    .bind(doc_comprador.as_ref().map(|(_, d)| d.as_str()))
+    .bind(doc_comprador.as_ref().map(|(t, _)| t.as_str()))
+    .execute(&mut *tx)
+    .await
+    .map_err(db_err)?;
+
+    for item in &items {
+        sqlx::query(
+            "INSERT INTO itens_pedido (pedido_id, product_id, product_name, qty, unit_price_cents) VALUES (?, ?, ?, ?, ?)",
+        )
+        .bind(&order_id)
+        .bind(item.product_id as i64)
+        .bind(&item.name)
+        .bind(item.qty as i64)
+        .bind(item.unit_price_cents as i64)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_err)?;
+
+        // Baixa do estoque; cruzando LOW_STOCK_THRESHOLD dispara product.low_stock
+        let qty = item.qty as i64;
+        let atual: Option<i64> = sqlx::query_scalar("UPDATE produtos SET stock = stock - ? WHERE id = ? AND stock >= ? RETURNING stock")
+            .bind(qty)
+            .bind(item.product_id as i64)
+            .bind(qty)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(db_err)?;
+        let Some(atual) = atual else {
+            return Err(ApiError::new(409, "Conflict", &format!("Estoque insuficiente para {}", item.name)));
+        };
+        products::avisar_estoque_baixo(&mut tx, item.product_id as i64, &item.name, atual + qty, atual, app_state.config.low_stock_threshold)
+            .await
+            .map_err(db_err)?;
+    }
+
+    // E-mails de confirmação e pagamento e o webhook order.created entram na fila junto com o pedido
+    for evento in [notificacoes::Evento::PedidoConfirmado, notificacoes::Evento::PagamentoRecebido] {
+        notificacoes::enfileirar(&mut tx, evento, &order_id, &app_state.config.app_base_url)
+            .await
+            .map_err(db_err)?;
+    }
+    let dados_pedido = webhooks::dados_pedido(&mut tx, &order_id).await.map_err(db_err)?;
+    webhooks::disparar(&mut tx, "order.created", dados_pedido).await.map_err(db_err)?;
+
+    tx.commit().await.map_err(db_err)?;
+    println!("Pedido {} salvo em pedidos + itens_pedido", order_id);
+    // Sem painel conectado não há receptores; o erro de envio é esperado
+    let _ = app_state.novos_pedidos.send(());
+    
+    // Limpar o carrinho após o checkout
+    cart.clear();
+    
+    Ok(Json(CheckoutResponse {
+        order_id,
+        status: "paid".to_string(),
+        total_cents,
+        message: "Pedido processado com sucesso".to_string(),
+        items,
+        mensagem_pagamento: Some(mensagem_pagamento),
+        payment_method: Some(metodo_pagamento),
+        installments: pr.installments,
+        interest_cents: Some(pr.interest_cents),
+        total_with_interest_cents: Some(pr.total_with_interest_cents),
+        installment_value_cents: pr.installment_value_cents,
+        delivery_slot_id,
+        documento: doc_comprador.map(|(_, d)| documento::formatar(&d)),
+    }))
+}
+
+// Relatório simples diário (admin): totais por dia e método de pagamento
+#[derive(Serialize)]
+struct DailyReportRow {
+    dia: String,
+    metodo: String,
+    total_cents: i64,
+}
+
+async fn reports_daily(State(app_state): State<AppState>) -> Result<Json<Vec<DailyReportRow>>, ApiError> {
+    let db = app_state.db.clone();
+    let rows = sqlx::query(
+        r#"SELECT DATE(created_at) AS dia, payment_method AS metodo, SUM(total_cents) AS total_cents
+           FROM pedidos
+           GROUP BY 1,2
+           ORDER BY 1 DESC"#,
+    )
+    .fetch_all(&db)
+    .await
+    .map_err(|e| ApiError::internal_server_error(&format!("Erro no relatório: {}", e)))?;
+
+    let mut result = Vec::new();
+    for row in rows {
+        let dia: String = row.try_get("dia").unwrap_or_default();
+        let metodo: String = row.try_get("metodo").unwrap_or_default();
+        let total_cents: i64 = row.try_get("total_cents").unwrap_or(0);
+        result.push(DailyReportRow { dia, metodo, total_cents });
+    }
+
+    Ok(Json(result))
+}
+
+// Listar pedidos salvos: o cliente vê os próprios; o administrador vê todos (auditoria)
+#[derive(Serialize)]
+struct PedidoRow {
+    id: String,
+    total_cents: i64,
+    payment_method: String,
+    status: String,
+    delivery_slot_id: Option<String>,
+    created_at: String,
+}
+
+async fn list_pedidos(State(app_state): State<AppState>, Extension(user_id): Extension<i64>) -> Result<Json<Vec<PedidoRow>>, ApiError> {
+    let db_err = |e: sqlx::Error| ApiError::internal_server_error(&format!("Erro ao listar pedidos: {}", e));
+    let admin = is_admin(&app_state.db, user_id).await.map_err(db_err)?;
+    let rows = sqlx::query(
+        r#"SELECT id, total_cents, payment_method, status, delivery_slot_id, created_at FROM pedidos
+           WHERE ?1 OR user_id = ?2 ORDER BY created_at DESC"#,
+    )
+    .bind(admin)
+    .bind(user_id)
+    .fetch_all(&app_state.db)
+    .await
+    .map_err(db_err)?;
+
+    let mut result = Vec::new();
+    for row in rows {
+        let id: String = row.try_get("id").unwrap_or_default();
+        let total_cents: i64 = row.try_get("total_cents").unwrap_or(0);
+        let payment_method: String = row.try_get("payment_method").unwrap_or_default();
+        let status: String = row.try_get("status").unwrap_or_default();
+        let delivery_slot_id: Option<String> = row.try_get("delivery_slot_id").unwrap_or(None);
+        let created_at: String = row.try_get("created_at").unwrap_or_default();
+        result.push(PedidoRow { id, total_cents, payment_method, status, delivery_slot_id, created_at });
+    }
+
+    Ok(Json(result))
+}
+
+// Pedido visível para o dono ou para um administrador; os demais recebem o mesmo 404 de
+// pedido inexistente, para não revelar que existe
+pub(crate) async fn dono_ou_admin<'e, E>(ex: E, user_id: i64, dono: Option<i64>) -> Result<bool, sqlx::Error>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Sqlite>,
+{
+    if dono == Some(user_id) {
+        return Ok(true);
+    }
+    is_admin(ex, user_id).await
+}
+
+// O usuário tem papel de administrador
+pub(crate) async fn is_admin<'e, E>(ex: E, user_id: i64) -> Result<bool, sqlx::Error>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Sqlite>,
+{
+    let papel: Option<String> = sqlx::query_scalar("SELECT papel FROM usuarios WHERE id = ?")
+        .bind(user_id)
+        .fetch_optional(ex)
+        .await?;
+    Ok(papel.as_deref() == Some("admin"))
+}
+
+// Cancelar pedido: marca como cancelado e devolve ao estoque os itens e a vaga da janela de entrega
+async fn cancel_pedido(
+    Path(order_id): Path<String>,
+    State(app_state): State<AppState>,
+    Extension(user_id): Extension<i64>,
+) -> Result<Json<Value>, ApiError> {
+    let db_err = |e: sqlx::Error| ApiError::internal_server_error(&format!("Erro ao cancelar pedido: {}", e));
+    let mut tx = app_state.db.begin().await.map_err(db_err)?;
+
+    let row = sqlx::query("SELECT status, delivery_slot_id, user_id, estoque_baixado FROM pedidos WHERE id = ? LIMIT 1")
+        .bind(&order_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(db_err)?
+        .ok_or_else(|| ApiError::not_found("Pedido não encontrado"))?;
+    let status: String = row.try_get("status").unwrap_or_default();
+    let delivery_slot_id: Option<String> = row.try_get("delivery_slot_id").unwrap_or(None);
+    let dono: Option<i64> = row.try_get("user_id").unwrap_or(None);
+    let estoque_baixado: bool = row.try_get("estoque_baixado").unwrap_or(false);
+    if !dono_ou_admin(&mut *tx, user_id, dono).await.map_err(db_err)? {
+        return Err(ApiError::not_found("Pedido não encontrado"));
+    }
+
+    if status == "cancelled" {
+        return Err(ApiError::new(409, "Conflict", "Pedido já está cancelado"));
+    }
+    if status == "shipped" {
+        return Err(ApiError::new(409, "Conflict", "Pedido já saiu para entrega e não pode ser cancelado"));
+    }
+
+    sqlx::query("UPDATE pedidos SET status = 'cancelled', estoque_baixado = 0 WHERE id = ?")
+        .bind(&order_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_err)?;
+    // Pedidos antigos não baixaram o estoque no checkout: não há o que devolver
+    if estoque_baixado {
+        sqlx::query(
+            r#"UPDATE produtos SET stock = stock + (SELECT SUM(qty) FROM itens_pedido WHERE pedido_id = ?1 AND product_id = produtos.id)
+               WHERE id IN (SELECT product_id FROM itens_pedido WHERE pedido_id = ?1)"#,
+        )
+        .bind(&order_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_err)?;
+    }
+    if let Some(slot_id) = delivery_slot_id.as_deref() {
+        delivery::release_slot(&mut tx, slot_id).await.map_err(db_err)?;
+    }
+    notificacoes::enfileirar(&mut tx, notificacoes::Evento::PedidoCancelado, &order_id, &app_state.config.app_base_url)
+        .await
+        .map_err(db_err)?;
+    webhooks::disparar(
+        &mut tx,
+        "order.status_changed",
+        json!({"order_id": order_id, "status_anterior": status, "status": "cancelled"}),
+    )
+    .await
+    .map_err(db_err)?;
+    tx.commit().await.map_err(db_err)?;
+
+    println!("Pedido {} cancelado", order_id);
+    Ok(Json(json!({"order_id": order_id, "status": "cancelled"})))
+}
+
+// Despachar pedido (admin): pedido pago passa a "shipped" e o cliente é avisado por e-mail
+async fn ship_pedido(
+    Path(order_id): Path<String>,
+    State(app_state): State<AppState>,
+) -> Result<Json<Value>, ApiError> {
+    let db_err = |e: sqlx::Error| ApiError::internal_server_error(&format!("Erro ao despachar pedido: {}", e));
+    let mut tx = app_state.db.begin().await.map_err(db_err)?;
+
+    let status: String = sqlx::query_scalar("SELECT status FROM pedidos WHERE id = ? LIMIT 1")
+        .bind(&order_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(db_err)?
+        .ok_or_else(|| ApiError::not_found("Pedido não encontrado"))?;
+    if status != "paid" {
+        return Err(ApiError::new(409, "Conflict", &format!("Pedido com status {} não pode ser despachado", status)));
+    }
+
+    sqlx::query("UPDATE pedidos SET status = 'shipped' WHERE id = ?")
+        .bind(&order_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_err)?;
+    notificacoes::enfileirar(&mut tx, notificacoes::Evento::PedidoEnviado, &order_id, &app_state.config.app_base_url)
+        .await
+        .map_err(db_err)?;
+    webhooks::disparar(
+        &mut tx,
+        "order.status_changed",
+        json!({"order_id": order_id, "status_anterior": status, "status": "shipped"}),
+    )
+    .await
+    .map_err(db_err)?;
+    tx.commit().await.map_err(db_err)?;
+
+    println!("Pedido {} despachado", order_id);
+    Ok(Json(json!({"order_id": order_id, "status": "shipped"})))
+}
+
+// Listar itens de um pedido específico
+#[derive(Serialize)]
+struct ItemPedidoRow {
+    id: i64,
+    pedido_id: String,
+    product_id: i64,
+    qty: i64,
+    unit_price_cents: i64,
+}
+
+async fn list_itens_do_pedido(
+    Path(order_id): Path<String>,
+    State(app_state): State<AppState>,
+    Extension(user_id): Extension<i64>,
+) -> Result<Json<Vec<ItemPedidoRow>>, ApiError> {
+    let db = app_state.db.clone();
+    let db_err = |e: sqlx::Error| ApiError::internal_server_error(&format!("Erro ao listar itens do pedido: {}", e));
+    let dono: Option<Option<i64>> = sqlx::query_scalar("SELECT user_id FROM pedidos WHERE id = ?")
+        .bind(&order_id)
+        .fetch_optional(&db)
+        .await
+        .map_err(db_err)?;
+    let dono = dono.ok_or_else(|| ApiError::not_found("Pedido não encontrado"))?;
+    if !dono_ou_admin(&db, user_id, dono).await.map_err(db_err)? {
+        return Err(ApiError::not_found("Pedido não encontrado"));
+    }
+
+    let rows = sqlx::query(
+        r#"SELECT id, pedido_id, product_id, qty, unit_price_cents FROM itens_pedido WHERE pedido_id = ? ORDER BY id"#,
+    )
+    .bind(&order_id)
+    .fetch_all(&db)
+    .await
+    .map_err(db_err)?;
+
+    let mut result = Vec::new();
+    for row in rows {
+        let id: i64 = row.try_get("id").unwrap_or(0);
+        let pedido_id: String = row.try_get("pedido_id").unwrap_or_default();
+        let product_id: i64 = row.try_get("product_id").unwrap_or(0);
+        let qty: i64 = row.try_get("qty").unwrap_or(0);
+        let unit_price_cents: i64 = row.try_get("unit_price_cents").unwrap_or(0);
+        result.push(ItemPedidoRow { id, pedido_id, product_id, qty, unit_price_cents });
+    }
+
+    Ok(Json(result))
+}
\ No newline at end of file
@@ -0,0 +1,202 @@
+use axum::{
+    extract::State,
+    response::Json,
+};
+use serde::Serialize;
+use sqlx::{Row, SqlitePool};
+
+use crate::config::Config;
+use crate::{ApiError, AppState};
+
+// Proteção contra força bruta no login: cada tentativa é registrada por e-mail e por IP.
+// Ao atingir o limite de falhas dentro da janela configurada a chave é bloqueada; cada falha
+// extra dobra o bloqueio. Um login bem-sucedido zera só a contagem da conta: a do IP segue
+// valendo, senão quem tem uma conta válida entraria nela entre tentativas contra as outras.
+pub async fn init_login_guard(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS login_attempts (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            email TEXT NOT NULL,
+            ip TEXT NOT NULL,
+            success INTEGER NOT NULL,
+            created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+        );
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_login_attempts_email ON login_attempts (email, id)")
+        .execute(pool)
+        .await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_login_attempts_ip ON login_attempts (ip, id)")
+        .execute(pool)
+        .await?;
+
+    // Auditoria: cada bloqueio aplicado fica registrado
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS login_lockouts (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            scope TEXT NOT NULL,
+            chave TEXT NOT NULL,
+            falhas INTEGER NOT NULL,
+            bloqueado_ate TIMESTAMP NOT NULL,
+            created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+        );
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// Escopo de contagem: conta (e-mail normalizado) ou IP de origem
+#[derive(Clone, Copy)]
+enum Scope {
+    Conta,
+    Ip,
+}
+
+impl Scope {
+    fn name(self) -> &'static str {
+        match self {
+            Scope::Conta => "conta",
+            Scope::Ip => "ip",
+        }
+    }
+
+    fn column(self) -> &'static str {
+        match self {
+            Scope::Conta => "email",
+            Scope::Ip => "ip",
+        }
+    }
+
+    fn threshold(self, config: &Config) -> i64 {
+        match self {
+            Scope::Conta => config.login_max_failures,
+            Scope::Ip => config.login_max_failures_ip,
+        }
+    }
+}
+
+// Segundos restantes de bloqueio para a conta ou o IP (o maior dos dois), se houver
+pub async fn locked_for(db: &SqlitePool, config: &Config, email: &str, ip: &str) -> Result<Option<i64>, sqlx::Error> {
+    // IP isento: só o bloqueio da conta vale (chave vazia não casa com nenhum IP)
+    let ip = if config.login_ip_exempt.iter().any(|e| e == ip) { "" } else { ip };
+    let row = sqlx::query(
+        r#"SELECT MAX(CAST(strftime('%s', bloqueado_ate) AS INTEGER) - CAST(strftime('%s', 'now') AS INTEGER)) AS restante
+           FROM login_lockouts
+           WHERE ((scope = 'conta' AND chave = ?) OR (scope = 'ip' AND chave = ?))
+             AND bloqueado_ate > CURRENT_TIMESTAMP"#,
+    )
+    .bind(email)
+    .bind(ip)
+    .fetch_one(db)
+    .await?;
+    let restante: Option<i64> = row.try_get("restante").unwrap_or(None);
+    Ok(restante.filter(|s| *s > 0))
+}
+
+// Falhas da chave dentro da janela; para a conta, só as posteriores ao último sucesso
+async fn recent_failures(db: &SqlitePool, config: &Config, scope: Scope, key: &str) -> Result<i64, sqlx::Error> {
+    let column = scope.column();
+    let desde_sucesso = match scope {
+        Scope::Conta => format!(
+            "AND id > COALESCE((SELECT MAX(id) FROM login_attempts WHERE {col} = ?1 AND success = 1), 0)",
+            col = column
+        ),
+        Scope::Ip => String::new(),
+    };
+    let sql = format!(
+        r#"SELECT COUNT(*) AS n FROM login_attempts
+           WHERE {col} = ?1 AND success = 0
+             AND created_at > datetime('now', ?2)
+             {desde_sucesso}"#,
+        col = column
+    );
+    let row = sqlx::query(&sql)
+        .bind(key)
+        .bind(format!("-{} seconds", config.login_failure_window_secs))
+        .fetch_one(db)
+        .await?;
+    Ok(row.try_get("n").unwrap_or(0))
+}
+
+// Registra a tentativa; em caso de falha aplica bloqueio se o limite foi atingido.
+// Retorna os segundos de bloqueio aplicados (se algum).
+pub async fn record_attempt(db: &SqlitePool, config: &Config, email: &str, ip: &str, success: bool) -> Result<Option<i64>, sqlx::Error> {
+    sqlx::query("INSERT INTO login_attempts (email, ip, success) VALUES (?, ?, ?)")
+        .bind(email)
+        .bind(ip)
+        .bind(success as i64)
+        .execute(db)
+        .await?;
+    if success {
+        return Ok(None);
+    }
+
+    let mut applied = None;
+    for (scope, key) in [(Scope::Conta, email), (Scope::Ip, ip)] {
+        if matches!(scope, Scope::Ip) && config.login_ip_exempt.iter().any(|e| e == key) {
+            continue;
+        }
+        let failures = recent_failures(db, config, scope, key).await?;
+        let threshold = scope.threshold(config);
+        if failures < threshold {
+            continue;
+        }
+        // Backoff exponencial: base * 2^(falhas além do limite), limitado ao máximo
+        let exponent = (failures - threshold).min(20) as u32;
+        let secs = config
+            .login_lockout_base_secs
+            .saturating_mul(1i64 << exponent)
+            .min(config.login_lockout_max_secs);
+        sqlx::query(
+            "INSERT INTO login_lockouts (scope, chave, falhas, bloqueado_ate) VALUES (?, ?, ?, datetime('now', ?))",
+        )
+        .bind(scope.name())
+        .bind(key)
+        .bind(failures)
+        .bind(format!("+{} seconds", secs))
+        .execute(db)
+        .await?;
+        eprintln!("[auth] Bloqueio de login ({} {}) por {}s após {} falhas", scope.name(), key, secs, failures);
+        applied = Some(applied.map_or(secs, |a: i64| a.max(secs)));
+    }
+    Ok(applied)
+}
+
+#[derive(Serialize)]
+pub struct LockoutRow {
+    id: i64,
+    scope: String,
+    chave: String,
+    falhas: i64,
+    bloqueado_ate: String,
+    created_at: String,
+}
+
+// GET /api/admin/login-lockouts: histórico de bloqueios (auditoria)
+pub async fn list_lockouts(State(app_state): State<AppState>) -> Result<Json<Vec<LockoutRow>>, ApiError> {
+    let rows = sqlx::query(
+        "SELECT id, scope, chave, falhas, bloqueado_ate, created_at FROM login_lockouts ORDER BY id DESC LIMIT 500",
+    )
+    .fetch_all(&app_state.db)
+    .await
+    .map_err(|e| ApiError::internal_server_error(&format!("Erro ao listar bloqueios: {}", e)))?;
+
+    let mut result = Vec::new();
+    for row in rows {
+        result.push(LockoutRow {
+            id: row.try_get("id").unwrap_or(0),
+            scope: row.try_get("scope").unwrap_or_default(),
+            chave: row.try_get("chave").unwrap_or_default(),
+            falhas: row.try_get("falhas").unwrap_or(0),
+            bloqueado_ate: row.try_get("bloqueado_ate").unwrap_or_default(),
+            created_at: row.try_get("created_at").unwrap_or_default(),
+        });
+    }
+    Ok(Json(result))
+}
@@ -0,0 +1,123 @@
+use axum::async_trait;
+use lettre::message::{header::ContentType, Mailbox};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use std::path::PathBuf;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::config::{Config, SmtpConfig};
+
+// Mensagem de e-mail em texto simples
+#[derive(Clone, Debug)]
+pub struct Email {
+    pub para: String,
+    pub assunto: String,
+    pub corpo: String,
+}
+
+// Envio de e-mails: a implementação é escolhida pela configuração (MAIL_TRANSPORT)
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    async fn send(&self, email: &Email) -> Result<(), String>;
+}
+
+// Grava cada mensagem como um arquivo .eml no diretório configurado (desenvolvimento e testes)
+pub struct FileMailer {
+    dir: PathBuf,
+}
+
+impl FileMailer {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+}
+
+#[async_trait]
+impl Mailer for FileMailer {
+    async fn send(&self, email: &Email) -> Result<(), String> {
+        tokio::fs::create_dir_all(&self.dir)
+            .await
+            .map_err(|e| format!("Erro ao criar diretório de e-mails: {}", e))?;
+        let millis = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or(0);
+        let path = self.dir.join(format!("{}-{}.eml", millis, Uuid::new_v4().simple()));
+        let conteudo = format!(
+            "To: {}\r\nSubject: {}\r\nContent-Type: text/plain; charset=utf-8\r\n\r\n{}\r\n",
+            email.para, email.assunto, email.corpo
+        );
+        tokio::fs::write(&path, conteudo)
+            .await
+            .map_err(|e| format!("Erro ao gravar e-mail: {}", e))?;
+        println!("[mail] E-mail para {} gravado em {}", email.para, path.display());
+        Ok(())
+    }
+}
+
+// Apenas escreve a mensagem no log do servidor
+pub struct ConsoleMailer;
+
+#[async_trait]
+impl Mailer for ConsoleMailer {
+    async fn send(&self, email: &Email) -> Result<(), String> {
+        println!("[mail] Para: {}\n[mail] Assunto: {}\n{}", email.para, email.assunto, email.corpo);
+        Ok(())
+    }
+}
+
+// Envio por servidor SMTP (STARTTLS, TLS direto ou sem cifra para relay local)
+pub struct SmtpMailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl SmtpMailer {
+    pub fn new(cfg: &SmtpConfig) -> Result<Self, String> {
+        let builder = match cfg.tls.as_str() {
+            "tls" => AsyncSmtpTransport::<Tokio1Executor>::relay(&cfg.host),
+            "nenhum" | "none" => Ok(AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&cfg.host)),
+            _ => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&cfg.host),
+        }
+        .map_err(|e| format!("SMTP_HOST inválido: {}", e))?;
+        let mut builder = builder.port(cfg.port);
+        if !cfg.user.is_empty() {
+            builder = builder.credentials(Credentials::new(cfg.user.clone(), cfg.password.clone()));
+        }
+        let from = cfg.from.parse::<Mailbox>().map_err(|e| format!("SMTP_FROM inválido: {}", e))?;
+        Ok(Self { transport: builder.build(), from })
+    }
+}
+
+#[async_trait]
+impl Mailer for SmtpMailer {
+    async fn send(&self, email: &Email) -> Result<(), String> {
+        let para = email.para.parse::<Mailbox>().map_err(|e| format!("Destinatário inválido: {}", e))?;
+        let mensagem = Message::builder()
+            .from(self.from.clone())
+            .to(para)
+            .subject(email.assunto.as_str())
+            .header(ContentType::TEXT_PLAIN)
+            .body(email.corpo.clone())
+            .map_err(|e| format!("Erro ao montar e-mail: {}", e))?;
+        self.transport.send(mensagem).await.map_err(|e| format!("Erro SMTP: {}", e))?;
+        println!("[mail] E-mail para {} enviado por SMTP", email.para);
+        Ok(())
+    }
+}
+
+pub fn from_config(config: &Config) -> Arc<dyn Mailer> {
+    match config.mail_transport.as_str() {
+        "console" => Arc::new(ConsoleMailer),
+        // Configuração SMTP inválida impede a subida (melhor que perder e-mails em silêncio)
+        "smtp" => match SmtpMailer::new(&config.smtp) {
+            Ok(m) => Arc::new(m),
+            Err(e) => {
+                eprintln!("[mail] {}", e);
+                std::process::exit(1);
+            }
+        },
+        _ => Arc::new(FileMailer::new(&config.mail_dir)),
+    }
+}
@@ -0,0 +1,572 @@
+use axum::{
+    async_trait,
+    extract::{Path, State},
+    http::{header::CONTENT_TYPE, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use sqlx::{Row, SqlitePool};
+use std::sync::Arc;
+
+use crate::config::{Config, NfceConfig};
+use crate::escape::xml_escape;
+use crate::{documento, ApiError, AppState};
+
+// NFC-e (modelo 65, leiaute 4.00): monta o XML a partir do pedido e dos itens, com NCM, CFOP e
+// ICMS de cada produto, calcula a chave de acesso e guarda o documento em nfce_documentos.
+// Assinatura e envio à SEFAZ ficam atrás de TransmissorSefaz; a implementação local (stub)
+// não envia nada. O QR Code (infNFeSupl) depende do CSC do emitente e cabe ao transmissor.
+const MODELO: &str = "65";
+const NAMESPACE: &str = "http://www.portalfiscal.inf.br/nfe";
+// Exigido pela SEFAZ na descrição do primeiro item em homologação
+const XPROD_HOMOLOGACAO: &str = "NOTA FISCAL EMITIDA EM AMBIENTE DE HOMOLOGACAO - SEM VALOR FISCAL";
+
+// Código IBGE de cada UF (início da chave de acesso)
+const UF_CODIGOS: &[(&str, &str)] = &[
+    ("RO", "11"), ("AC", "12"), ("AM", "13"), ("RR", "14"), ("PA", "15"), ("AP", "16"), ("TO", "17"),
+    ("MA", "21"), ("PI", "22"), ("CE", "23"), ("RN", "24"), ("PB", "25"), ("PE", "26"), ("AL", "27"),
+    ("SE", "28"), ("BA", "29"), ("MG", "31"), ("ES", "32"), ("RJ", "33"), ("SP", "35"), ("PR", "41"),
+    ("SC", "42"), ("RS", "43"), ("MS", "50"), ("MT", "51"), ("GO", "52"), ("DF", "53"),
+];
+
+// Situação tributária do ICMS aceita por produto: CST (regime normal) ou CSOSN (Simples Nacional)
+const CST_NORMAL: &[&str] = &["00", "40", "41", "50"];
+const CSOSN: &[&str] = &["102", "103", "300", "400"];
+
+pub async fn init_nfce(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    // Dados fiscais por produto
+    crate::ensure_column(pool, "produtos", "ncm", "ALTER TABLE produtos ADD COLUMN ncm TEXT NULL").await?;
+    crate::ensure_column(pool, "produtos", "cfop", "ALTER TABLE produtos ADD COLUMN cfop TEXT NOT NULL DEFAULT '5102'").await?;
+    crate::ensure_column(pool, "produtos", "icms_origem", "ALTER TABLE produtos ADD COLUMN icms_origem INTEGER NOT NULL DEFAULT 0").await?;
+    crate::ensure_column(pool, "produtos", "icms_cst", "ALTER TABLE produtos ADD COLUMN icms_cst TEXT NOT NULL DEFAULT '102'").await?;
+    // Alíquota em centésimos de ponto percentual (1800 = 18,00%)
+    crate::ensure_column(pool, "produtos", "icms_aliquota_bps", "ALTER TABLE produtos ADD COLUMN icms_aliquota_bps INTEGER NOT NULL DEFAULT 0").await?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS nfce_documentos (
+            pedido_id TEXT PRIMARY KEY,
+            serie INTEGER NOT NULL,
+            numero INTEGER NOT NULL,
+            chave TEXT NOT NULL UNIQUE,
+            xml TEXT NOT NULL,
+            status TEXT NOT NULL,
+            protocolo TEXT NULL,
+            mensagem TEXT NULL,
+            created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            UNIQUE(serie, numero)
+        );
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// Status de falha local (antes da resposta da SEFAZ): a nota pode ser reenviada
+const ERROS_REENVIAVEIS: &[&str] = &["erro_assinatura", "erro_transmissao"];
+// Nota em "gerada" há mais tempo que isso ficou presa (falha ao gravar o resultado) e pode ser reenviada
+const GERADA_EXPIRA_SEGUNDOS: i64 = 300;
+
+// Resultado do envio à SEFAZ
+pub struct Autorizacao {
+    // "autorizada", "rejeitada" ou "simulada" (stub)
+    pub status: String,
+    pub protocolo: Option<String>,
+    pub mensagem: String,
+}
+
+// Assinatura (certificado A1 do emitente) e transmissão para autorização
+#[async_trait]
+pub trait TransmissorSefaz: Send + Sync {
+    async fn assinar(&self, xml: &str) -> Result<String, String>;
+    async fn autorizar(&self, chave: &str, xml_assinado: &str) -> Result<Autorizacao, String>;
+}
+
+// Transmissor local para desenvolvimento: não assina nem envia, só registra o protocolo simulado
+pub struct StubSefaz;
+
+#[async_trait]
+impl TransmissorSefaz for StubSefaz {
+    async fn assinar(&self, xml: &str) -> Result<String, String> {
+        Ok(xml.to_string())
+    }
+
+    async fn autorizar(&self, chave: &str, _xml_assinado: &str) -> Result<Autorizacao, String> {
+        Ok(Autorizacao {
+            status: "simulada".to_string(),
+            protocolo: Some(format!("STUB{}", &chave[chave.len() - 11..])),
+            mensagem: "Transmissor local: documento não enviado à SEFAZ".to_string(),
+        })
+    }
+}
+
+pub fn from_config(config: &Config) -> Arc<dyn TransmissorSefaz> {
+    if config.nfce.transmissor != "stub" {
+        eprintln!("[nfce] Transmissor {:?} não disponível; usando o stub local", config.nfce.transmissor);
+    }
+    Arc::new(StubSefaz)
+}
+
+// Dígito verificador da chave de acesso: módulo 11 com pesos 2..9 da direita para a esquerda.
+// Letras (CNPJ alfanumérico) valem o código ASCII menos 48.
+pub fn digito_chave(chave43: &str) -> u32 {
+    let soma: u32 = chave43
+        .bytes()
+        .rev()
+        .enumerate()
+        .map(|(i, b)| (b - b'0') as u32 * (2 + (i as u32 % 8)))
+        .sum();
+    match soma % 11 {
+        0 | 1 => 0,
+        r => 11 - r,
+    }
+}
+
+// cUF + AAMM + CNPJ + modelo + série + número + tpEmis (1 = normal) + cNF + DV (44 posições)
+pub fn montar_chave(cuf: &str, aamm: &str, cnpj: &str, serie: i64, numero: i64, cnf: u32) -> String {
+    let base = format!("{}{}{}{}{:03}{:09}1{:08}", cuf, aamm, cnpj, MODELO, serie, numero, cnf);
+    format!("{}{}", base, digito_chave(&base))
+}
+
+// Centavos em decimal com 2 casas ("1099" → "10.99")
+fn valor(cents: i64) -> String {
+    format!("{}.{:02}", cents / 100, cents % 100)
+}
+
+// Meio de pagamento (tPag): 03 crédito, 04 débito, 17 PIX, 99 outros
+fn t_pag(metodo: &str) -> &'static str {
+    match metodo {
+        "pix" => "17",
+        "credit" => "03",
+        "debit" => "04",
+        _ => "99",
+    }
+}
+
+pub struct ItemNota {
+    pub product_id: i64,
+    pub nome: String,
+    pub ncm: String,
+    pub cfop: String,
+    pub origem: i64,
+    pub cst: String,
+    pub aliquota_bps: i64,
+    pub qty: i64,
+    pub unit_cents: i64,
+}
+
+pub struct DadosNota {
+    pub chave: String,
+    pub numero: i64,
+    // Data/hora de emissão já com o fuso (AAAA-MM-DDThh:mm:ss-03:00)
+    pub dh_emi: String,
+    // Comprador (CPF ou CNPJ sem máscara), quando informado
+    pub destinatario: Option<(documento::TipoDocumento, String)>,
+    pub payment_method: String,
+    // Pedido com entrega em domicílio (indPres 4); sem janela de entrega é retirada na loja (indPres 1)
+    pub entrega: bool,
+    pub itens: Vec<ItemNota>,
+}
+
+fn icms_xml(item: &ItemNota, v_prod: i64) -> (String, i64, i64) {
+    if item.cst.len() == 3 {
+        let xml = format!("<ICMSSN102><orig>{}</orig><CSOSN>{}</CSOSN></ICMSSN102>", item.origem, item.cst);
+        return (xml, 0, 0);
+    }
+    if item.cst == "00" {
+        let v_icms = (v_prod * item.aliquota_bps + 5000) / 10000;
+        let xml = format!(
+            "<ICMS00><orig>{}</orig><CST>00</CST><modBC>3</modBC><vBC>{}</vBC><pICMS>{}</pICMS><vICMS>{}</vICMS></ICMS00>",
+            item.origem,
+            valor(v_prod),
+            valor(item.aliquota_bps),
+            valor(v_icms)
+        );
+        return (xml, v_prod, v_icms);
+    }
+    (format!("<ICMS40><orig>{}</orig><CST>{}</CST></ICMS40>", item.origem, item.cst), 0, 0)
+}
+
+// Monta o XML da NFC-e (sem assinatura)
+pub fn build_xml(cfg: &NfceConfig, nota: &DadosNota) -> String {
+    let cuf = &nota.chave[..2];
+    let cnf = &nota.chave[35..43];
+    let c_dv = &nota.chave[43..];
+    let mut x = String::new();
+    x.push_str(&format!("<?xml version=\"1.0\" encoding=\"UTF-8\"?><NFe xmlns=\"{}\"><infNFe versao=\"4.00\" Id=\"NFe{}\">", NAMESPACE, nota.chave));
+    x.push_str(&format!(
+        "<ide><cUF>{}</cUF><cNF>{}</cNF><natOp>VENDA</natOp><mod>{}</mod><serie>{}</serie><nNF>{}</nNF><dhEmi>{}</dhEmi>\
+         <tpNF>1</tpNF><idDest>1</idDest><cMunFG>{}</cMunFG><tpImp>4</tpImp><tpEmis>1</tpEmis><cDV>{}</cDV><tpAmb>{}</tpAmb>\
+         <finNFe>1</finNFe><indFinal>1</indFinal><indPres>{}</indPres><procEmi>0</procEmi><verProc>mercado-backend</verProc></ide>",
+        cuf, cnf, MODELO, cfg.serie, nota.numero, nota.dh_emi, cfg.municipio_codigo, c_dv, cfg.ambiente, if nota.entrega { 4 } else { 1 }
+    ));
+    x.push_str(&format!(
+        "<emit><CNPJ>{}</CNPJ><xNome>{}</xNome><enderEmit><xLgr>{}</xLgr><nro>{}</nro><xBairro>{}</xBairro><cMun>{}</cMun>\
+         <xMun>{}</xMun><UF>{}</UF><CEP>{}</CEP><cPais>1058</cPais><xPais>BRASIL</xPais></enderEmit><IE>{}</IE><CRT>{}</CRT></emit>",
+        xml_escape(&cfg.cnpj),
+        xml_escape(&cfg.razao_social),
+        xml_escape(&cfg.logradouro),
+        xml_escape(&cfg.numero),
+        xml_escape(&cfg.bairro),
+        xml_escape(&cfg.municipio_codigo),
+        xml_escape(&cfg.municipio),
+        xml_escape(&cfg.uf),
+        xml_escape(&cfg.cep),
+        xml_escape(&cfg.ie),
+        cfg.crt
+    ));
+    if let Some((tipo, doc)) = &nota.destinatario {
+        let tag = match tipo {
+            documento::TipoDocumento::Cpf => "CPF",
+            documento::TipoDocumento::Cnpj => "CNPJ",
+        };
+        x.push_str(&format!("<dest><{tag}>{}</{tag}><indIEDest>9</indIEDest></dest>", doc, tag = tag));
+    }
+
+    let (mut t_prod, mut t_bc, mut t_icms) = (0i64, 0i64, 0i64);
+    for (i, item) in nota.itens.iter().enumerate() {
+        let v_prod = item.unit_cents * item.qty;
+        let nome = if i == 0 && cfg.ambiente == 2 { XPROD_HOMOLOGACAO.to_string() } else { xml_escape(&item.nome) };
+        let (icms, v_bc, v_icms) = icms_xml(item, v_prod);
+        t_prod += v_prod;
+        t_bc += v_bc;
+        t_icms += v_icms;
+        x.push_str(&format!(
+            "<det nItem=\"{}\"><prod><cProd>{}</cProd><cEAN>SEM GTIN</cEAN><xProd>{}</xProd><NCM>{}</NCM><CFOP>{}</CFOP>\
+             <uCom>UN</uCom><qCom>{}.0000</qCom><vUnCom>{}</vUnCom><vProd>{}</vProd><cEANTrib>SEM GTIN</cEANTrib><uTrib>UN</uTrib>\
+             <qTrib>{}.0000</qTrib><vUnTrib>{}</vUnTrib><indTot>1</indTot></prod><imposto><ICMS>{}</ICMS></imposto></det>",
+            i + 1,
+            item.product_id,
+            nome,
+            item.ncm,
+            item.cfop,
+            item.qty,
+            valor(item.unit_cents),
+            valor(v_prod),
+            item.qty,
+            valor(item.unit_cents),
+            icms
+        ));
+    }
+
+    let zero = valor(0);
+    x.push_str(&format!(
+        "<total><ICMSTot><vBC>{bc}</vBC><vICMS>{icms}</vICMS><vICMSDeson>{z}</vICMSDeson><vFCP>{z}</vFCP><vBCST>{z}</vBCST>\
+         <vST>{z}</vST><vFCPST>{z}</vFCPST><vFCPSTRet>{z}</vFCPSTRet><vProd>{prod}</vProd><vFrete>{z}</vFrete><vSeg>{z}</vSeg>\
+         <vDesc>{z}</vDesc><vII>{z}</vII><vIPI>{z}</vIPI><vIPIDevol>{z}</vIPIDevol><vPIS>{z}</vPIS><vCOFINS>{z}</vCOFINS>\
+         <vOutro>{z}</vOutro><vNF>{prod}</vNF></ICMSTot></total>",
+        bc = valor(t_bc),
+        icms = valor(t_icms),
+        prod = valor(t_prod),
+        z = zero
+    ));
+    x.push_str("<transp><modFrete>9</modFrete></transp>");
+    x.push_str(&format!(
+        "<pag><detPag><tPag>{}</tPag><vPag>{}</vPag></detPag></pag>",
+        t_pag(&nota.payment_method),
+        valor(t_prod)
+    ));
+    x.push_str("</infNFe></NFe>");
+    x
+}
+
+// "-03:00" → modificador de data do SQLite ("-180 minutes"); None fora do formato ±HH:MM
+pub fn offset_modifier(offset: &str) -> Option<String> {
+    let (sinal, resto) = match offset.strip_prefix('+') {
+        Some(r) => ('+', r),
+        None => ('-', offset.strip_prefix('-')?),
+    };
+    let (h, m) = resto.split_once(':')?;
+    if h.len() != 2 || m.len() != 2 || !h.bytes().chain(m.bytes()).all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let (h, m) = (h.parse::<i64>().ok()?, m.parse::<i64>().ok()?);
+    (h <= 14 && m <= 59).then(|| format!("{}{} minutes", sinal, h * 60 + m))
+}
+
+// Status e identificação da nota de um pedido
+async fn resumo(db: &SqlitePool, pedido_id: &str) -> Result<Option<Value>, sqlx::Error> {
+    let row = sqlx::query("SELECT serie, numero, chave, status, protocolo, mensagem, created_at FROM nfce_documentos WHERE pedido_id = ?")
+        .bind(pedido_id)
+        .fetch_optional(db)
+        .await?;
+    Ok(row.map(|r| {
+        json!({
+            "pedido_id": pedido_id,
+            "serie": r.try_get::<i64, _>("serie").unwrap_or(0),
+            "numero": r.try_get::<i64, _>("numero").unwrap_or(0),
+            "chave": r.try_get::<String, _>("chave").unwrap_or_default(),
+            "status": r.try_get::<String, _>("status").unwrap_or_default(),
+            "protocolo": r.try_get::<Option<String>, _>("protocolo").unwrap_or(None),
+            "mensagem": r.try_get::<Option<String>, _>("mensagem").unwrap_or(None),
+            "created_at": r.try_get::<String, _>("created_at").unwrap_or_default(),
+        })
+    }))
+}
+
+// Assina (se ainda não assinado) e transmite o XML da nota, registrando o resultado.
+// Falhas ficam com status erro_assinatura/erro_transmissao e podem ser reenviadas.
+async fn transmitir(app_state: &AppState, pedido_id: &str, chave: &str, xml: String, assinado: bool) -> Result<String, sqlx::Error> {
+    let assinatura = if assinado { Ok(xml.clone()) } else { app_state.sefaz.assinar(&xml).await };
+    let (status, protocolo, mensagem, xml_final) = match assinatura {
+        Ok(assinado) => match app_state.sefaz.autorizar(chave, &assinado).await {
+            Ok(a) => (a.status, a.protocolo, a.mensagem, assinado),
+            Err(e) => ("erro_transmissao".to_string(), None, e, assinado),
+        },
+        Err(e) => ("erro_assinatura".to_string(), None, e, xml),
+    };
+    sqlx::query("UPDATE nfce_documentos SET xml = ?, status = ?, protocolo = ?, mensagem = ?, updated_at = CURRENT_TIMESTAMP WHERE pedido_id = ?")
+        .bind(&xml_final)
+        .bind(&status)
+        .bind(&protocolo)
+        .bind(&mensagem)
+        .bind(pedido_id)
+        .execute(&app_state.db)
+        .await?;
+    Ok(status)
+}
+
+// POST /api/admin/pedidos/:id/nfce: gera, assina e transmite a NFC-e do pedido. Idempotente para
+// notas já transmitidas; com erro de assinatura ou de transmissão, ou presa em "gerada", reenvia o XML armazenado.
+pub async fn emitir_nfce(State(app_state): State<AppState>, Path(pedido_id): Path<String>) -> Result<Response, ApiError> {
+    let db_err = |e: sqlx::Error| ApiError::internal_server_error(&format!("Erro ao emitir NFC-e: {}", e));
+    let db = &app_state.db;
+    let cfg = &app_state.config.nfce;
+
+    let existente = resumo(db, &pedido_id).await.map_err(db_err)?;
+    let reenvio = existente
+        .as_ref()
+        .and_then(|n| n["status"].as_str())
+        .filter(|s| ERROS_REENVIAVEIS.contains(s) || *s == "gerada")
+        .map(str::to_string);
+    if let (Some(nota), None) = (existente, &reenvio) {
+        return Ok(Json(nota).into_response());
+    }
+
+    let pedido = sqlx::query("SELECT status, payment_method, documento, delivery_slot_id FROM pedidos WHERE id = ?")
+        .bind(&pedido_id)
+        .fetch_optional(db)
+        .await
+        .map_err(db_err)?
+        .ok_or_else(|| ApiError::not_found("Pedido não encontrado"))?;
+    if pedido.try_get::<String, _>("status").unwrap_or_default() == "cancelled" {
+        return Err(ApiError::new(409, "Conflict", "Pedido cancelado não gera NFC-e"));
+    }
+
+    if let Some(status_anterior) = reenvio {
+        // Marca a nota como em emissão: duas requisições simultâneas não reenviam a mesma nota.
+        // "gerada" recente está em emissão por outra requisição; só é retomada depois do prazo.
+        let row = sqlx::query(
+            r#"UPDATE nfce_documentos SET status = 'gerada', updated_at = CURRENT_TIMESTAMP
+               WHERE pedido_id = ? AND status = ? AND (status <> 'gerada' OR updated_at <= datetime('now', ?))
+               RETURNING chave, xml"#,
+        )
+        .bind(&pedido_id)
+        .bind(&status_anterior)
+        .bind(format!("-{} seconds", GERADA_EXPIRA_SEGUNDOS))
+        .fetch_optional(db)
+        .await
+        .map_err(db_err)?
+        .ok_or_else(|| ApiError::new(409, "Conflict", "NFC-e em emissão por outra requisição; tente novamente"))?;
+        let chave: String = row.try_get("chave").unwrap_or_default();
+        let xml: String = row.try_get("xml").unwrap_or_default();
+        // Só o XML que passou pela assinatura leva o bloco <Signature>
+        let assinado = status_anterior == "erro_transmissao" || xml.contains("<Signature");
+        let status = transmitir(&app_state, &pedido_id, &chave, xml, assinado)
+            .await
+            .map_err(db_err)?;
+        println!("[nfce] Pedido {} → NFC-e reenviada após {} ({})", pedido_id, status_anterior, status);
+        let corpo = resumo(db, &pedido_id).await.map_err(db_err)?.unwrap_or(Value::Null);
+        return Ok(Json(corpo).into_response());
+    }
+
+    let Some((_, cuf)) = UF_CODIGOS.iter().find(|(uf, _)| *uf == cfg.uf) else {
+        return Err(ApiError::internal_server_error("NFCE_UF inválida"));
+    };
+    if !documento::cnpj_valido(&cfg.cnpj) {
+        return Err(ApiError::internal_server_error("NFCE_CNPJ inválido"));
+    }
+
+    let rows = sqlx::query(
+        r#"SELECT i.product_id, i.qty, i.unit_price_cents, i.product_name, p.ncm, p.cfop, p.icms_origem, p.icms_cst, p.icms_aliquota_bps
+           FROM itens_pedido i LEFT JOIN produtos p ON p.id = i.product_id
+           WHERE i.pedido_id = ? ORDER BY i.id"#,
+    )
+    .bind(&pedido_id)
+    .fetch_all(db)
+    .await
+    .map_err(db_err)?;
+    let mut itens = Vec::new();
+    let mut pendentes = Vec::new();
+    for r in rows {
+        let product_id: i64 = r.try_get("product_id").unwrap_or(0);
+        let ncm: Option<String> = r.try_get("ncm").unwrap_or(None);
+        let cst: String = r.try_get("icms_cst").unwrap_or_default();
+        // CSOSN só no Simples Nacional (CRT 1); CST só no regime normal
+        let cst_ok = if cfg.crt == 1 { CSOSN.contains(&cst.as_str()) } else { CST_NORMAL.contains(&cst.as_str()) };
+        let Some(ncm) = ncm.filter(|n| n.len() == 8) else {
+            pendentes.push(format!("produto {} sem NCM", product_id));
+            continue;
+        };
+        if !cst_ok {
+            pendentes.push(format!("produto {} com CST/CSOSN {} incompatível com o regime do emitente", product_id, cst));
+            continue;
+        }
+        itens.push(ItemNota {
+            product_id,
+            nome: r.try_get::<Option<String>, _>("product_name").unwrap_or(None).unwrap_or_else(|| format!("Produto {}", product_id)),
+            ncm,
+            cfop: r.try_get("cfop").unwrap_or_default(),
+            origem: r.try_get("icms_origem").unwrap_or(0),
+            cst,
+            aliquota_bps: r.try_get("icms_aliquota_bps").unwrap_or(0),
+            qty: r.try_get("qty").unwrap_or(0),
+            unit_cents: r.try_get("unit_price_cents").unwrap_or(0),
+        });
+    }
+    if !pendentes.is_empty() {
+        return Err(ApiError::validation_error("itens", &format!("Dados fiscais incompletos: {}", pendentes.join("; "))));
+    }
+    if itens.is_empty() {
+        return Err(ApiError::bad_request("Pedido sem itens"));
+    }
+
+    let offset = offset_modifier(&cfg.utc_offset).unwrap_or_else(|| "-180 minutes".to_string());
+    let dh_local: String = sqlx::query_scalar("SELECT strftime('%Y-%m-%dT%H:%M:%S', 'now', ?)")
+        .bind(&offset)
+        .fetch_one(db)
+        .await
+        .map_err(db_err)?;
+    let aamm = format!("{}{}", &dh_local[2..4], &dh_local[5..7]);
+    let destinatario = pedido
+        .try_get::<Option<String>, _>("documento")
+        .unwrap_or(None)
+        .and_then(|d| documento::validar(&d));
+    let payment_method: String = pedido.try_get("payment_method").unwrap_or_default();
+
+    // Numeração sequencial por série; a chave única do pedido impede nota duplicada
+    let mut tx = db.begin().await.map_err(db_err)?;
+    let numero: i64 = sqlx::query_scalar("SELECT COALESCE(MAX(numero), 0) + 1 FROM nfce_documentos WHERE serie = ?")
+        .bind(cfg.serie)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(db_err)?;
+    // cNF aleatório de 8 dígitos, diferente do número da nota
+    let mut cnf: u32 = rand::random::<u32>() % 100_000_000;
+    if cnf as i64 == numero {
+        cnf = (cnf + 1) % 100_000_000;
+    }
+    let chave = montar_chave(cuf, &aamm, &cfg.cnpj, cfg.serie, numero, cnf);
+    let nota = DadosNota {
+        chave: chave.clone(),
+        numero,
+        dh_emi: format!("{}{}", dh_local, cfg.utc_offset),
+        destinatario,
+        payment_method,
+        entrega: pedido.try_get::<Option<String>, _>("delivery_slot_id").unwrap_or(None).is_some(),
+        itens,
+    };
+    let xml = build_xml(cfg, &nota);
+    let inserido = sqlx::query("INSERT INTO nfce_documentos (pedido_id, serie, numero, chave, xml, status) VALUES (?, ?, ?, ?, ?, 'gerada')")
+        .bind(&pedido_id)
+        .bind(cfg.serie)
+        .bind(numero)
+        .bind(&chave)
+        .bind(&xml)
+        .execute(&mut *tx)
+        .await;
+    match inserido {
+        Ok(_) => {}
+        Err(sqlx::Error::Database(e)) if e.is_unique_violation() => {
+            return Err(ApiError::new(409, "Conflict", "NFC-e em emissão por outra requisição; tente novamente"));
+        }
+        Err(e) => return Err(db_err(e)),
+    }
+    tx.commit().await.map_err(db_err)?;
+
+    // Assinatura e autorização; falhas ficam registradas e a nota pode ser consultada
+    let status = transmitir(&app_state, &pedido_id, &chave, xml, false).await.map_err(db_err)?;
+    println!("[nfce] Pedido {} → NFC-e {} série {} ({})", pedido_id, numero, cfg.serie, status);
+
+    let corpo = resumo(db, &pedido_id).await.map_err(db_err)?.unwrap_or(Value::Null);
+    Ok((StatusCode::CREATED, Json(corpo)).into_response())
+}
+
+// GET /api/admin/pedidos/:id/nfce: XML armazenado
+pub async fn obter_nfce_xml(State(app_state): State<AppState>, Path(pedido_id): Path<String>) -> Result<Response, ApiError> {
+    let xml: Option<String> = sqlx::query_scalar("SELECT xml FROM nfce_documentos WHERE pedido_id = ?")
+        .bind(&pedido_id)
+        .fetch_optional(&app_state.db)
+        .await
+        .map_err(|e| ApiError::internal_server_error(&format!("Erro ao buscar NFC-e: {}", e)))?;
+    let xml = xml.ok_or_else(|| ApiError::not_found("NFC-e não emitida para este pedido"))?;
+    Ok(([(CONTENT_TYPE, "application/xml; charset=utf-8")], xml).into_response())
+}
+
+#[derive(Deserialize)]
+pub struct FiscalInput {
+    pub ncm: String,
+    pub cfop: String,
+    pub icms_origem: Option<i64>,
+    pub icms_cst: String,
+    // Percentual (ex.: 18 ou 18.5); só usado com CST 00
+    pub icms_aliquota: Option<f64>,
+}
+
+// PATCH /api/admin/products/:id/fiscal: NCM, CFOP e ICMS do produto
+pub async fn update_fiscal(
+    State(app_state): State<AppState>,
+    Path(product_id): Path<i64>,
+    Json(input): Json<FiscalInput>,
+) -> Result<Json<Value>, ApiError> {
+    let ncm = documento::limpar(&input.ncm);
+    if ncm.len() != 8 || !ncm.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(ApiError::validation_error("ncm", "NCM deve ter 8 dígitos"));
+    }
+    let cfop = input.cfop.trim();
+    // Venda a consumidor final: operações internas (5xxx); NFC-e não admite interestaduais
+    if cfop.len() != 4 || !cfop.bytes().all(|b| b.is_ascii_digit()) || !cfop.starts_with('5') {
+        return Err(ApiError::validation_error("cfop", "CFOP deve ter 4 dígitos e começar com 5"));
+    }
+    let origem = input.icms_origem.unwrap_or(0);
+    if !(0..=8).contains(&origem) {
+        return Err(ApiError::validation_error("icms_origem", "Origem da mercadoria deve estar entre 0 e 8"));
+    }
+    let cst = input.icms_cst.trim();
+    if !CST_NORMAL.contains(&cst) && !CSOSN.contains(&cst) {
+        return Err(ApiError::validation_error(
+            "icms_cst",
+            &format!("Use CST {} ou CSOSN {}", CST_NORMAL.join("/"), CSOSN.join("/")),
+        ));
+    }
+    let aliquota = input.icms_aliquota.unwrap_or(0.0);
+    if !(0.0..=100.0).contains(&aliquota) {
+        return Err(ApiError::validation_error("icms_aliquota", "Alíquota deve estar entre 0 e 100"));
+    }
+    let aliquota_bps = (aliquota * 100.0).round() as i64;
+
+    let res = sqlx::query("UPDATE produtos SET ncm = ?, cfop = ?, icms_origem = ?, icms_cst = ?, icms_aliquota_bps = ? WHERE id = ?")
+        .bind(&ncm)
+        .bind(cfop)
+        .bind(origem)
+        .bind(cst)
+        .bind(aliquota_bps)
+        .bind(product_id)
+        .execute(&app_state.db)
+        .await
+        .map_err(|e| ApiError::internal_server_error(&format!("Erro ao salvar dados fiscais: {}", e)))?;
+    if res.rows_affected() == 0 {
+        return Err(ApiError::not_found("Produto não encontrado"));
+    }
+    Ok(Json(json!({
+        "product_id": product_id,
+        "ncm": ncm,
+        "cfop": cfop,
+        "icms_origem": origem,
+        "icms_cst": cst,
+        "icms_aliquota": valor(aliquota_bps),
+    })))
+}
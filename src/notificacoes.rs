@@ -0,0 +1,292 @@
+use axum::{
+    extract::{Query, State},
+    response::Json,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::{Row, SqliteConnection, SqlitePool};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::mail::{Email, Mailer};
+use crate::{format_brl, metodo_label, ApiError, AppState};
+
+// E-mails de acompanhamento do pedido (outbox): a mensagem é gravada em email_outbox na mesma
+// transação que altera o pedido e um worker em segundo plano faz o envio pelo Mailer
+// configurado. Falhas voltam para a fila com espera crescente até o limite de tentativas.
+const LOTE: i64 = 20;
+const BACKOFF_MAX_SECS: i64 = 60 * 60;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Evento {
+    PedidoConfirmado,
+    PagamentoRecebido,
+    PedidoEnviado,
+    PedidoCancelado,
+}
+
+impl Evento {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Evento::PedidoConfirmado => "pedido_confirmado",
+            Evento::PagamentoRecebido => "pagamento_recebido",
+            Evento::PedidoEnviado => "pedido_enviado",
+            Evento::PedidoCancelado => "pedido_cancelado",
+        }
+    }
+}
+
+pub async fn init_notificacoes(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS email_outbox (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            evento TEXT NOT NULL,
+            pedido_id TEXT NOT NULL,
+            para TEXT NOT NULL,
+            assunto TEXT NOT NULL,
+            corpo TEXT NOT NULL,
+            status TEXT NOT NULL DEFAULT 'pendente',
+            tentativas INTEGER NOT NULL DEFAULT 0,
+            proxima_tentativa_em TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            ultimo_erro TEXT NULL,
+            created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            enviado_em TIMESTAMP NULL,
+            UNIQUE(evento, pedido_id)
+        );
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_email_outbox_fila ON email_outbox (status, proxima_tentativa_em)")
+        .execute(pool)
+        .await?;
+    // Mensagens interrompidas no meio do envio (servidor reiniciado) voltam para a fila
+    sqlx::query("UPDATE email_outbox SET status = 'pendente' WHERE status = 'enviando'")
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+// Grava o e-mail do evento na fila, dentro da transação de quem chama. Pedidos sem conta
+// ativa (anônimos ou de contas excluídas) não geram mensagem; o mesmo evento não se repete.
+pub async fn enfileirar(conn: &mut SqliteConnection, evento: Evento, pedido_id: &str, app_base_url: &str) -> Result<(), sqlx::Error> {
+    let row = sqlx::query(
+        r#"SELECT p.total_cents, p.total_with_interest_cents, p.payment_method, p.payment_installments, u.nome, u.email
+           FROM pedidos p JOIN usuarios u ON u.id = p.user_id
+           WHERE p.id = ? AND u.deleted_at IS NULL"#,
+    )
+    .bind(pedido_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+    let Some(row) = row else { return Ok(()) };
+
+    let nome: String = row.try_get("nome").unwrap_or_default();
+    let para: String = row.try_get("email").unwrap_or_default();
+    let total_cents: i64 = row.try_get("total_cents").unwrap_or(0);
+    let com_juros: i64 = row.try_get("total_with_interest_cents").unwrap_or(0);
+    let total = if com_juros > 0 { com_juros } else { total_cents };
+    let metodo: String = row.try_get("payment_method").unwrap_or_default();
+    let parcelas: Option<i64> = row.try_get("payment_installments").unwrap_or(None);
+    let curto: String = pedido_id.chars().take(8).collect();
+    let recibo = format!("{}/api/pedidos/{}/recibo.pdf", app_base_url, pedido_id);
+
+    let (assunto, texto) = match evento {
+        Evento::PedidoConfirmado => {
+            let itens = sqlx::query(
+                r#"SELECT qty, unit_price_cents, product_name
+                   FROM itens_pedido
+                   WHERE pedido_id = ? ORDER BY id"#,
+            )
+            .bind(pedido_id)
+            .fetch_all(&mut *conn)
+            .await?;
+            let linhas: Vec<String> = itens
+                .iter()
+                .map(|i| {
+                    let qty: i64 = i.try_get("qty").unwrap_or(0);
+                    let unit: i64 = i.try_get("unit_price_cents").unwrap_or(0);
+                    let nome: Option<String> = i.try_get("product_name").unwrap_or(None);
+                    format!("  {} x {} — {}", qty, nome.unwrap_or_else(|| "Produto".to_string()), format_brl(unit * qty))
+                })
+                .collect();
+            (
+                format!("Pedido {} confirmado - Mercado Online", curto),
+                format!(
+                    "Recebemos seu pedido {}.\n\nItens:\n{}\n\nTotal: {}\n\nRecibo: {}",
+                    pedido_id,
+                    linhas.join("\n"),
+                    format_brl(total),
+                    recibo
+                ),
+            )
+        }
+        Evento::PagamentoRecebido => {
+            let forma = match parcelas.filter(|n| *n > 1) {
+                Some(n) => format!("{} em {}x", metodo_label(&metodo), n),
+                None => metodo_label(&metodo).to_string(),
+            };
+            (
+                format!("Pagamento do pedido {} recebido - Mercado Online", curto),
+                format!("Confirmamos o pagamento de {} via {} referente ao pedido {}.\n\nRecibo: {}", format_brl(total), forma, pedido_id, recibo),
+            )
+        }
+        Evento::PedidoEnviado => (
+            format!("Pedido {} saiu para entrega - Mercado Online", curto),
+            format!("Seu pedido {} foi separado e saiu para entrega.\n\nRecibo: {}", pedido_id, recibo),
+        ),
+        Evento::PedidoCancelado => (
+            format!("Pedido {} cancelado - Mercado Online", curto),
+            format!(
+                "O pedido {} foi cancelado.\n\nSe o pagamento já tinha sido feito, o estorno de {} segue pela mesma forma de pagamento ({}).",
+                pedido_id,
+                format_brl(total),
+                metodo_label(&metodo)
+            ),
+        ),
+    };
+    let corpo = format!("Olá, {}!\n\n{}\n\nObrigado por comprar no Mercado Online.", nome, texto);
+
+    sqlx::query("INSERT OR IGNORE INTO email_outbox (evento, pedido_id, para, assunto, corpo) VALUES (?, ?, ?, ?, ?)")
+        .bind(evento.as_str())
+        .bind(pedido_id)
+        .bind(&para)
+        .bind(&assunto)
+        .bind(&corpo)
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+// Espera antes da próxima tentativa: base × 2^(tentativas - 1), limitada a 1 hora
+pub fn backoff_secs(base: i64, tentativas: i64) -> i64 {
+    let expoente = (tentativas - 1).clamp(0, 20) as u32;
+    base.saturating_mul(2_i64.saturating_pow(expoente)).min(BACKOFF_MAX_SECS)
+}
+
+// Envia um lote de mensagens vencidas; devolve quantas foram processadas
+pub async fn processar_fila(db: &SqlitePool, mailer: &dyn Mailer, max_tentativas: i64, backoff_base: i64) -> Result<usize, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"SELECT id, para, assunto, corpo, tentativas FROM email_outbox
+           WHERE status = 'pendente' AND proxima_tentativa_em <= CURRENT_TIMESTAMP
+           ORDER BY id LIMIT ?"#,
+    )
+    .bind(LOTE)
+    .fetch_all(db)
+    .await?;
+
+    let mut processadas = 0;
+    for row in rows {
+        let id: i64 = row.try_get("id").unwrap_or(0);
+        // Reserva a mensagem; se outra instância já pegou, segue para a próxima
+        let reservada = sqlx::query("UPDATE email_outbox SET status = 'enviando' WHERE id = ? AND status = 'pendente'")
+            .bind(id)
+            .execute(db)
+            .await?;
+        if reservada.rows_affected() == 0 {
+            continue;
+        }
+        let email = Email {
+            para: row.try_get("para").unwrap_or_default(),
+            assunto: row.try_get("assunto").unwrap_or_default(),
+            corpo: row.try_get("corpo").unwrap_or_default(),
+        };
+        let tentativas: i64 = row.try_get::<i64, _>("tentativas").unwrap_or(0) + 1;
+        match mailer.send(&email).await {
+            Ok(()) => {
+                sqlx::query("UPDATE email_outbox SET status = 'enviado', tentativas = ?, ultimo_erro = NULL, enviado_em = CURRENT_TIMESTAMP WHERE id = ?")
+                    .bind(tentativas)
+                    .bind(id)
+                    .execute(db)
+                    .await?;
+            }
+            Err(e) => {
+                let (status, espera) = if tentativas >= max_tentativas {
+                    ("falhou", 0)
+                } else {
+                    ("pendente", backoff_secs(backoff_base, tentativas))
+                };
+                eprintln!("[outbox] Falha ao enviar mensagem {} (tentativa {}): {}", id, tentativas, e);
+                sqlx::query(
+                    "UPDATE email_outbox SET status = ?, tentativas = ?, ultimo_erro = ?, proxima_tentativa_em = datetime('now', ?) WHERE id = ?",
+                )
+                .bind(status)
+                .bind(tentativas)
+                .bind(&e)
+                .bind(format!("+{} seconds", espera))
+                .bind(id)
+                .execute(db)
+                .await?;
+            }
+        }
+        processadas += 1;
+    }
+    Ok(processadas)
+}
+
+// Worker em segundo plano: lê a fila a cada OUTBOX_POLL_SECS (sem esperar se o lote veio cheio)
+pub fn iniciar_worker(db: SqlitePool, mailer: Arc<dyn Mailer>, poll_secs: u64, max_tentativas: i64, backoff_base: i64) {
+    tokio::spawn(async move {
+        loop {
+            match processar_fila(&db, mailer.as_ref(), max_tentativas, backoff_base).await {
+                Ok(n) if n as i64 >= LOTE => continue,
+                Ok(_) => {}
+                Err(e) => eprintln!("[outbox] Erro ao ler a fila de e-mails: {}", e),
+            }
+            tokio::time::sleep(Duration::from_secs(poll_secs)).await;
+        }
+    });
+}
+
+#[derive(Deserialize)]
+pub struct OutboxQuery {
+    pub status: Option<String>,
+    pub pedido_id: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct OutboxRow {
+    id: i64,
+    evento: String,
+    pedido_id: String,
+    para: String,
+    assunto: String,
+    status: String,
+    tentativas: i64,
+    proxima_tentativa_em: String,
+    ultimo_erro: Option<String>,
+    created_at: String,
+    enviado_em: Option<String>,
+}
+
+// GET /api/admin/email-outbox?status=&pedido_id=: acompanhamento da fila (últimas 200)
+pub async fn list_outbox(State(app_state): State<AppState>, Query(q): Query<OutboxQuery>) -> Result<Json<Vec<OutboxRow>>, ApiError> {
+    let rows = sqlx::query(
+        r#"SELECT id, evento, pedido_id, para, assunto, status, tentativas, proxima_tentativa_em, ultimo_erro, created_at, enviado_em
+           FROM email_outbox
+           WHERE (?1 IS NULL OR status = ?1) AND (?2 IS NULL OR pedido_id = ?2)
+           ORDER BY id DESC LIMIT 200"#,
+    )
+    .bind(q.status.as_deref().map(str::trim).filter(|s| !s.is_empty()))
+    .bind(q.pedido_id.as_deref().map(str::trim).filter(|s| !s.is_empty()))
+    .fetch_all(&app_state.db)
+    .await
+    .map_err(|e| ApiError::internal_server_error(&format!("Erro ao listar e-mails: {}", e)))?;
+
+    let mut result = Vec::new();
+    for row in rows {
+        result.push(OutboxRow {
+            id: row.try_get("id").unwrap_or(0),
+            evento: row.try_get("evento").unwrap_or_default(),
+            pedido_id: row.try_get("pedido_id").unwrap_or_default(),
+            para: row.try_get("para").unwrap_or_default(),
+            assunto: row.try_get("assunto").unwrap_or_default(),
+            status: row.try_get("status").unwrap_or_default(),
+            tentativas: row.try_get("tentativas").unwrap_or(0),
+            proxima_tentativa_em: row.try_get("proxima_tentativa_em").unwrap_or_default(),
+            ultimo_erro: row.try_get("ultimo_erro").unwrap_or(None),
+            created_at: row.try_get("created_at").unwrap_or_default(),
+            enviado_em: row.try_get("enviado_em").unwrap_or(None),
+        });
+    }
+    Ok(Json(result))
+}
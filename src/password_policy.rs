@@ -0,0 +1,80 @@
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+use crate::config::Config;
+
+// Lista local de senhas comuns/vazadas (embutida no binário)
+const SENHAS_COMUNS: &str = include_str!("../data/senhas_comuns.txt");
+
+// bcrypt ignora tudo após 72 bytes: senhas maiores seriam truncadas silenciosamente
+const MAX_PASSWORD_BYTES: usize = 72;
+const MAX_NOME_CHARS: usize = 100;
+
+fn senhas_comuns() -> &'static Vec<String> {
+    static LISTA: OnceLock<Vec<String>> = OnceLock::new();
+    LISTA.get_or_init(|| {
+        SENHAS_COMUNS
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty() && !l.starts_with('#'))
+            .map(str::to_lowercase)
+            .collect()
+    })
+}
+
+fn email_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"^[^\s@]+@[^\s@]+\.[^\s@]+$").unwrap())
+}
+
+// E-mail normalizado para armazenamento e comparação: sem espaços nas pontas e em minúsculas
+pub fn normalize_email(email: &str) -> String {
+    email.trim().to_lowercase()
+}
+
+pub fn is_valid_email(email: &str) -> bool {
+    email.len() <= 254 && email_regex().is_match(email)
+}
+
+// Erros por campo (campo -> mensagem), devolvidos em respostas 422
+pub type FieldErrors = BTreeMap<&'static str, String>;
+
+// Verifica a senha conforme a política configurada. A senha é usada exatamente como digitada.
+pub fn check_password(config: &Config, senha: &str, email: &str) -> Option<String> {
+    if senha.chars().count() < config.password_min_length {
+        return Some(format!("A senha deve ter pelo menos {} caracteres", config.password_min_length));
+    }
+    if senha.len() > MAX_PASSWORD_BYTES {
+        return Some(format!("A senha deve ter no máximo {} bytes", MAX_PASSWORD_BYTES));
+    }
+    if senha.trim().is_empty() {
+        return Some("A senha não pode conter apenas espaços".to_string());
+    }
+    let lower = senha.to_lowercase();
+    if config.password_reject_common && senhas_comuns().contains(&lower) {
+        return Some("Senha muito comum, escolha outra".to_string());
+    }
+    let local = email.split('@').next().unwrap_or("");
+    if !local.is_empty() && (lower == normalize_email(email) || lower == local) {
+        return Some("A senha não pode ser igual ao e-mail".to_string());
+    }
+    None
+}
+
+// Valida os dados de cadastro; o e-mail já deve estar normalizado
+pub fn validate_registration(config: &Config, nome: &str, email: &str, senha: &str) -> FieldErrors {
+    let mut erros = FieldErrors::new();
+    if nome.is_empty() {
+        erros.insert("nome", "Informe o nome".to_string());
+    } else if nome.chars().count() > MAX_NOME_CHARS {
+        erros.insert("nome", format!("O nome deve ter no máximo {} caracteres", MAX_NOME_CHARS));
+    }
+    if !is_valid_email(email) {
+        erros.insert("email", "Formato de e-mail inválido".to_string());
+    }
+    if let Some(msg) = check_password(config, senha, email) {
+        erros.insert("senha", msg);
+    }
+    erros
+}
@@ -0,0 +1,226 @@
+use axum::{
+    extract::{ConnectInfo, State},
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+};
+use serde::Deserialize;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use sqlx::{Row, SqlitePool};
+use std::net::SocketAddr;
+use uuid::Uuid;
+
+use crate::email_verification::{self, ENVIO_REDEFINICAO};
+use crate::mail::Email;
+use crate::{password_policy, AppState};
+
+// Redefinição de senha: o token vai apenas no e-mail; no banco fica só o SHA-256 dele,
+// com validade e marca de uso (cada token vale uma única vez)
+pub async fn init_password_reset(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS password_resets (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            user_id INTEGER NOT NULL,
+            token_hash TEXT NOT NULL UNIQUE,
+            expires_at TIMESTAMP NOT NULL,
+            used_at TIMESTAMP,
+            created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            FOREIGN KEY(user_id) REFERENCES usuarios(id)
+        );
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub fn hash_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+// 244 bits aleatórios (dois UUID v4) em hexadecimal
+pub fn new_token() -> String {
+    format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
+
+fn erro_interno() -> Response {
+    (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"erro": "Erro interno"}))).into_response()
+}
+
+#[derive(Deserialize)]
+pub struct ForgotInput {
+    pub email: String,
+}
+
+// POST /api/password/forgot: sempre responde igual, exista ou não a conta. Limitado por e-mail
+// e por IP como o reenvio de verificação; token e e-mail saem fora da requisição para o tempo
+// de resposta também não revelar se a conta existe.
+pub async fn forgot_password(
+    State(app_state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Json(input): Json<ForgotInput>,
+) -> Response {
+    let email = password_policy::normalize_email(&input.email);
+    let ip = addr.ip().to_string();
+
+    match email_verification::resend_wait(&app_state, ENVIO_REDEFINICAO, &email, &ip).await {
+        Ok(Some(segundos)) => return email_verification::muitos_envios(segundos),
+        Ok(None) => {}
+        Err(e) => {
+            eprintln!("[auth] Erro ao verificar limite de redefinição: {}", e);
+            return erro_interno();
+        }
+    }
+
+    let user = match sqlx::query("SELECT id, email FROM usuarios WHERE lower(email) = ? LIMIT 1")
+        .bind(&email)
+        .fetch_optional(&app_state.db)
+        .await
+    {
+        Ok(r) => r,
+        Err(e) => {
+            eprintln!("[auth] Erro ao buscar usuário para redefinição: {}", e);
+            return erro_interno();
+        }
+    };
+    // O pedido conta para o limite exista ou não a conta
+    if let Err(e) = email_verification::record_send(&app_state.db, ENVIO_REDEFINICAO, &email, &ip).await {
+        eprintln!("[auth] Erro ao registrar pedido de redefinição: {}", e);
+        return erro_interno();
+    }
+    if let Some(user) = user {
+        let user_id: i64 = user.try_get("id").unwrap_or(0);
+        let email_db: String = user.try_get("email").unwrap_or_default();
+        let app_state = app_state.clone();
+        tokio::spawn(async move {
+            if let Err(e) = send_reset_link(&app_state, user_id, &email_db).await {
+                eprintln!("[mail] Falha ao enviar e-mail de redefinição: {}", e);
+            }
+        });
+    }
+
+    (
+        StatusCode::OK,
+        Json(json!({"status": "ok", "mensagem": "Se o e-mail estiver cadastrado, enviaremos um link para redefinir a senha"})),
+    )
+        .into_response()
+}
+
+// Gera um novo token (só o link mais recente vale: pedidos anteriores são invalidados) e envia o link
+async fn send_reset_link(app_state: &AppState, user_id: i64, email: &str) -> Result<(), String> {
+    let token = new_token();
+    let gravado = async {
+        let mut tx = app_state.db.begin().await?;
+        sqlx::query("UPDATE password_resets SET used_at = CURRENT_TIMESTAMP WHERE user_id = ? AND used_at IS NULL")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("INSERT INTO password_resets (user_id, token_hash, expires_at) VALUES (?, ?, datetime('now', ?))")
+            .bind(user_id)
+            .bind(hash_token(&token))
+            .bind(format!("+{} minutes", app_state.config.password_reset_ttl_mins))
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await
+    }
+    .await;
+    gravado.map_err(|e| format!("Erro ao gravar token de redefinição: {}", e))?;
+
+    let link = format!("{}/login?token={}", app_state.config.app_base_url, token);
+    let mensagem = Email {
+        para: email.to_string(),
+        assunto: "Redefinição de senha - Mercado Online".to_string(),
+        corpo: format!(
+            "Recebemos um pedido para redefinir a sua senha.\n\nAcesse o link abaixo em até {} minutos:\n{}\n\nSe não foi você, ignore este e-mail.",
+            app_state.config.password_reset_ttl_mins, link
+        ),
+    };
+    app_state.mailer.send(&mensagem).await
+}
+
+#[derive(Deserialize)]
+pub struct ResetInput {
+    pub token: String,
+    pub senha: String,
+}
+
+// POST /api/password/reset: troca a senha e encerra todas as sessões do usuário
+pub async fn reset_password(State(app_state): State<AppState>, Json(input): Json<ResetInput>) -> Response {
+    let invalido = || (StatusCode::BAD_REQUEST, Json(json!({"erro": "Link inválido ou expirado"}))).into_response();
+    let token = input.token.trim();
+    if token.is_empty() {
+        return invalido();
+    }
+    let token_hash = hash_token(token);
+
+    let row = match sqlx::query(
+        r#"SELECT r.id, r.user_id, u.email FROM password_resets r JOIN usuarios u ON u.id = r.user_id
+           WHERE r.token_hash = ? AND r.used_at IS NULL AND r.expires_at > CURRENT_TIMESTAMP LIMIT 1"#,
+    )
+    .bind(&token_hash)
+    .fetch_optional(&app_state.db)
+    .await
+    {
+        Ok(Some(r)) => r,
+        Ok(None) => return invalido(),
+        Err(e) => {
+            eprintln!("[auth] Erro ao validar token de redefinição: {}", e);
+            return erro_interno();
+        }
+    };
+    let reset_id: i64 = row.try_get("id").unwrap_or(0);
+    let user_id: i64 = row.try_get("user_id").unwrap_or(0);
+    let email: String = row.try_get("email").unwrap_or_default();
+
+    // Senha fora da política: o token continua válido para nova tentativa
+    if let Some(msg) = password_policy::check_password(&app_state.config, &input.senha, &email) {
+        return (StatusCode::UNPROCESSABLE_ENTITY, Json(json!({"erro": "Dados inválidos", "campos": {"senha": msg}}))).into_response();
+    }
+    let hash = match bcrypt::hash(&input.senha, 12) {
+        Ok(h) => h,
+        Err(e) => {
+            eprintln!("[auth] Erro ao gerar hash: {}", e);
+            return erro_interno();
+        }
+    };
+
+    let resultado = async {
+        let mut tx = app_state.db.begin().await?;
+        // Consome o token; se outra requisição chegou antes, nada é alterado
+        let usado = sqlx::query("UPDATE password_resets SET used_at = CURRENT_TIMESTAMP WHERE id = ? AND used_at IS NULL")
+            .bind(reset_id)
+            .execute(&mut *tx)
+            .await?;
+        if usado.rows_affected() == 0 {
+            return Ok(false);
+        }
+        sqlx::query("UPDATE usuarios SET senha_hash = ? WHERE id = ?")
+            .bind(&hash)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        // Sessões e tokens de API de quem tinha a conta antes da redefinição deixam de valer
+        for tabela in ["sessions", "api_tokens"] {
+            sqlx::query(&format!("DELETE FROM {} WHERE user_id = ?", tabela))
+                .bind(user_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok::<bool, sqlx::Error>(true)
+    }
+    .await;
+
+    match resultado {
+        Ok(true) => {
+            println!("Senha redefinida para usuário {}; sessões e tokens de API encerrados", user_id);
+            (StatusCode::OK, Json(json!({"status": "ok", "mensagem": "Senha redefinida com sucesso"}))).into_response()
+        }
+        Ok(false) => invalido(),
+        Err(e) => {
+            eprintln!("[auth] Erro ao redefinir senha: {}", e);
+            erro_interno()
+        }
+    }
+}
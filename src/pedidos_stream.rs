@@ -0,0 +1,106 @@
+use axum::{
+    extract::State,
+    http::HeaderMap,
+    response::sse::{Event, KeepAlive, Sse},
+};
+use futures_util::stream::{self, Stream};
+use serde_json::Value;
+use sqlx::{Row, SqlitePool};
+use std::collections::VecDeque;
+use std::convert::Infallible;
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+use crate::{webhooks, ApiError, AppState};
+
+// Painel da loja: GET /api/admin/pedidos/stream (Server-Sent Events). Cada pedido novo vira um
+// evento "pedido" com os itens; o id do evento é o `seq` do pedido (sequencial, gravado no
+// checkout dentro da transação, portanto na ordem dos commits), então o navegador retoma
+// de onde parou com Last-Event-ID. O checkout avisa pelo canal de broadcast após o commit e o
+// stream relê o banco, o que também cobre avisos perdidos.
+const LOTE: i64 = 100;
+
+pub fn canal() -> broadcast::Sender<()> {
+    broadcast::channel(64).0
+}
+
+struct Estado {
+    db: SqlitePool,
+    avisos: broadcast::Receiver<()>,
+    ultimo: i64,
+    fila: VecDeque<Event>,
+}
+
+// Próximos pedidos (com itens) depois da posição `ultimo`; também usado pelo `mercado-admin pedidos watch`
+pub async fn pedidos_apos(db: &SqlitePool, ultimo: i64) -> Result<Vec<(i64, Value)>, sqlx::Error> {
+    let rows = sqlx::query("SELECT seq, id FROM pedidos WHERE seq > ? ORDER BY seq LIMIT ?")
+        .bind(ultimo)
+        .bind(LOTE)
+        .fetch_all(db)
+        .await?;
+    let mut conn = db.acquire().await?;
+    let mut pedidos = Vec::new();
+    for row in rows {
+        let seq: i64 = row.try_get("seq").unwrap_or(0);
+        let id: String = row.try_get("id").unwrap_or_default();
+        pedidos.push((seq, webhooks::dados_pedido(&mut conn, &id).await?));
+    }
+    Ok(pedidos)
+}
+
+// Posição do pedido mais recente (ponto de partida sem Last-Event-ID)
+pub async fn posicao_atual(db: &SqlitePool) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar("SELECT COALESCE(MAX(seq), 0) FROM pedidos").fetch_one(db).await
+}
+
+fn eventos(estado: Estado) -> impl Stream<Item = Result<Event, Infallible>> {
+    stream::unfold(estado, |mut estado| async move {
+        loop {
+            if let Some(evento) = estado.fila.pop_front() {
+                return Some((Ok(evento), estado));
+            }
+            match pedidos_apos(&estado.db, estado.ultimo).await {
+                Ok(novos) if !novos.is_empty() => {
+                    for (seq, dados) in novos {
+                        estado.ultimo = seq;
+                        estado.fila.push_back(Event::default().event("pedido").id(seq.to_string()).data(dados.to_string()));
+                    }
+                    continue;
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    eprintln!("[stream] Erro ao ler pedidos: {}", e);
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                    continue;
+                }
+            }
+            // Espera o próximo checkout; aviso atrasado (Lagged) só significa reler o banco
+            match estado.avisos.recv().await {
+                Ok(()) | Err(broadcast::error::RecvError::Lagged(_)) => {}
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    })
+}
+
+// GET /api/admin/pedidos/stream: sem Last-Event-ID, começa pelos pedidos feitos a partir de agora
+pub async fn stream_pedidos(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, ApiError> {
+    // Inscreve antes de ler a posição inicial para não perder pedidos entre as duas coisas
+    let avisos = app_state.novos_pedidos.subscribe();
+    let retomada = headers
+        .get("last-event-id")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<i64>().ok());
+    let ultimo = match retomada {
+        Some(id) => id.max(0),
+        None => posicao_atual(&app_state.db)
+            .await
+            .map_err(|e| ApiError::internal_server_error(&format!("Erro ao abrir stream: {}", e)))?,
+    };
+
+    let estado = Estado { db: app_state.db.clone(), avisos, ultimo, fila: VecDeque::new() };
+    Ok(Sse::new(eventos(estado)).keep_alive(KeepAlive::new().interval(Duration::from_secs(15))))
+}
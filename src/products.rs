@@ -0,0 +1,395 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sqlx::{QueryBuilder, Row, Sqlite, SqliteConnection, SqlitePool};
+use sqlx::sqlite::SqliteRow;
+use sha2::{Digest, Sha256};
+
+use crate::{webhooks, ApiError, AppState};
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Product {
+    pub id: u32,
+    pub name: String,
+    pub price_cents: u32,
+    pub image_url: Option<String>,
+    // Miniatura gerada no upload; produtos sem upload reutilizam a imagem principal
+    pub thumbnail_url: Option<String>,
+    pub stock: u32,
+    pub category: String,
+    pub brand: String,
+    // Unidade de medida da embalagem ("kg", "g", "L", "ml") e quantidade líquida
+    pub unit: String,
+    pub unit_size: f64,
+    // Código interno da loja e código de barras (GTIN), usados na importação do catálogo
+    pub sku: Option<String>,
+    pub ean: Option<String>,
+}
+
+// Colunas lidas em todas as consultas de produto
+pub const PRODUCT_COLUMNS: &str = "id, name, price_cents, image_url, thumbnail_url, stock, category, brand, unit, unit_size, sku, ean";
+
+impl Product {
+    pub fn from_row(row: &SqliteRow) -> Self {
+        let image_url: Option<String> = row.try_get("image_url").unwrap_or(None);
+        Self {
+            id: row.try_get::<i64, _>("id").unwrap_or(0) as u32,
+            name: row.try_get("name").unwrap_or_default(),
+            price_cents: row.try_get::<i64, _>("price_cents").unwrap_or(0) as u32,
+            image_url: image_url.clone(),
+            thumbnail_url: row.try_get::<Option<String>, _>("thumbnail_url").unwrap_or(None).or(image_url),
+            stock: row.try_get::<i64, _>("stock").unwrap_or(0) as u32,
+            category: row.try_get("category").unwrap_or_default(),
+            brand: row.try_get("brand").unwrap_or_default(),
+            unit: row.try_get("unit").unwrap_or_default(),
+            unit_size: row.try_get("unit_size").unwrap_or(0.0),
+            sku: row.try_get("sku").unwrap_or(None),
+            ean: row.try_get("ean").unwrap_or(None),
+        }
+    }
+}
+
+// Catálogo inicial: (id, nome, preço, imagem, estoque, categoria, marca, unidade, quantidade)
+#[allow(clippy::type_complexity)]
+const SEED: &[(u32, &str, u32, &str, u32, &str, &str, &str, f64)] = &[
+    (1, "Arroz 1kg", 799, "images/arroz.png", 50, "Mercearia", "Bom Grão", "kg", 1.0),
+    (2, "Feijão 1kg", 899, "images/feijao.png", 50, "Mercearia", "Bom Grão", "kg", 1.0),
+    (3, "Macarrão 500g", 599, "images/macarrao.png", 60, "Mercearia", "Dona Nona", "g", 500.0),
+    (4, "Leite 1L", 549, "images/leite.png", 70, "Laticínios", "Vale Verde", "L", 1.0),
+    (5, "Café 500g", 1899, "images/cafe.png", 40, "Mercearia", "Serra Alta", "g", 500.0),
+    (6, "Açúcar 1kg", 489, "images/açucar.png", 80, "Mercearia", "Doce Vida", "kg", 1.0),
+    (7, "Óleo 900ml", 999, "images/oleo.png", 50, "Mercearia", "Sol Dourado", "ml", 900.0),
+    (8, "Biscoito 200g", 399, "images/biscoito.png", 90, "Doces e Snacks", "Crocante", "g", 200.0),
+    (9, "Molho de Tomate 340g", 499, "images/molho de tomate.png", 70, "Mercearia", "Dona Nona", "g", 340.0),
+    (10, "Farinha de Trigo 1kg", 699, "images/farinha de trigo.png", 60, "Mercearia", "Bom Grão", "kg", 1.0),
+    (11, "Sal 1kg", 299, "images/sal.png", 100, "Mercearia", "Marinho", "kg", 1.0),
+    (12, "Manteiga 200g", 1299, "images/manteiga.png", 40, "Laticínios", "Vale Verde", "g", 200.0),
+    (13, "Queijo Mussarela 200g", 1599, "images/queijo mussarela.png", 35, "Frios", "Fazenda Feliz", "g", 200.0),
+    (14, "Presunto 200g", 1399, "images/presunto.png", 35, "Frios", "Fazenda Feliz", "g", 200.0),
+    (15, "Refrigerante 2L", 999, "images/refrigerante.png", 80, "Bebidas", "Borbulha", "L", 2.0),
+    (16, "Água Mineral 1.5L", 399, "images/agua.png", 120, "Bebidas", "Fonte Clara", "L", 1.5),
+    (17, "Suco 1L", 699, "images/suco.png", 70, "Bebidas", "Pomar", "L", 1.0),
+    (18, "Cereal 300g", 1499, "images/cereal.png", 40, "Matinais", "Crocante", "g", 300.0),
+    (19, "Chocolate 100g", 799, "images/chocolate.png", 50, "Doces e Snacks", "Cacau Real", "g", 100.0),
+    (20, "Arroz Integral 1kg", 999, "images/arroz integral.png", 45, "Mercearia", "Bom Grão", "kg", 1.0),
+];
+
+// Cria a tabela de produtos e popula o catálogo inicial quando vazia
+pub async fn init_products(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS produtos (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            price_cents INTEGER NOT NULL,
+            image_url TEXT NULL,
+            thumbnail_url TEXT NULL,
+            stock INTEGER NOT NULL DEFAULT 0,
+            category TEXT NOT NULL,
+            brand TEXT NOT NULL,
+            unit TEXT NOT NULL,
+            unit_size REAL NOT NULL
+        );
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    // SKU e EAN únicos quando informados; produtos anteriores ao SKU recebem MER-<id>
+    if crate::ensure_column(pool, "produtos", "sku", "ALTER TABLE produtos ADD COLUMN sku TEXT NULL").await? {
+        sqlx::query("UPDATE produtos SET sku = printf('MER-%04d', id) WHERE sku IS NULL").execute(pool).await?;
+    }
+    crate::ensure_column(pool, "produtos", "ean", "ALTER TABLE produtos ADD COLUMN ean TEXT NULL").await?;
+    sqlx::query("CREATE UNIQUE INDEX IF NOT EXISTS idx_produtos_sku ON produtos (sku) WHERE sku IS NOT NULL")
+        .execute(pool)
+        .await?;
+    sqlx::query("CREATE UNIQUE INDEX IF NOT EXISTS idx_produtos_ean ON produtos (ean) WHERE ean IS NOT NULL")
+        .execute(pool)
+        .await?;
+
+    let count: i64 = sqlx::query("SELECT COUNT(*) AS n FROM produtos")
+        .fetch_one(pool)
+        .await?
+        .try_get("n")
+        .unwrap_or(0);
+    if count == 0 {
+        for (id, name, price, image, stock, category, brand, unit, size) in SEED {
+            sqlx::query(
+                "INSERT INTO produtos (id, name, price_cents, image_url, stock, category, brand, unit, unit_size, sku) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(*id as i64)
+            .bind(*name)
+            .bind(*price as i64)
+            .bind(*image)
+            .bind(*stock as i64)
+            .bind(*category)
+            .bind(*brand)
+            .bind(*unit)
+            .bind(*size)
+            .bind(format!("MER-{:04}", id))
+            .execute(pool)
+            .await?;
+        }
+        println!("Catálogo inicial com {} produtos criado", SEED.len());
+    }
+    Ok(())
+}
+
+// Busca um produto pelo id
+pub async fn get_product_by_id(db: &SqlitePool, id: u32) -> Result<Option<Product>, ApiError> {
+    let row = sqlx::query(&format!("SELECT {} FROM produtos WHERE id = ? LIMIT 1", PRODUCT_COLUMNS))
+        .bind(id as i64)
+        .fetch_optional(db)
+        .await
+        .map_err(|e| ApiError::internal_server_error(&format!("Erro ao buscar produto: {}", e)))?;
+    Ok(row.as_ref().map(Product::from_row))
+}
+
+// Paginação padrão e máxima de GET /api/products
+const DEFAULT_PER_PAGE: u32 = 50;
+const MAX_PER_PAGE: u32 = 100;
+
+// Filtros aceitos em GET /api/products (preços em centavos), paginação e ordenação
+#[derive(Deserialize, Default)]
+pub struct ProductFilter {
+    pub category: Option<String>,
+    pub brand: Option<String>,
+    pub min_price: Option<u32>,
+    pub max_price: Option<u32>,
+    pub in_stock: Option<bool>,
+    pub page: Option<u32>,
+    pub per_page: Option<u32>,
+    pub sort: Option<String>,
+    pub order: Option<String>,
+}
+
+// Facetas contadas na resposta; cada contagem ignora o próprio filtro
+#[derive(Clone, Copy, PartialEq)]
+enum Facet {
+    Category,
+    Brand,
+    InStock,
+}
+
+impl ProductFilter {
+    // Acrescenta as condições WHERE ao builder, pulando a faceta informada
+    fn push_where(&self, qb: &mut QueryBuilder<'_, Sqlite>, skip: Option<Facet>) {
+        qb.push(" WHERE 1 = 1");
+        if let Some(category) = self.category.as_deref().map(str::trim).filter(|c| !c.is_empty()) {
+            if skip != Some(Facet::Category) {
+                qb.push(" AND category = ").push_bind(category.to_string()).push(" COLLATE NOCASE");
+            }
+        }
+        if let Some(brand) = self.brand.as_deref().map(str::trim).filter(|b| !b.is_empty()) {
+            if skip != Some(Facet::Brand) {
+                qb.push(" AND brand = ").push_bind(brand.to_string()).push(" COLLATE NOCASE");
+            }
+        }
+        if let Some(min) = self.min_price {
+            qb.push(" AND price_cents >= ").push_bind(min as i64);
+        }
+        if let Some(max) = self.max_price {
+            qb.push(" AND price_cents <= ").push_bind(max as i64);
+        }
+        if let Some(in_stock) = self.in_stock {
+            if skip != Some(Facet::InStock) {
+                qb.push(if in_stock { " AND stock > 0" } else { " AND stock <= 0" });
+            }
+        }
+    }
+}
+
+// Conta produtos por valor da coluna, aplicando os demais filtros
+async fn facet_counts(db: &SqlitePool, filter: &ProductFilter, facet: Facet, column: &str) -> Result<Vec<Value>, sqlx::Error> {
+    let mut qb = QueryBuilder::<Sqlite>::new(format!("SELECT {} AS value, COUNT(*) AS count FROM produtos", column));
+    filter.push_where(&mut qb, Some(facet));
+    qb.push(" GROUP BY 1 ORDER BY 1");
+    let rows = qb.build().fetch_all(db).await?;
+
+    let mut result = Vec::new();
+    for row in rows {
+        let count: i64 = row.try_get("count").unwrap_or(0);
+        let value: Value = if facet == Facet::InStock {
+            json!(row.try_get::<i64, _>("value").unwrap_or(0) == 1)
+        } else {
+            json!(row.try_get::<String, _>("value").unwrap_or_default())
+        };
+        result.push(json!({"value": value, "count": count}));
+    }
+    Ok(result)
+}
+
+// Expressão ORDER BY a partir de sort=price|name|popularity e order=asc|desc.
+// Popularidade = unidades vendidas em pedidos não cancelados (padrão decrescente).
+fn order_by(sort: Option<&str>, order: Option<&str>) -> Result<String, ApiError> {
+    let (expr, default_desc) = match sort.unwrap_or("id") {
+        "id" => ("id", false),
+        "price" => ("price_cents", false),
+        "name" => ("name COLLATE NOCASE", false),
+        "popularity" => (
+            "(SELECT COALESCE(SUM(i.qty), 0) FROM itens_pedido i JOIN pedidos pe ON pe.id = i.pedido_id \
+              WHERE i.product_id = produtos.id AND pe.status != 'cancelled')",
+            true,
+        ),
+        _ => return Err(ApiError::validation_error("sort", "Ordenação inválida (use price, name ou popularity)")),
+    };
+    let desc = match order {
+        None => default_desc,
+        Some("asc") => false,
+        Some("desc") => true,
+        Some(_) => return Err(ApiError::validation_error("order", "Direção inválida (use asc ou desc)")),
+    };
+    Ok(format!(" ORDER BY {} {}, id", expr, if desc { "DESC" } else { "ASC" }))
+}
+
+// ETag forte a partir do corpo serializado da resposta
+fn etag_for(body: &[u8]) -> String {
+    let digest = Sha256::digest(body);
+    let hex: String = digest.iter().take(16).map(|b| format!("{:02x}", b)).collect();
+    format!("\"{}\"", hex)
+}
+
+// Verifica se algum valor de If-None-Match corresponde ao ETag atual
+fn etag_matches(headers: &HeaderMap, etag: &str) -> bool {
+    let Some(value) = headers.get(header::IF_NONE_MATCH).and_then(|v| v.to_str().ok()) else {
+        return false;
+    };
+    value
+        .split(',')
+        .map(|t| t.trim().trim_start_matches("W/"))
+        .any(|t| t == "*" || t == etag)
+}
+
+// Endpoint para listar produtos com filtros, contagem por faceta, paginação e ETag
+pub async fn get_products(
+    State(app_state): State<AppState>,
+    Query(filter): Query<ProductFilter>,
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
+    let db = &app_state.db;
+    let db_err = |e: sqlx::Error| ApiError::internal_server_error(&format!("Erro ao listar produtos: {}", e));
+
+    let page = filter.page.unwrap_or(1);
+    if page == 0 {
+        return Err(ApiError::validation_error("page", "Página deve ser maior que zero"));
+    }
+    let per_page = filter.per_page.unwrap_or(DEFAULT_PER_PAGE);
+    if per_page == 0 || per_page > MAX_PER_PAGE {
+        return Err(ApiError::validation_error("per_page", "per_page deve estar entre 1 e 100"));
+    }
+    let order = order_by(filter.sort.as_deref(), filter.order.as_deref())?;
+
+    let mut count_qb = QueryBuilder::<Sqlite>::new("SELECT COUNT(*) AS n FROM produtos");
+    filter.push_where(&mut count_qb, None);
+    let total: i64 = count_qb.build().fetch_one(db).await.map_err(db_err)?.try_get("n").unwrap_or(0);
+
+    let mut qb = QueryBuilder::<Sqlite>::new(format!("SELECT {} FROM produtos", PRODUCT_COLUMNS));
+    filter.push_where(&mut qb, None);
+    qb.push(order);
+    qb.push(" LIMIT ").push_bind(per_page as i64);
+    qb.push(" OFFSET ").push_bind((page as i64 - 1) * per_page as i64);
+    let rows = qb.build().fetch_all(db).await.map_err(db_err)?;
+    let items: Vec<Product> = rows.iter().map(Product::from_row).collect();
+
+    let facets = json!({
+        "category": facet_counts(db, &filter, Facet::Category, "category").await.map_err(db_err)?,
+        "brand": facet_counts(db, &filter, Facet::Brand, "brand").await.map_err(db_err)?,
+        "in_stock": facet_counts(db, &filter, Facet::InStock, "stock > 0").await.map_err(db_err)?,
+    });
+
+    let body = json!({
+        "items": items,
+        "total": total,
+        "page": page,
+        "per_page": per_page,
+        "total_pages": (total + per_page as i64 - 1) / per_page as i64,
+        "facets": facets,
+    });
+
+    // Catálogo inalterado desde a última consulta do cliente: 304 sem corpo, com os mesmos
+    // ETag e Cache-Control da resposta 200
+    let bytes = serde_json::to_vec(&body).map_err(|e| ApiError::internal_server_error(&e.to_string()))?;
+    let etag = etag_for(&bytes);
+    if etag_matches(&headers, &etag) {
+        return Ok((StatusCode::NOT_MODIFIED, [(header::ETAG, etag), (header::CACHE_CONTROL, "no-cache".to_string())]).into_response());
+    }
+    Ok((
+        StatusCode::OK,
+        [
+            (header::ETAG, etag),
+            (header::CACHE_CONTROL, "no-cache".to_string()),
+            (header::CONTENT_TYPE, "application/json".to_string()),
+        ],
+        bytes,
+    )
+        .into_response())
+}
+
+#[derive(Deserialize)]
+pub struct EstoqueInput {
+    pub stock: i64,
+}
+
+// Dispara product.low_stock quando o estoque cruza o limite (de acima para igual ou abaixo)
+pub async fn avisar_estoque_baixo(
+    conn: &mut SqliteConnection,
+    product_id: i64,
+    nome: &str,
+    anterior: i64,
+    atual: i64,
+    limite: i64,
+) -> Result<(), sqlx::Error> {
+    if atual > limite || anterior <= limite {
+        return Ok(());
+    }
+    let dados = json!({
+        "product_id": product_id,
+        "name": nome,
+        "stock": atual,
+        "stock_anterior": anterior,
+        "limite": limite,
+    });
+    webhooks::disparar(conn, "product.low_stock", dados).await
+}
+
+// PATCH /api/admin/products/:id/stock: ajuste de estoque. Ao cair para o limite
+// (LOW_STOCK_THRESHOLD) ou abaixo dele, dispara o webhook product.low_stock.
+pub async fn update_stock(
+    State(app_state): State<AppState>,
+    Path(product_id): Path<u32>,
+    Json(input): Json<EstoqueInput>,
+) -> Result<Json<Product>, ApiError> {
+    if !(0..=1_000_000).contains(&input.stock) {
+        return Err(ApiError::validation_error("stock", "Estoque deve estar entre 0 e 1.000.000"));
+    }
+    let db_err = |e: sqlx::Error| ApiError::internal_server_error(&format!("Erro ao atualizar estoque: {}", e));
+    let mut tx = app_state.db.begin().await.map_err(db_err)?;
+    let row = sqlx::query("SELECT name, stock FROM produtos WHERE id = ?")
+        .bind(product_id as i64)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(db_err)?
+        .ok_or_else(|| ApiError::not_found("Produto não encontrado"))?;
+    let anterior: i64 = row.try_get("stock").unwrap_or(0);
+
+    sqlx::query("UPDATE produtos SET stock = ? WHERE id = ?")
+        .bind(input.stock)
+        .bind(product_id as i64)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_err)?;
+    let nome: String = row.try_get("name").unwrap_or_default();
+    avisar_estoque_baixo(&mut tx, product_id as i64, &nome, anterior, input.stock, app_state.config.low_stock_threshold)
+        .await
+        .map_err(db_err)?;
+    tx.commit().await.map_err(db_err)?;
+    println!("Estoque do produto {}: {} → {}", product_id, anterior, input.stock);
+
+    get_product_by_id(&app_state.db, product_id)
+        .await?
+        .map(Json)
+        .ok_or_else(|| ApiError::not_found("Produto não encontrado"))
+}
@@ -0,0 +1,375 @@
+use axum::{
+    extract::{Extension, State},
+    http::{header::SET_COOKIE, HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use serde::Deserialize;
+use serde_json::json;
+use sqlx::{Row, SqlitePool};
+
+use crate::password_policy;
+use crate::sessions::{self, CurrentSession};
+use crate::{documento, lgpd, ApiError, AppState};
+
+// Perfil e autoatendimento da conta: dados cadastrais, troca de senha e exclusão.
+// A exclusão não apaga a linha de usuarios: ela vira um registro anônimo para que os
+// pedidos antigos continuem apontando para um usuário (histórico e relatórios intactos).
+pub async fn init_profile(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    crate::ensure_column(pool, "usuarios", "telefone", "ALTER TABLE usuarios ADD COLUMN telefone TEXT NULL").await?;
+    crate::ensure_column(pool, "usuarios", "cpf", "ALTER TABLE usuarios ADD COLUMN cpf TEXT NULL").await?;
+    // CNPJ para compras de empresas (vários compradores podem usar o mesmo)
+    crate::ensure_column(pool, "usuarios", "cnpj", "ALTER TABLE usuarios ADD COLUMN cnpj TEXT NULL").await?;
+    crate::ensure_column(pool, "usuarios", "deleted_at", "ALTER TABLE usuarios ADD COLUMN deleted_at TIMESTAMP NULL").await?;
+    // Conta desativada pelo mercado-admin: dados mantidos, sem login
+    crate::ensure_column(pool, "usuarios", "disabled_at", "ALTER TABLE usuarios ADD COLUMN disabled_at TIMESTAMP NULL").await?;
+    sqlx::query("CREATE UNIQUE INDEX IF NOT EXISTS idx_usuarios_cpf ON usuarios (cpf) WHERE cpf IS NOT NULL")
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+// Erros no formato comum da API (ApiError), o mesmo dos 422 de validação
+fn erro_interno() -> Response {
+    ApiError::internal_server_error("Erro interno").into_response()
+}
+
+fn nao_autenticado(mensagem: &str) -> Response {
+    ApiError::new(401, "Unauthorized", mensagem).into_response()
+}
+
+fn so_digitos(s: &str) -> String {
+    s.chars().filter(|c| c.is_ascii_digit()).collect()
+}
+
+async fn perfil(db: &SqlitePool, user_id: i64) -> Result<Option<serde_json::Value>, sqlx::Error> {
+    let row = sqlx::query("SELECT id, nome, email, papel, telefone, cpf, cnpj, verified_at IS NOT NULL AS verificado FROM usuarios WHERE id = ? AND deleted_at IS NULL")
+        .bind(user_id)
+        .fetch_optional(db)
+        .await?;
+    Ok(row.map(|r| {
+        json!({
+            "id": r.try_get::<i64, _>("id").unwrap_or(0),
+            "nome": r.try_get::<String, _>("nome").unwrap_or_default(),
+            "email": r.try_get::<String, _>("email").unwrap_or_default(),
+            "papel": r.try_get::<String, _>("papel").unwrap_or_default(),
+            "telefone": r.try_get::<Option<String>, _>("telefone").unwrap_or(None),
+            "cpf": r.try_get::<Option<String>, _>("cpf").unwrap_or(None),
+            "cnpj": r.try_get::<Option<String>, _>("cnpj").unwrap_or(None),
+            "verificado": r.try_get::<bool, _>("verificado").unwrap_or(false),
+        })
+    }))
+}
+
+// GET /api/me: perfil do usuário autenticado
+pub async fn get_profile(State(app_state): State<AppState>, Extension(user_id): Extension<i64>) -> Response {
+    match perfil(&app_state.db, user_id).await {
+        Ok(Some(p)) => Json(p).into_response(),
+        Ok(None) => nao_autenticado("não autenticado"),
+        Err(e) => {
+            eprintln!("[perfil] Erro ao buscar perfil: {}", e);
+            erro_interno()
+        }
+    }
+}
+
+// Campos ausentes não mudam; string vazia em telefone/cpf/cnpj remove o valor
+#[derive(Deserialize)]
+pub struct PerfilInput {
+    pub nome: Option<String>,
+    pub telefone: Option<String>,
+    pub cpf: Option<String>,
+    pub cnpj: Option<String>,
+}
+
+// PATCH /api/me: atualiza nome, telefone, CPF e CNPJ
+pub async fn update_profile(
+    State(app_state): State<AppState>,
+    Extension(user_id): Extension<i64>,
+    Json(input): Json<PerfilInput>,
+) -> Response {
+    // Erros de validação no formato comum da API (422 com error e field), como no checkout
+    let nome = input.nome.as_deref().map(str::trim);
+    if let Some(n) = nome {
+        if n.is_empty() || n.chars().count() > 100 {
+            return ApiError::validation_error("nome", "Informe um nome de até 100 caracteres").into_response();
+        }
+    }
+    // Telefone brasileiro com DDD: 10 ou 11 dígitos
+    let telefone = input.telefone.as_deref().map(so_digitos);
+    if let Some(t) = telefone.as_deref() {
+        if !t.is_empty() && !(10..=11).contains(&t.len()) {
+            return ApiError::validation_error("telefone", "Informe o telefone com DDD (10 ou 11 dígitos)").into_response();
+        }
+    }
+    let cpf = input.cpf.as_deref().map(documento::limpar);
+    if let Some(c) = cpf.as_deref() {
+        if !c.is_empty() && !documento::cpf_valido(c) {
+            return ApiError::validation_error("cpf", "CPF inválido").into_response();
+        }
+    }
+    let cnpj = input.cnpj.as_deref().map(documento::limpar);
+    if let Some(c) = cnpj.as_deref() {
+        if !c.is_empty() && !documento::cnpj_valido(c) {
+            return ApiError::validation_error("cnpj", "CNPJ inválido").into_response();
+        }
+    }
+
+    let res = sqlx::query(
+        r#"UPDATE usuarios SET
+               nome = COALESCE(?, nome),
+               telefone = CASE WHEN ? THEN ? ELSE telefone END,
+               cpf = CASE WHEN ? THEN ? ELSE cpf END,
+               cnpj = CASE WHEN ? THEN ? ELSE cnpj END
+           WHERE id = ? AND deleted_at IS NULL"#,
+    )
+    .bind(nome)
+    .bind(telefone.is_some())
+    .bind(telefone.clone().filter(|t| !t.is_empty()))
+    .bind(cpf.is_some())
+    .bind(cpf.clone().filter(|c| !c.is_empty()))
+    .bind(cnpj.is_some())
+    .bind(cnpj.clone().filter(|c| !c.is_empty()))
+    .bind(user_id)
+    .execute(&app_state.db)
+    .await;
+    match res {
+        Ok(_) => {}
+        Err(sqlx::Error::Database(e)) if e.is_unique_violation() => {
+            return ApiError::new(409, "Conflict", "CPF já cadastrado em outra conta").into_response();
+        }
+        Err(e) => {
+            eprintln!("[perfil] Erro ao atualizar perfil: {}", e);
+            return erro_interno();
+        }
+    }
+    get_profile(State(app_state), Extension(user_id)).await
+}
+
+#[derive(Deserialize)]
+pub struct TrocaSenhaInput {
+    pub senha_atual: String,
+    pub nova_senha: String,
+}
+
+// POST /api/me/password: troca a senha (exige a atual) e encerra as demais sessões
+pub async fn change_password(
+    State(app_state): State<AppState>,
+    Extension(user_id): Extension<i64>,
+    Extension(atual): Extension<CurrentSession>,
+    headers: HeaderMap,
+    Json(input): Json<TrocaSenhaInput>,
+) -> Response {
+    let row = match sqlx::query("SELECT email, senha_hash FROM usuarios WHERE id = ? AND deleted_at IS NULL")
+        .bind(user_id)
+        .fetch_optional(&app_state.db)
+        .await
+    {
+        Ok(Some(r)) => r,
+        Ok(None) => return nao_autenticado("não autenticado"),
+        Err(e) => {
+            eprintln!("[perfil] Erro ao buscar usuário: {}", e);
+            return erro_interno();
+        }
+    };
+    let email: String = row.try_get("email").unwrap_or_default();
+    let senha_hash: String = row.try_get("senha_hash").unwrap_or_default();
+    // Mesmo formato de 422 do PATCH /api/me (error e field)
+    if !bcrypt::verify(&input.senha_atual, &senha_hash).unwrap_or(false) {
+        return ApiError::validation_error("senha_atual", "Senha atual incorreta").into_response();
+    }
+    if let Some(msg) = password_policy::check_password(&app_state.config, &input.nova_senha, &email) {
+        return ApiError::validation_error("nova_senha", &msg).into_response();
+    }
+    let hash = match bcrypt::hash(&input.nova_senha, 12) {
+        Ok(h) => h,
+        Err(e) => {
+            eprintln!("[perfil] Erro ao gerar hash: {}", e);
+            return erro_interno();
+        }
+    };
+
+    let resultado = async {
+        let mut tx = app_state.db.begin().await?;
+        sqlx::query("UPDATE usuarios SET senha_hash = ? WHERE id = ?")
+            .bind(&hash)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        let encerradas = sqlx::query("DELETE FROM sessions WHERE user_id = ? AND public_id != ?")
+            .bind(user_id)
+            .bind(&atual.public_id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        // Tokens de API criados por quem conhecia a senha antiga também são revogados
+        sqlx::query("DELETE FROM api_tokens WHERE user_id = ?")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("UPDATE password_resets SET used_at = CURRENT_TIMESTAMP WHERE user_id = ? AND used_at IS NULL")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok::<u64, sqlx::Error>(encerradas)
+    }
+    .await;
+    match resultado {
+        Ok(encerradas) => {
+            println!("Usuário {} trocou a senha; {} outras sessões encerradas", user_id, encerradas);
+            let resp = Json(json!({"status": "ok", "mensagem": "Senha alterada", "sessoes_encerradas": encerradas})).into_response();
+            sessions::rotate_in_response(&app_state, &headers, user_id, resp).await
+        }
+        Err(e) => {
+            eprintln!("[perfil] Erro ao trocar senha: {}", e);
+            erro_interno()
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ExcluirContaInput {
+    pub senha: String,
+}
+
+// DELETE /api/me: exclui a conta. Os dados pessoais são apagados e a linha vira um registro
+// anônimo; pedidos continuam ligados a ela, sem nome, e-mail, telefone ou CPF.
+pub async fn delete_account(
+    State(app_state): State<AppState>,
+    Extension(user_id): Extension<i64>,
+    headers: HeaderMap,
+    Json(input): Json<ExcluirContaInput>,
+) -> Response {
+    let row = match sqlx::query("SELECT email, senha_hash, papel FROM usuarios WHERE id = ? AND deleted_at IS NULL")
+        .bind(user_id)
+        .fetch_optional(&app_state.db)
+        .await
+    {
+        Ok(Some(r)) => r,
+        Ok(None) => return nao_autenticado("não autenticado"),
+        Err(e) => {
+            eprintln!("[perfil] Erro ao buscar usuário: {}", e);
+            return erro_interno();
+        }
+    };
+    let email: String = row.try_get("email").unwrap_or_default();
+    let senha_hash: String = row.try_get("senha_hash").unwrap_or_default();
+    let papel: String = row.try_get("papel").unwrap_or_default();
+    if !bcrypt::verify(&input.senha, &senha_hash).unwrap_or(false) {
+        return nao_autenticado("Senha incorreta");
+    }
+    if papel == "admin" {
+        let outros: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM usuarios WHERE papel = 'admin' AND deleted_at IS NULL AND id != ?")
+            .bind(user_id)
+            .fetch_one(&app_state.db)
+            .await
+            .unwrap_or(0);
+        if outros == 0 {
+            return ApiError::new(409, "Conflict", "Não é possível excluir o único administrador").into_response();
+        }
+    }
+
+    match anonymize_user(&app_state.db, user_id, &email).await {
+        Ok(()) => {
+            if let Err(e) = lgpd::registrar(&app_state.db, user_id, "exclusao_conta", user_id, None).await {
+                eprintln!("[perfil] Erro ao registrar exclusão: {}", e);
+            }
+            println!("Conta {} excluída (dados anonimizados)", user_id);
+            (
+                StatusCode::OK,
+                [(SET_COOKIE, sessions::clear_cookie(&app_state.config, &headers))],
+                Json(json!({"status": "ok", "mensagem": "Conta excluída"})),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            eprintln!("[perfil] Erro ao excluir conta: {}", e);
+            erro_interno()
+        }
+    }
+}
+
+// Apaga credenciais e dados pessoais da conta, mantendo a linha (anônima) para os pedidos
+pub async fn anonymize_user(db: &SqlitePool, user_id: i64, email: &str) -> Result<(), sqlx::Error> {
+    let mut tx = db.begin().await?;
+    sqlx::query(
+        r#"UPDATE usuarios SET
+               nome = 'Conta excluída',
+               email = 'excluido-' || id || '@anonimo.invalid',
+               senha_hash = '',
+               papel = 'cliente',
+               telefone = NULL,
+               cpf = NULL,
+               cnpj = NULL,
+               totp_secret = NULL,
+               totp_enabled = 0,
+               totp_last_step = NULL,
+               deleted_at = CURRENT_TIMESTAMP
+           WHERE id = ?"#,
+    )
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+    // Pedidos ficam com valores e datas; o documento do comprador é apagado
+    sqlx::query("UPDATE pedidos SET documento = NULL WHERE user_id = ?")
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+    for tabela in ["sessions", "api_tokens", "totp_recovery_codes", "login_challenges", "password_resets", "email_verifications"] {
+        sqlx::query(&format!("DELETE FROM {} WHERE user_id = ?", tabela))
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+    for tabela in ["verification_sends", "login_attempts"] {
+        sqlx::query(&format!("DELETE FROM {} WHERE email = ?", tabela))
+            .bind(email)
+            .execute(&mut *tx)
+            .await?;
+    }
+    // Bloqueios de login por conta guardam o e-mail como chave
+    sqlx::query("DELETE FROM login_lockouts WHERE scope = 'conta' AND chave = ?")
+        .bind(email)
+        .execute(&mut *tx)
+        .await?;
+    // E-mails de pedidos (enviados ou na fila) levam nome e endereço
+    sqlx::query("DELETE FROM email_outbox WHERE para = ?")
+        .bind(email)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await
+}
+
+pub async fn is_disabled(db: &SqlitePool, user_id: i64) -> Result<bool, sqlx::Error> {
+    let v: Option<i64> = sqlx::query_scalar("SELECT disabled_at IS NOT NULL FROM usuarios WHERE id = ?")
+        .bind(user_id)
+        .fetch_optional(db)
+        .await?;
+    Ok(v == Some(1))
+}
+
+// Desativa (ou reativa) a conta. Desativar encerra as sessões e revoga os tokens de API.
+// Retorna false se a conta não existe ou já foi excluída.
+pub async fn set_disabled(db: &SqlitePool, user_id: i64, desativar: bool) -> Result<bool, sqlx::Error> {
+    let mut tx = db.begin().await?;
+    let r = sqlx::query(
+        "UPDATE usuarios SET disabled_at = CASE WHEN ? THEN COALESCE(disabled_at, CURRENT_TIMESTAMP) ELSE NULL END WHERE id = ? AND deleted_at IS NULL",
+    )
+    .bind(desativar)
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+    if r.rows_affected() == 0 {
+        return Ok(false);
+    }
+    if desativar {
+        for tabela in ["sessions", "api_tokens", "login_challenges"] {
+            sqlx::query(&format!("DELETE FROM {} WHERE user_id = ?", tabela))
+                .bind(user_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+    }
+    tx.commit().await?;
+    Ok(true)
+}
@@ -0,0 +1,389 @@
+use axum::{
+    extract::{Extension, Path, State},
+    http::header::{CONTENT_DISPOSITION, CONTENT_TYPE},
+    response::{IntoResponse, Response},
+};
+use sqlx::Row;
+
+use crate::escape::xml_escape;
+use crate::nfce::offset_modifier;
+use crate::{documento, dono_ou_admin, format_brl, metodo_label, ApiError, AppState};
+
+// Recibo do pedido (estilo DANFE simplificado) em HTML e PDF, gerado localmente a partir de
+// pedidos e itens_pedido. Só o dono do pedido e administradores (equipe de separação) acessam.
+// O PDF é montado à mão: páginas A4 com Courier (fontes padrão do PDF, sem embutir arquivos).
+const LARGURA: usize = 78;
+const LINHAS_POR_PAGINA: usize = 58;
+
+struct ItemRecibo {
+    nome: String,
+    qty: i64,
+    unit_cents: i64,
+}
+
+struct Recibo {
+    pedido_id: String,
+    data: String,
+    status: String,
+    metodo: String,
+    parcelas: Option<i64>,
+    total_cents: i64,
+    juros_cents: i64,
+    total_com_juros_cents: i64,
+    documento: Option<String>,
+    entrega: Option<String>,
+    nfce_chave: Option<String>,
+    itens: Vec<ItemRecibo>,
+}
+
+fn status_label(status: &str) -> &'static str {
+    match status {
+        "paid" => "Pago",
+        "shipped" => "Enviado",
+        "cancelled" => "Cancelado",
+        _ => "Pendente",
+    }
+}
+
+// Identificador da transação PIX (txid: até 25 caracteres alfanuméricos)
+fn pix_txid(pedido_id: &str) -> String {
+    let id: String = pedido_id.chars().filter(|c| c.is_ascii_alphanumeric()).collect();
+    format!("MERCADO{}", id.to_uppercase()).chars().take(25).collect()
+}
+
+// Parcelas como no checkout: valor arredondado para cima, última parcela ajusta a diferença
+fn parcelas(total_cents: i64, n: i64) -> (i64, i64) {
+    let valor = (total_cents + n - 1) / n;
+    (valor, total_cents - valor * (n - 1))
+}
+
+fn referencia_pagamento(r: &Recibo) -> String {
+    match r.metodo.as_str() {
+        "pix" => format!("PIX txid {}", pix_txid(&r.pedido_id)),
+        _ => format!("{} — pedido {}", metodo_label(&r.metodo), r.pedido_id.to_uppercase()),
+    }
+}
+
+// Pagamento em linhas de texto (compartilhado entre HTML e PDF)
+fn linhas_pagamento(r: &Recibo) -> Vec<String> {
+    let mut linhas = vec![format!("Forma de pagamento: {}", metodo_label(&r.metodo))];
+    if let Some(n) = r.parcelas.filter(|n| *n > 0) {
+        let (valor, ultima) = parcelas(r.total_com_juros_cents, n);
+        if n == 1 {
+            linhas.push(format!("1x de {} (sem juros)", format_brl(valor)));
+        } else if valor == ultima {
+            linhas.push(format!("{}x de {}", n, format_brl(valor)));
+        } else {
+            linhas.push(format!("{}x de {} (última de {})", n - 1, format_brl(valor), format_brl(ultima)));
+        }
+        if r.juros_cents > 0 {
+            linhas.push(format!("Juros: {}", format_brl(r.juros_cents)));
+        }
+    }
+    linhas.push(format!("Referência: {}", referencia_pagamento(r)));
+    linhas
+}
+
+fn cabecalho_loja(app_state: &AppState) -> Vec<String> {
+    let c = &app_state.config.nfce;
+    vec![
+        c.razao_social.clone(),
+        format!("CNPJ {}  IE {}", documento::formatar(&c.cnpj), c.ie),
+        format!("{}, {} - {} - {}/{} - CEP {}", c.logradouro, c.numero, c.bairro, c.municipio, c.uf, c.cep),
+    ]
+}
+
+async fn carregar(app_state: &AppState, user_id: i64, pedido_id: &str) -> Result<Recibo, ApiError> {
+    let db_err = |e: sqlx::Error| ApiError::internal_server_error(&format!("Erro ao gerar recibo: {}", e));
+    let db = &app_state.db;
+    let offset = offset_modifier(&app_state.config.nfce.utc_offset).unwrap_or_else(|| "-180 minutes".to_string());
+
+    let row = sqlx::query(
+        r#"SELECT p.id, strftime('%d/%m/%Y %H:%M', p.created_at, ?) AS data, p.status, p.payment_method,
+                  p.payment_installments, p.interest_cents, p.total_cents, p.total_with_interest_cents,
+                  p.documento, p.user_id, s.slot_date, s.start_time, s.end_time, n.chave
+           FROM pedidos p
+           LEFT JOIN delivery_slots s ON s.id = p.delivery_slot_id
+           LEFT JOIN nfce_documentos n ON n.pedido_id = p.id
+           WHERE p.id = ?"#,
+    )
+    .bind(&offset)
+    .bind(pedido_id)
+    .fetch_optional(db)
+    .await
+    .map_err(db_err)?
+    .ok_or_else(|| ApiError::not_found("Pedido não encontrado"))?;
+
+    // Pedido de outro cliente: mesmo 404, para não revelar que existe
+    let dono: Option<i64> = row.try_get("user_id").unwrap_or(None);
+    if !dono_ou_admin(db, user_id, dono).await.map_err(db_err)? {
+        return Err(ApiError::not_found("Pedido não encontrado"));
+    }
+
+    let itens = sqlx::query(
+        r#"SELECT product_id, qty, unit_price_cents, product_name
+           FROM itens_pedido
+           WHERE pedido_id = ? ORDER BY id"#,
+    )
+    .bind(pedido_id)
+    .fetch_all(db)
+    .await
+    .map_err(db_err)?
+    .into_iter()
+    .map(|r| {
+        let product_id: i64 = r.try_get("product_id").unwrap_or(0);
+        ItemRecibo {
+            nome: r.try_get::<Option<String>, _>("product_name").unwrap_or(None).unwrap_or_else(|| format!("Produto {}", product_id)),
+            qty: r.try_get("qty").unwrap_or(0),
+            unit_cents: r.try_get("unit_price_cents").unwrap_or(0),
+        }
+    })
+    .collect();
+
+    let total_cents: i64 = row.try_get("total_cents").unwrap_or(0);
+    let total_com_juros: i64 = row.try_get("total_with_interest_cents").unwrap_or(0);
+    let slot_date: Option<String> = row.try_get("slot_date").unwrap_or(None);
+    let entrega = slot_date.map(|d| {
+        let inicio: String = row.try_get("start_time").unwrap_or_default();
+        let fim: String = row.try_get("end_time").unwrap_or_default();
+        let data = match d.split('-').collect::<Vec<_>>()[..] {
+            [a, m, dia] => format!("{}/{}/{}", dia, m, a),
+            _ => d.clone(),
+        };
+        format!("{} das {} às {}", data, inicio, fim)
+    });
+    Ok(Recibo {
+        pedido_id: row.try_get("id").unwrap_or_default(),
+        data: row.try_get("data").unwrap_or_default(),
+        status: row.try_get("status").unwrap_or_default(),
+        metodo: row.try_get("payment_method").unwrap_or_default(),
+        parcelas: row.try_get("payment_installments").unwrap_or(None),
+        total_cents,
+        juros_cents: row.try_get("interest_cents").unwrap_or(0),
+        // Pedidos antigos não gravavam o total com juros
+        total_com_juros_cents: if total_com_juros > 0 { total_com_juros } else { total_cents },
+        documento: row.try_get::<Option<String>, _>("documento").unwrap_or(None).map(|d| documento::formatar(&d)),
+        entrega,
+        nfce_chave: row.try_get("chave").unwrap_or(None),
+        itens,
+    })
+}
+
+fn nome_arquivo(pedido_id: &str, ext: &str) -> String {
+    let id: String = pedido_id.chars().filter(|c| c.is_ascii_alphanumeric() || *c == '-').collect();
+    format!("attachment; filename=\"recibo-{}.{}\"", id, ext)
+}
+
+// GET /api/pedidos/:id/recibo.html
+pub async fn recibo_html(
+    State(app_state): State<AppState>,
+    Extension(user_id): Extension<i64>,
+    Path(pedido_id): Path<String>,
+) -> Result<Response, ApiError> {
+    let r = carregar(&app_state, user_id, &pedido_id).await?;
+    let e = |s: &str| xml_escape(s);
+
+    let loja = cabecalho_loja(&app_state);
+    let mut html = String::from("<!DOCTYPE html><html lang=\"pt-BR\"><head><meta charset=\"utf-8\">");
+    html.push_str(&format!("<title>Recibo {}</title>", e(&r.pedido_id)));
+    html.push_str(
+        "<style>body{font-family:sans-serif;max-width:720px;margin:2rem auto;color:#222}\
+         header{text-align:center;border-bottom:1px dashed #999;padding-bottom:.5rem}\
+         table{width:100%;border-collapse:collapse;margin:1rem 0}th,td{padding:.25rem;border-bottom:1px solid #ddd}\
+         td.n{text-align:right;white-space:nowrap}.total{font-weight:bold}.chave{font-family:monospace;word-break:break-all}\
+         @media print{body{margin:0}}</style></head><body>",
+    );
+    html.push_str(&format!("<header><h1>{}</h1>", e(&loja[0])));
+    for linha in &loja[1..] {
+        html.push_str(&format!("<div>{}</div>", e(linha)));
+    }
+    html.push_str("</header><h2>Recibo do pedido</h2>");
+    html.push_str(&format!(
+        "<p>Pedido <strong>{}</strong><br>Data: {}<br>Situação: {}",
+        e(&r.pedido_id),
+        e(&r.data),
+        status_label(&r.status)
+    ));
+    if let Some(doc) = &r.documento {
+        html.push_str(&format!("<br>CPF/CNPJ do consumidor: {}", e(doc)));
+    }
+    if let Some(entrega) = &r.entrega {
+        html.push_str(&format!("<br>Entrega: {}", e(entrega)));
+    }
+    html.push_str("</p><table><thead><tr><th>Qtd</th><th>Produto</th><th>Unitário</th><th>Total</th></tr></thead><tbody>");
+    for item in &r.itens {
+        html.push_str(&format!(
+            "<tr><td class=\"n\">{}</td><td>{}</td><td class=\"n\">{}</td><td class=\"n\">{}</td></tr>",
+            item.qty,
+            e(&item.nome),
+            format_brl(item.unit_cents),
+            format_brl(item.unit_cents * item.qty)
+        ));
+    }
+    html.push_str(&format!(
+        "</tbody><tfoot><tr class=\"total\"><td colspan=\"3\">Total dos produtos</td><td class=\"n\">{}</td></tr>",
+        format_brl(r.total_cents)
+    ));
+    if r.total_com_juros_cents != r.total_cents {
+        html.push_str(&format!(
+            "<tr class=\"total\"><td colspan=\"3\">Total a pagar</td><td class=\"n\">{}</td></tr>",
+            format_brl(r.total_com_juros_cents)
+        ));
+    }
+    html.push_str("</tfoot></table><section><h3>Pagamento</h3><p>");
+    html.push_str(&linhas_pagamento(&r).iter().map(|l| e(l)).collect::<Vec<_>>().join("<br>"));
+    html.push_str("</p></section>");
+    if let Some(chave) = &r.nfce_chave {
+        html.push_str(&format!("<section><h3>NFC-e</h3><p class=\"chave\">Chave de acesso: {}</p></section>", chave));
+    } else {
+        html.push_str("<p><small>Documento sem valor fiscal.</small></p>");
+    }
+    html.push_str("</body></html>");
+
+    Ok(([(CONTENT_TYPE, "text/html; charset=utf-8")], html).into_response())
+}
+
+// GET /api/pedidos/:id/recibo.pdf
+pub async fn recibo_pdf(
+    State(app_state): State<AppState>,
+    Extension(user_id): Extension<i64>,
+    Path(pedido_id): Path<String>,
+) -> Result<Response, ApiError> {
+    let r = carregar(&app_state, user_id, &pedido_id).await?;
+    let centro = |s: &str| format!("{:^width$}", s, width = LARGURA);
+    let separador = "-".repeat(LARGURA);
+
+    let mut linhas: Vec<Linha> = Vec::new();
+    let loja = cabecalho_loja(&app_state);
+    linhas.push(Linha::Destaque(centro(&loja[0])));
+    for l in &loja[1..] {
+        linhas.push(Linha::Texto(centro(l)));
+    }
+    linhas.push(Linha::Texto(separador.clone()));
+    linhas.push(Linha::Destaque(centro("RECIBO DO PEDIDO")));
+    linhas.push(Linha::Texto(format!("Pedido: {}", r.pedido_id)));
+    linhas.push(Linha::Texto(format!("Data: {}   Situação: {}", r.data, status_label(&r.status))));
+    if let Some(doc) = &r.documento {
+        linhas.push(Linha::Texto(format!("CPF/CNPJ do consumidor: {}", doc)));
+    }
+    if let Some(entrega) = &r.entrega {
+        linhas.push(Linha::Texto(format!("Entrega: {}", entrega)));
+    }
+    linhas.push(Linha::Texto(separador.clone()));
+    linhas.push(Linha::Destaque(format!("{:>4}  {:<40} {:>14} {:>14}", "Qtd", "Produto", "Unitário", "Total")));
+    for item in &r.itens {
+        let nome: String = item.nome.chars().take(40).collect();
+        linhas.push(Linha::Texto(format!(
+            "{:>4}  {:<40} {:>14} {:>14}",
+            item.qty,
+            nome,
+            format_brl(item.unit_cents),
+            format_brl(item.unit_cents * item.qty)
+        )));
+    }
+    linhas.push(Linha::Texto(separador.clone()));
+    linhas.push(Linha::Destaque(format!("{:<60}{:>18}", "Total dos produtos", format_brl(r.total_cents))));
+    if r.total_com_juros_cents != r.total_cents {
+        linhas.push(Linha::Destaque(format!("{:<60}{:>18}", "Total a pagar", format_brl(r.total_com_juros_cents))));
+    }
+    linhas.push(Linha::Texto(String::new()));
+    for l in linhas_pagamento(&r) {
+        linhas.push(Linha::Texto(l));
+    }
+    linhas.push(Linha::Texto(separador));
+    match &r.nfce_chave {
+        Some(chave) => {
+            linhas.push(Linha::Texto("NFC-e - chave de acesso:".to_string()));
+            let grupos: Vec<String> = chave.as_bytes().chunks(4).map(|c| String::from_utf8_lossy(c).into_owned()).collect();
+            linhas.push(Linha::Texto(grupos.join(" ")));
+        }
+        None => linhas.push(Linha::Texto(centro("Documento sem valor fiscal"))),
+    }
+
+    Ok((
+        [(CONTENT_TYPE, "application/pdf".to_string()), (CONTENT_DISPOSITION, nome_arquivo(&r.pedido_id, "pdf"))],
+        gerar_pdf(&linhas),
+    )
+        .into_response())
+}
+
+enum Linha {
+    Texto(String),
+    Destaque(String),
+}
+
+// Texto em WinAnsiEncoding (Latin-1 para acentos) com parênteses e barra escapados
+fn pdf_string(s: &str) -> Vec<u8> {
+    let mut out = vec![b'('];
+    for c in s.chars() {
+        match c {
+            '(' | ')' | '\\' => {
+                out.push(b'\\');
+                out.push(c as u8);
+            }
+            '—' | '–' => out.push(b'-'),
+            c if (' '..='~').contains(&c) || ('\u{a0}'..='\u{ff}').contains(&c) => out.push(c as u32 as u8),
+            _ => out.push(b'?'),
+        }
+    }
+    out.push(b')');
+    out
+}
+
+// PDF 1.4 mínimo: catálogo, árvore de páginas, duas fontes padrão e um fluxo de conteúdo por página
+fn gerar_pdf(linhas: &[Linha]) -> Vec<u8> {
+    let paginas: Vec<&[Linha]> = linhas.chunks(LINHAS_POR_PAGINA).collect();
+    let total = paginas.len();
+    // Objetos: 1 catálogo, 2 páginas, 3 e 4 fontes, depois (página, conteúdo) para cada página
+    let mut objetos: Vec<Vec<u8>> = Vec::new();
+    objetos.push(b"<< /Type /Catalog /Pages 2 0 R >>".to_vec());
+    let kids: Vec<String> = (0..total).map(|i| format!("{} 0 R", 5 + i * 2)).collect();
+    objetos.push(format!("<< /Type /Pages /Kids [{}] /Count {} >>", kids.join(" "), total).into_bytes());
+    objetos.push(b"<< /Type /Font /Subtype /Type1 /BaseFont /Courier /Encoding /WinAnsiEncoding >>".to_vec());
+    objetos.push(b"<< /Type /Font /Subtype /Type1 /BaseFont /Courier-Bold /Encoding /WinAnsiEncoding >>".to_vec());
+
+    for (i, pagina) in paginas.iter().enumerate() {
+        let mut conteudo: Vec<u8> = b"BT 12 TL 40 800 Td\n".to_vec();
+        for linha in pagina.iter() {
+            let (fonte, texto) = match linha {
+                Linha::Texto(t) => ("/F1", t),
+                Linha::Destaque(t) => ("/F2", t),
+            };
+            conteudo.extend_from_slice(format!("{} 9.5 Tf ", fonte).as_bytes());
+            conteudo.extend(pdf_string(texto));
+            conteudo.extend_from_slice(b" Tj T*\n");
+        }
+        let rodape = format!("Página {} de {}", i + 1, total);
+        conteudo.extend_from_slice(b"ET BT /F1 8 Tf 40 30 Td ");
+        conteudo.extend(pdf_string(&rodape));
+        conteudo.extend_from_slice(b" Tj ET");
+
+        objetos.push(
+            format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 595 842] /Resources << /Font << /F1 3 0 R /F2 4 0 R >> >> /Contents {} 0 R >>",
+                6 + i * 2
+            )
+            .into_bytes(),
+        );
+        let mut stream = format!("<< /Length {} >>\nstream\n", conteudo.len()).into_bytes();
+        stream.extend(conteudo);
+        stream.extend_from_slice(b"\nendstream");
+        objetos.push(stream);
+    }
+
+    let mut pdf: Vec<u8> = b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n".to_vec();
+    let mut offsets = Vec::new();
+    for (i, obj) in objetos.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.extend_from_slice(format!("{} 0 obj\n", i + 1).as_bytes());
+        pdf.extend_from_slice(obj);
+        pdf.extend_from_slice(b"\nendobj\n");
+    }
+    let xref = pdf.len();
+    pdf.extend_from_slice(format!("xref\n0 {}\n0000000000 65535 f \n", objetos.len() + 1).as_bytes());
+    for off in offsets {
+        pdf.extend_from_slice(format!("{:010} 00000 n \n", off).as_bytes());
+    }
+    pdf.extend_from_slice(format!("trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n", objetos.len() + 1, xref).as_bytes());
+    pdf
+}
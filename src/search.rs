@@ -0,0 +1,265 @@
+use axum::{
+    extract::{Query, State},
+    response::Json,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::{Row, SqlitePool};
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+
+use crate::escape::xml_escape;
+use crate::products::{Product, PRODUCT_COLUMNS};
+use crate::{ApiError, AppState};
+
+// Máximo de resultados e de sugestões devolvidos por busca
+const SEARCH_LIMIT: i64 = 50;
+const MAX_SUGGESTIONS: usize = 3;
+
+// Marcadores do highlight() (uso privado do Unicode): o nome é escapado antes de virarem <mark>
+const MARCA_INICIO: char = '\u{E000}';
+const MARCA_FIM: char = '\u{E001}';
+
+// Índice FTS5 sobre a tabela de produtos. O tokenizer unicode61 com
+// remove_diacritics=2 faz o "accent folding" (Açúcar → acucar) tanto no
+// índice quanto na consulta; triggers mantêm o índice sincronizado.
+pub async fn init_search(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE VIRTUAL TABLE IF NOT EXISTS produtos_fts USING fts5(
+            name, category, brand,
+            content='produtos', content_rowid='id',
+            tokenize='unicode61 remove_diacritics 2'
+        );
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query("CREATE VIRTUAL TABLE IF NOT EXISTS produtos_fts_vocab USING fts5vocab(produtos_fts, row);")
+        .execute(pool)
+        .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TRIGGER IF NOT EXISTS produtos_fts_ai AFTER INSERT ON produtos BEGIN
+            INSERT INTO produtos_fts(rowid, name, category, brand) VALUES (new.id, new.name, new.category, new.brand);
+        END;
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    sqlx::query(
+        r#"
+        CREATE TRIGGER IF NOT EXISTS produtos_fts_ad AFTER DELETE ON produtos BEGIN
+            INSERT INTO produtos_fts(produtos_fts, rowid, name, category, brand) VALUES ('delete', old.id, old.name, old.category, old.brand);
+        END;
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    // Só as colunas indexadas: baixa de estoque e troca de preço não reescrevem o índice.
+    // Recriado para substituir a versão antiga (AFTER UPDATE de qualquer coluna); na mesma
+    // conexão, para o CREATE já enxergar o DROP.
+    let mut tx = pool.begin().await?;
+    sqlx::query("DROP TRIGGER IF EXISTS produtos_fts_au;").execute(&mut *tx).await?;
+    sqlx::query(
+        r#"
+        CREATE TRIGGER produtos_fts_au AFTER UPDATE OF name, category, brand ON produtos BEGIN
+            INSERT INTO produtos_fts(produtos_fts, rowid, name, category, brand) VALUES ('delete', old.id, old.name, old.category, old.brand);
+            INSERT INTO produtos_fts(rowid, name, category, brand) VALUES (new.id, new.name, new.category, new.brand);
+        END;
+        "#,
+    )
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    // Reconstrói o índice a partir do catálogo (cobre bancos criados antes do índice existir)
+    sqlx::query("INSERT INTO produtos_fts(produtos_fts) VALUES ('rebuild');")
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+// Minúsculas e sem diacríticos: decompõe (NFD) e descarta as marcas combinantes, como o
+// remove_diacritics 2 do índice faz com "Açúcar" → "acucar"
+pub fn fold(s: &str) -> String {
+    s.nfd().filter(|c| !is_combining_mark(*c)).flat_map(char::to_lowercase).collect()
+}
+
+// Quebra a consulta em termos alfanuméricos já normalizados
+fn tokenize(q: &str) -> Vec<String> {
+    fold(q)
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+// Expressão MATCH: cada termo entre aspas (sem sintaxe FTS do usuário) e com prefixo
+fn match_expression(tokens: &[String]) -> String {
+    tokens
+        .iter()
+        .map(|t| format!("\"{}\"*", t.replace('"', "")))
+        .collect::<Vec<_>>()
+        .join(" AND ")
+}
+
+// Escapa o nome marcado pelo highlight() e troca os marcadores por <mark>
+fn destacar(marcado: &str) -> String {
+    xml_escape(marcado)
+        .replace(MARCA_INICIO, "<mark>")
+        .replace(MARCA_FIM, "</mark>")
+}
+
+// Distância de edição (Levenshtein) entre duas palavras
+fn levenshtein(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut cur = vec![0; b.len() + 1];
+    for i in 1..=a.len() {
+        cur[0] = i;
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            cur[j] = (prev[j] + 1).min(cur[j - 1] + 1).min(prev[j - 1] + cost);
+        }
+        std::mem::swap(&mut prev, &mut cur);
+    }
+    prev[b.len()]
+}
+
+// Sugestões "você quis dizer": troca termos desconhecidos pelos mais próximos do vocabulário
+async fn suggestions(db: &SqlitePool, tokens: &[String]) -> Result<Vec<String>, sqlx::Error> {
+    let rows = sqlx::query("SELECT term, doc FROM produtos_fts_vocab")
+        .fetch_all(db)
+        .await?;
+    let vocab: Vec<(String, i64)> = rows
+        .iter()
+        .map(|r| (r.try_get("term").unwrap_or_default(), r.try_get("doc").unwrap_or(0)))
+        .collect();
+
+    // Candidatos por termo: o próprio termo se for conhecido (ou prefixo de um conhecido),
+    // senão as palavras do vocabulário mais próximas
+    let mut per_token: Vec<Vec<String>> = Vec::new();
+    let mut changed = false;
+    for token in tokens {
+        if vocab.iter().any(|(term, _)| term.starts_with(token.as_str())) {
+            per_token.push(vec![token.clone()]);
+            continue;
+        }
+        let max_distance = if token.chars().count() <= 4 { 1 } else { 2 };
+        let mut candidates: Vec<(usize, i64, &String)> = vocab
+            .iter()
+            .map(|(term, doc)| (levenshtein(token, term), *doc, term))
+            .filter(|(d, _, _)| *d <= max_distance)
+            .collect();
+        candidates.sort_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)).then(a.2.cmp(b.2)));
+        if candidates.is_empty() {
+            per_token.push(vec![token.clone()]);
+        } else {
+            changed = true;
+            per_token.push(candidates.into_iter().take(MAX_SUGGESTIONS).map(|(_, _, t)| t.clone()).collect());
+        }
+    }
+    if !changed {
+        return Ok(Vec::new());
+    }
+
+    // Variação apenas no primeiro termo corrigido; os demais usam o melhor candidato
+    let varying = per_token.iter().position(|c| c.len() > 1).unwrap_or(0);
+    let mut result = Vec::new();
+    for alternative in &per_token[varying] {
+        let words: Vec<&str> = per_token
+            .iter()
+            .enumerate()
+            .map(|(i, c)| if i == varying { alternative.as_str() } else { c[0].as_str() })
+            .collect();
+        let suggestion = words.join(" ");
+        if !result.contains(&suggestion) {
+            result.push(suggestion);
+        }
+    }
+    Ok(result)
+}
+
+#[derive(Deserialize)]
+pub struct SearchQuery {
+    pub q: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct SearchHit {
+    #[serde(flatten)]
+    pub product: Product,
+    // Nome escapado para HTML, com os termos encontrados marcados em <mark>
+    pub highlight: String,
+}
+
+#[derive(Serialize)]
+pub struct SearchResponse {
+    pub query: String,
+    pub items: Vec<SearchHit>,
+    // Quantidade de produtos encontrados (items traz no máximo SEARCH_LIMIT)
+    pub total: i64,
+    pub suggestions: Vec<String>,
+}
+
+// GET /api/products/search?q=: busca por relevância (bm25, nome pesa mais)
+pub async fn search_products(
+    State(app_state): State<AppState>,
+    Query(params): Query<SearchQuery>,
+) -> Result<Json<SearchResponse>, ApiError> {
+    let query = params.q.unwrap_or_default().trim().to_string();
+    let tokens = tokenize(&query);
+    if tokens.is_empty() {
+        return Err(ApiError::validation_error("q", "Informe o termo de busca"));
+    }
+
+    let db_err = |e: sqlx::Error| ApiError::internal_server_error(&format!("Erro na busca: {}", e));
+    let columns = PRODUCT_COLUMNS
+        .split(", ")
+        .map(|c| format!("p.{}", c))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let rows = sqlx::query(&format!(
+        r#"SELECT {}, highlight(produtos_fts, 0, ?, ?) AS highlight
+           FROM produtos_fts JOIN produtos p ON p.id = produtos_fts.rowid
+           WHERE produtos_fts MATCH ?
+           ORDER BY bm25(produtos_fts, 10.0, 2.0, 1.0), p.id
+           LIMIT ?"#,
+        columns
+    ))
+    .bind(MARCA_INICIO.to_string())
+    .bind(MARCA_FIM.to_string())
+    .bind(match_expression(&tokens))
+    .bind(SEARCH_LIMIT)
+    .fetch_all(&app_state.db)
+    .await
+    .map_err(db_err)?;
+    let total: i64 = if (rows.len() as i64) < SEARCH_LIMIT {
+        rows.len() as i64
+    } else {
+        sqlx::query_scalar("SELECT COUNT(*) FROM produtos_fts WHERE produtos_fts MATCH ?")
+            .bind(match_expression(&tokens))
+            .fetch_one(&app_state.db)
+            .await
+            .map_err(db_err)?
+    };
+
+    let items: Vec<SearchHit> = rows
+        .iter()
+        .map(|row| SearchHit {
+            product: Product::from_row(row),
+            highlight: destacar(&row.try_get::<String, _>("highlight").unwrap_or_default()),
+        })
+        .collect();
+
+    // Só sugere quando a busca vem vazia
+    let suggestions = if items.is_empty() {
+        suggestions(&app_state.db, &tokens).await.map_err(db_err)?
+    } else {
+        Vec::new()
+    };
+
+    Ok(Json(SearchResponse { query, items, total, suggestions }))
+}
@@ -0,0 +1,321 @@
+use axum::{
+    extract::{Extension, Path, State},
+    http::{header::SET_COOKIE, HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use sqlx::{Row, SqlitePool};
+use uuid::Uuid;
+
+use crate::config::Config;
+use crate::AppState;
+
+// Sessões server-side com expiração deslizante (renovada a cada uso, até o limite
+// de inatividade) e um teto absoluto contado a partir do login.
+// O cookie leva "<token>.<assinatura HMAC>"; no banco (sessions.id) fica só o SHA-256 do token.
+pub async fn init_sessions(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    // public_id identifica a sessão nas listagens sem expor o valor do cookie
+    crate::ensure_column(pool, "sessions", "public_id", "ALTER TABLE sessions ADD COLUMN public_id TEXT NULL").await?;
+    crate::ensure_column(pool, "sessions", "user_agent", "ALTER TABLE sessions ADD COLUMN user_agent TEXT NULL").await?;
+    crate::ensure_column(pool, "sessions", "ip", "ALTER TABLE sessions ADD COLUMN ip TEXT NULL").await?;
+    crate::ensure_column(pool, "sessions", "last_seen_at", "ALTER TABLE sessions ADD COLUMN last_seen_at TIMESTAMP NULL").await?;
+    crate::ensure_column(pool, "sessions", "absolute_expires_at", "ALTER TABLE sessions ADD COLUMN absolute_expires_at TIMESTAMP NULL").await?;
+    // Token anti-CSRF (sincronizador) de cada sessão
+    crate::ensure_column(pool, "sessions", "csrf_token", "ALTER TABLE sessions ADD COLUMN csrf_token TEXT NULL").await?;
+    // Sessões antigas guardavam o UUID puro do cookie: não servem mais e são descartadas
+    sqlx::query("DELETE FROM sessions WHERE length(id) != 64")
+        .execute(pool)
+        .await?;
+    sqlx::query("UPDATE sessions SET public_id = lower(hex(randomblob(16))) WHERE public_id IS NULL")
+        .execute(pool)
+        .await?;
+    sqlx::query("UPDATE sessions SET csrf_token = lower(hex(randomblob(32))) WHERE csrf_token IS NULL")
+        .execute(pool)
+        .await?;
+    sqlx::query("UPDATE sessions SET absolute_expires_at = expires_at WHERE absolute_expires_at IS NULL")
+        .execute(pool)
+        .await?;
+    sqlx::query("CREATE UNIQUE INDEX IF NOT EXISTS idx_sessions_public_id ON sessions (public_id)")
+        .execute(pool)
+        .await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_sessions_user ON sessions (user_id)")
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+// Sessão da requisição atual (inserida pelo auth_middleware)
+#[derive(Clone)]
+pub struct CurrentSession {
+    pub public_id: String,
+    pub csrf_token: String,
+}
+
+// Sessão recém-criada: valor do cookie e token CSRF a devolver ao cliente
+pub struct NovaSessao {
+    pub cookie_value: String,
+    pub csrf_token: String,
+}
+
+// Condição de sessão válida (inatividade e teto absoluto)
+const VALIDA: &str = "expires_at > CURRENT_TIMESTAMP AND (absolute_expires_at IS NULL OR absolute_expires_at > CURRENT_TIMESTAMP)";
+
+type HmacSha256 = Hmac<Sha256>;
+
+fn sign(config: &Config, token: &str) -> Hmac<Sha256> {
+    let mut mac = HmacSha256::new_from_slice(&config.session_secret).expect("HMAC aceita chave de qualquer tamanho");
+    mac.update(token.as_bytes());
+    mac
+}
+
+fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+// Confere a assinatura do cookie (em tempo constante) e devolve a chave da sessão no banco
+pub fn session_key(config: &Config, cookie_value: &str) -> Option<String> {
+    let (token, assinatura) = cookie_value.split_once('.')?;
+    let assinatura = hex::decode(assinatura).ok()?;
+    sign(config, token).verify_slice(&assinatura).ok()?;
+    Some(hash_token(token))
+}
+
+// Token aleatório (256 bits) já no formato do cookie, junto com a chave para o banco
+fn new_token(config: &Config) -> (String, String) {
+    let token = hex::encode(rand::random::<[u8; 32]>());
+    let assinatura = hex::encode(sign(config, &token).finalize().into_bytes());
+    (format!("{}.{}", token, assinatura), hash_token(&token))
+}
+
+// Cria a sessão e retorna o valor do cookie e o token CSRF
+pub async fn create_session(
+    db: &SqlitePool,
+    config: &Config,
+    user_id: i64,
+    user_agent: Option<&str>,
+    ip: &str,
+) -> Result<NovaSessao, sqlx::Error> {
+    let (cookie_value, key) = new_token(config);
+    let csrf_token = hex::encode(rand::random::<[u8; 32]>());
+    sqlx::query(
+        r#"INSERT INTO sessions (id, public_id, csrf_token, user_id, user_agent, ip, last_seen_at, expires_at, absolute_expires_at)
+           VALUES (?, ?, ?, ?, ?, ?, CURRENT_TIMESTAMP, datetime('now', ?), datetime('now', ?))"#,
+    )
+    .bind(&key)
+    .bind(Uuid::new_v4().simple().to_string())
+    .bind(&csrf_token)
+    .bind(user_id)
+    .bind(user_agent.map(|ua| ua.chars().take(300).collect::<String>()))
+    .bind(ip)
+    .bind(format!("+{} seconds", config.session_idle_secs))
+    .bind(format!("+{} seconds", config.session_max_secs))
+    .execute(db)
+    .await?;
+    Ok(NovaSessao { cookie_value, csrf_token })
+}
+
+// Troca o identificador de uma sessão válida do usuário mantendo dispositivo e prazos (após
+// mudança de privilégio). Retorna o novo valor do cookie, ou None se a sessão não existe.
+pub async fn rotate_session(db: &SqlitePool, config: &Config, cookie_value: &str, user_id: i64) -> Result<Option<String>, sqlx::Error> {
+    let Some(old_key) = session_key(config, cookie_value) else { return Ok(None) };
+    let (novo_cookie, nova_key) = new_token(config);
+    let res = sqlx::query(&format!("UPDATE sessions SET id = ? WHERE id = ? AND user_id = ? AND {}", VALIDA))
+        .bind(&nova_key)
+        .bind(&old_key)
+        .bind(user_id)
+        .execute(db)
+        .await?;
+    Ok((res.rows_affected() == 1).then_some(novo_cookie))
+}
+
+// Rotaciona a sessão do cookie da requisição (após verificar o e-mail, trocar a senha ou
+// ligar/desligar a 2FA) e acrescenta à resposta o Set-Cookie com o novo identificador
+pub async fn rotate_in_response(app_state: &AppState, headers: &HeaderMap, user_id: i64, mut resp: Response) -> Response {
+    let cookie = headers.get("cookie").and_then(|v| v.to_str().ok()).and_then(|c| crate::auth::extract_cookie(c, "session_id"));
+    let Some(cookie) = cookie else { return resp };
+    match rotate_session(&app_state.db, &app_state.config, &cookie, user_id).await {
+        Ok(Some(novo)) => {
+            if let Ok(valor) = session_cookie(&app_state.config, headers, &novo).parse() {
+                resp.headers_mut().insert(SET_COOKIE, valor);
+            }
+        }
+        Ok(None) => {}
+        Err(e) => eprintln!("[auth] Erro ao rotacionar sessão: {}", e),
+    }
+    resp
+}
+
+// Remove a sessão do cookie (se válida)
+pub async fn delete_session(db: &SqlitePool, config: &Config, cookie_value: &str) -> Result<(), sqlx::Error> {
+    if let Some(key) = session_key(config, cookie_value) {
+        sqlx::query("DELETE FROM sessions WHERE id = ?").bind(key).execute(db).await?;
+    }
+    Ok(())
+}
+
+// Valida a sessão e desliza a expiração (no máximo uma escrita por minuto por sessão)
+pub async fn touch_session(db: &SqlitePool, config: &Config, cookie_value: &str) -> Result<Option<(i64, CurrentSession)>, sqlx::Error> {
+    let Some(sid) = session_key(config, cookie_value) else { return Ok(None) };
+    let sid = sid.as_str();
+    let row = sqlx::query(&format!("SELECT user_id, public_id, csrf_token FROM sessions WHERE id = ? AND {} LIMIT 1", VALIDA))
+        .bind(sid)
+        .fetch_optional(db)
+        .await?;
+    let Some(row) = row else { return Ok(None) };
+    sqlx::query(
+        r#"UPDATE sessions
+           SET last_seen_at = CURRENT_TIMESTAMP,
+               expires_at = MIN(datetime('now', ?), COALESCE(absolute_expires_at, datetime('now', ?)))
+           WHERE id = ? AND (last_seen_at IS NULL OR last_seen_at < datetime('now', '-60 seconds'))"#,
+    )
+    .bind(format!("+{} seconds", config.session_idle_secs))
+    .bind(format!("+{} seconds", config.session_idle_secs))
+    .bind(sid)
+    .execute(db)
+    .await?;
+    let user_id: i64 = row.try_get("user_id").unwrap_or(0);
+    let public_id: String = row.try_get("public_id").unwrap_or_default();
+    let csrf_token: String = row.try_get("csrf_token").unwrap_or_default();
+    Ok(Some((user_id, CurrentSession { public_id, csrf_token })))
+}
+
+// Requisição chegou por HTTPS? Configurável (COOKIE_SECURE) ou detectado via proxy reverso
+pub fn is_secure(config: &Config, headers: &HeaderMap) -> bool {
+    config.cookie_secure.unwrap_or_else(|| {
+        headers
+            .get("x-forwarded-proto")
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|p| p.trim().eq_ignore_ascii_case("https"))
+    })
+}
+
+fn secure_attr(secure: bool) -> &'static str {
+    if secure { "; Secure" } else { "" }
+}
+
+// Cookie da sessão: o navegador guarda até o teto absoluto; a inatividade é controlada no servidor
+pub fn session_cookie(config: &Config, headers: &HeaderMap, value: &str) -> String {
+    format!(
+        "session_id={}; HttpOnly; SameSite=Strict; Path=/; Max-Age={}{}",
+        value,
+        config.session_max_secs,
+        secure_attr(is_secure(config, headers))
+    )
+}
+
+pub fn clear_cookie(config: &Config, headers: &HeaderMap) -> String {
+    format!("session_id=; HttpOnly; SameSite=Strict; Path=/; Max-Age=0{}", secure_attr(is_secure(config, headers)))
+}
+
+#[derive(Serialize)]
+pub struct SessionRow {
+    id: String,
+    user_agent: Option<String>,
+    ip: Option<String>,
+    created_at: String,
+    last_seen_at: Option<String>,
+    expires_at: Option<String>,
+    atual: bool,
+}
+
+fn erro_interno() -> Response {
+    (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"erro": "Erro interno"}))).into_response()
+}
+
+// GET /api/me/sessions: sessões ativas do usuário, a atual marcada
+pub async fn list_my_sessions(
+    State(app_state): State<AppState>,
+    Extension(user_id): Extension<i64>,
+    Extension(atual): Extension<CurrentSession>,
+) -> Response {
+    let rows = sqlx::query(&format!(
+        r#"SELECT public_id, user_agent, ip, created_at, last_seen_at,
+                  MIN(expires_at, COALESCE(absolute_expires_at, expires_at)) AS expira
+           FROM sessions WHERE user_id = ? AND {} ORDER BY COALESCE(last_seen_at, created_at) DESC"#,
+        VALIDA
+    ))
+    .bind(user_id)
+    .fetch_all(&app_state.db)
+    .await;
+    let rows = match rows {
+        Ok(r) => r,
+        Err(e) => {
+            eprintln!("[auth] Erro ao listar sessões: {}", e);
+            return erro_interno();
+        }
+    };
+
+    let mut result = Vec::new();
+    for row in rows {
+        let id: String = row.try_get("public_id").unwrap_or_default();
+        result.push(SessionRow {
+            atual: id == atual.public_id,
+            id,
+            user_agent: row.try_get("user_agent").unwrap_or(None),
+            ip: row.try_get("ip").unwrap_or(None),
+            created_at: row.try_get("created_at").unwrap_or_default(),
+            last_seen_at: row.try_get("last_seen_at").unwrap_or(None),
+            expires_at: row.try_get("expira").unwrap_or(None),
+        });
+    }
+    Json(result).into_response()
+}
+
+// DELETE /api/me/sessions/:id: encerra uma sessão do próprio usuário
+pub async fn revoke_session(
+    State(app_state): State<AppState>,
+    Extension(user_id): Extension<i64>,
+    Extension(atual): Extension<CurrentSession>,
+    headers: HeaderMap,
+    Path(public_id): Path<String>,
+) -> Response {
+    let res = sqlx::query("DELETE FROM sessions WHERE public_id = ? AND user_id = ?")
+        .bind(&public_id)
+        .bind(user_id)
+        .execute(&app_state.db)
+        .await;
+    match res {
+        Ok(r) if r.rows_affected() == 0 => {
+            (StatusCode::NOT_FOUND, Json(json!({"erro": "Sessão não encontrada"}))).into_response()
+        }
+        Ok(_) if public_id == atual.public_id => {
+            let clear = clear_cookie(&app_state.config, &headers);
+            (StatusCode::OK, [(SET_COOKIE, clear)], Json(json!({"status": "ok", "mensagem": "Sessão encerrada"}))).into_response()
+        }
+        Ok(_) => (StatusCode::OK, Json(json!({"status": "ok", "mensagem": "Sessão encerrada"}))).into_response(),
+        Err(e) => {
+            eprintln!("[auth] Erro ao encerrar sessão: {}", e);
+            erro_interno()
+        }
+    }
+}
+
+// DELETE /api/me/sessions: "sair de todos os dispositivos" (inclui a sessão atual)
+pub async fn revoke_all_sessions(
+    State(app_state): State<AppState>,
+    Extension(user_id): Extension<i64>,
+    headers: HeaderMap,
+) -> Response {
+    match sqlx::query("DELETE FROM sessions WHERE user_id = ?")
+        .bind(user_id)
+        .execute(&app_state.db)
+        .await
+    {
+        Ok(r) => {
+            println!("Usuário {} encerrou {} sessões", user_id, r.rows_affected());
+            (
+                StatusCode::OK,
+                [(SET_COOKIE, clear_cookie(&app_state.config, &headers))],
+                Json(json!({"status": "ok", "encerradas": r.rows_affected()})),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            eprintln!("[auth] Erro ao encerrar sessões: {}", e);
+            erro_interno()
+        }
+    }
+}
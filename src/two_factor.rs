@@ -0,0 +1,479 @@
+use axum::{
+    extract::{ConnectInfo, Extension, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use data_encoding::BASE32_NOPAD;
+use hmac::{Hmac, Mac};
+use qrcode::{render::svg, QrCode};
+use serde::Deserialize;
+use serde_json::json;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use sqlx::{Row, SqlitePool};
+use std::net::SocketAddr;
+
+use crate::auth::{self, Usuario};
+use crate::{login_guard, sessions, AppState};
+
+// Verificação em duas etapas com TOTP (RFC 6238: HMAC-SHA1, passo de 30s, 6 dígitos).
+// Tudo é calculado localmente; o QR code é gerado em SVG pelo próprio servidor.
+const ISSUER: &str = "Mercado Online";
+const STEP_SECS: u64 = 30;
+const DIGITS: u32 = 6;
+const RECOVERY_CODES: usize = 10;
+// Segunda etapa do login: validade e tentativas por desafio
+const CHALLENGE_TTL_SECS: i64 = 300;
+const CHALLENGE_MAX_ATTEMPTS: i64 = 5;
+
+pub async fn init_two_factor(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    // Segredo pendente (cadastro iniciado) ou ativo; último passo aceito evita reuso do mesmo código
+    crate::ensure_column(pool, "usuarios", "totp_secret", "ALTER TABLE usuarios ADD COLUMN totp_secret TEXT NULL").await?;
+    crate::ensure_column(pool, "usuarios", "totp_enabled", "ALTER TABLE usuarios ADD COLUMN totp_enabled INTEGER NOT NULL DEFAULT 0").await?;
+    crate::ensure_column(pool, "usuarios", "totp_last_step", "ALTER TABLE usuarios ADD COLUMN totp_last_step INTEGER NULL").await?;
+
+    // Códigos de recuperação: só o SHA-256 é guardado
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS totp_recovery_codes (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            user_id INTEGER NOT NULL,
+            code_hash TEXT NOT NULL,
+            used_at TIMESTAMP,
+            created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            FOREIGN KEY(user_id) REFERENCES usuarios(id)
+        );
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    // Desafios da segunda etapa do login (id = hash do token entregue ao cliente)
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS login_challenges (
+            id TEXT PRIMARY KEY,
+            user_id INTEGER NOT NULL,
+            ip TEXT NOT NULL,
+            attempts INTEGER NOT NULL DEFAULT 0,
+            expires_at TIMESTAMP NOT NULL,
+            created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+        );
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+fn sha256_hex(valor: &str) -> String {
+    hex::encode(Sha256::digest(valor.as_bytes()))
+}
+
+fn hotp(secret: &[u8], counter: u64) -> u32 {
+    let mut mac = Hmac::<Sha1>::new_from_slice(secret).expect("HMAC aceita chave de qualquer tamanho");
+    mac.update(&counter.to_be_bytes());
+    let hash = mac.finalize().into_bytes();
+    let offset = (hash[hash.len() - 1] & 0x0f) as usize;
+    let bin = u32::from_be_bytes([hash[offset] & 0x7f, hash[offset + 1], hash[offset + 2], hash[offset + 3]]);
+    bin % 10u32.pow(DIGITS)
+}
+
+fn current_step() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() / STEP_SECS)
+        .unwrap_or(0)
+}
+
+// Aceita o passo atual e os vizinhos (tolerância de relógio), desde que posterior ao último usado.
+// Retorna o passo aceito.
+fn verify_totp(secret_b32: &str, codigo: &str, last_step: Option<i64>) -> Option<u64> {
+    let codigo = codigo.trim();
+    if codigo.len() != DIGITS as usize || !codigo.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let esperado: u32 = codigo.parse().ok()?;
+    let secret = BASE32_NOPAD.decode(secret_b32.as_bytes()).ok()?;
+    let agora = current_step();
+    (agora.saturating_sub(1)..=agora + 1)
+        .filter(|step| last_step.is_none_or(|last| *step as i64 > last))
+        .find(|step| hotp(&secret, *step) == esperado)
+}
+
+// Percent-encoding para o rótulo e o emissor da URI otpauth
+fn encode_uri_component(valor: &str) -> String {
+    valor
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+fn otpauth_uri(email: &str, secret_b32: &str) -> String {
+    format!(
+        "otpauth://totp/{issuer}:{label}?secret={secret}&issuer={issuer}&algorithm=SHA1&digits={digits}&period={period}",
+        issuer = encode_uri_component(ISSUER),
+        label = encode_uri_component(email),
+        secret = secret_b32,
+        digits = DIGITS,
+        period = STEP_SECS
+    )
+}
+
+// Códigos de recuperação no formato xxxx-xxxx (comparados sem hífen e sem diferenciar maiúsculas)
+fn normalize_recovery(codigo: &str) -> String {
+    codigo.chars().filter(|c| c.is_ascii_alphanumeric()).collect::<String>().to_lowercase()
+}
+
+fn new_recovery_code() -> String {
+    let raw = hex::encode(rand::random::<[u8; 4]>());
+    format!("{}-{}", &raw[..4], &raw[4..])
+}
+
+async fn replace_recovery_codes(db: &SqlitePool, user_id: i64) -> Result<Vec<String>, sqlx::Error> {
+    let codes: Vec<String> = (0..RECOVERY_CODES).map(|_| new_recovery_code()).collect();
+    let mut tx = db.begin().await?;
+    sqlx::query("DELETE FROM totp_recovery_codes WHERE user_id = ?").bind(user_id).execute(&mut *tx).await?;
+    for code in &codes {
+        sqlx::query("INSERT INTO totp_recovery_codes (user_id, code_hash) VALUES (?, ?)")
+            .bind(user_id)
+            .bind(sha256_hex(&normalize_recovery(code)))
+            .execute(&mut *tx)
+            .await?;
+    }
+    tx.commit().await?;
+    Ok(codes)
+}
+
+// Confere um código TOTP ou de recuperação do usuário, consumindo-o se válido
+async fn check_second_factor(db: &SqlitePool, user_id: i64, codigo: &str) -> Result<bool, sqlx::Error> {
+    let row = sqlx::query("SELECT totp_secret, totp_last_step FROM usuarios WHERE id = ? AND totp_enabled = 1")
+        .bind(user_id)
+        .fetch_optional(db)
+        .await?;
+    let Some(row) = row else { return Ok(false) };
+    let secret: String = row.try_get("totp_secret").unwrap_or_default();
+    let last_step: Option<i64> = row.try_get("totp_last_step").unwrap_or(None);
+
+    if let Some(step) = verify_totp(&secret, codigo, last_step) {
+        // Atualização condicional: o mesmo código não vale duas vezes, nem em requisições simultâneas
+        let res = sqlx::query("UPDATE usuarios SET totp_last_step = ? WHERE id = ? AND (totp_last_step IS NULL OR totp_last_step < ?)")
+            .bind(step as i64)
+            .bind(user_id)
+            .bind(step as i64)
+            .execute(db)
+            .await?;
+        return Ok(res.rows_affected() == 1);
+    }
+
+    let normalizado = normalize_recovery(codigo);
+    if normalizado.len() != 8 {
+        return Ok(false);
+    }
+    let res = sqlx::query("UPDATE totp_recovery_codes SET used_at = CURRENT_TIMESTAMP WHERE user_id = ? AND code_hash = ? AND used_at IS NULL")
+        .bind(user_id)
+        .bind(sha256_hex(&normalizado))
+        .execute(db)
+        .await?;
+    if res.rows_affected() == 1 {
+        println!("[2fa] Código de recuperação usado pelo usuário {}", user_id);
+    }
+    Ok(res.rows_affected() == 1)
+}
+
+pub async fn is_enabled(db: &SqlitePool, user_id: i64) -> Result<bool, sqlx::Error> {
+    let row = sqlx::query("SELECT totp_enabled FROM usuarios WHERE id = ?")
+        .bind(user_id)
+        .fetch_optional(db)
+        .await?;
+    Ok(row.map(|r| r.try_get::<i64, _>("totp_enabled").unwrap_or(0) == 1).unwrap_or(false))
+}
+
+// Cria o desafio da segunda etapa e retorna o token entregue ao cliente
+pub async fn create_challenge(db: &SqlitePool, user_id: i64, ip: &str) -> Result<String, sqlx::Error> {
+    let token = hex::encode(rand::random::<[u8; 32]>());
+    sqlx::query("DELETE FROM login_challenges WHERE expires_at <= CURRENT_TIMESTAMP")
+        .execute(db)
+        .await?;
+    sqlx::query("INSERT INTO login_challenges (id, user_id, ip, expires_at) VALUES (?, ?, ?, datetime('now', ?))")
+        .bind(sha256_hex(&token))
+        .bind(user_id)
+        .bind(ip)
+        .bind(format!("+{} seconds", CHALLENGE_TTL_SECS))
+        .execute(db)
+        .await?;
+    Ok(token)
+}
+
+fn erro_interno() -> Response {
+    (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"erro": "Erro interno"}))).into_response()
+}
+
+// GET /api/me/2fa: situação da verificação em duas etapas
+pub async fn status_2fa(State(app_state): State<AppState>, Extension(user_id): Extension<i64>) -> Response {
+    let row = sqlx::query(
+        r#"SELECT u.totp_enabled, u.papel,
+                  (SELECT COUNT(*) FROM totp_recovery_codes c WHERE c.user_id = u.id AND c.used_at IS NULL) AS restantes
+           FROM usuarios u WHERE u.id = ?"#,
+    )
+    .bind(user_id)
+    .fetch_optional(&app_state.db)
+    .await;
+    match row {
+        Ok(Some(r)) => {
+            let ativo = r.try_get::<i64, _>("totp_enabled").unwrap_or(0) == 1;
+            let papel: String = r.try_get("papel").unwrap_or_default();
+            Json(json!({
+                "ativo": ativo,
+                "obrigatorio": app_state.config.admin_require_2fa && papel == "admin",
+                "recovery_codes_restantes": r.try_get::<i64, _>("restantes").unwrap_or(0)
+            }))
+            .into_response()
+        }
+        Ok(None) => (StatusCode::UNAUTHORIZED, Json(json!({"erro": "não autenticado"}))).into_response(),
+        Err(e) => {
+            eprintln!("[2fa] Erro ao consultar 2FA: {}", e);
+            erro_interno()
+        }
+    }
+}
+
+// POST /api/me/2fa/setup: gera um novo segredo pendente e devolve URI otpauth e QR code (SVG)
+pub async fn setup_2fa(State(app_state): State<AppState>, Extension(user_id): Extension<i64>) -> Response {
+    let row = match sqlx::query("SELECT email, totp_enabled FROM usuarios WHERE id = ?")
+        .bind(user_id)
+        .fetch_optional(&app_state.db)
+        .await
+    {
+        Ok(Some(r)) => r,
+        Ok(None) => return (StatusCode::UNAUTHORIZED, Json(json!({"erro": "não autenticado"}))).into_response(),
+        Err(e) => {
+            eprintln!("[2fa] Erro ao buscar usuário: {}", e);
+            return erro_interno();
+        }
+    };
+    if row.try_get::<i64, _>("totp_enabled").unwrap_or(0) == 1 {
+        return (StatusCode::CONFLICT, Json(json!({"erro": "Verificação em duas etapas já está ativa"}))).into_response();
+    }
+    let email: String = row.try_get("email").unwrap_or_default();
+
+    let secret = BASE32_NOPAD.encode(&rand::random::<[u8; 20]>());
+    if let Err(e) = sqlx::query("UPDATE usuarios SET totp_secret = ?, totp_last_step = NULL WHERE id = ?")
+        .bind(&secret)
+        .bind(user_id)
+        .execute(&app_state.db)
+        .await
+    {
+        eprintln!("[2fa] Erro ao gravar segredo: {}", e);
+        return erro_interno();
+    }
+
+    let uri = otpauth_uri(&email, &secret);
+    let qr_svg = match QrCode::new(uri.as_bytes()) {
+        Ok(code) => code.render::<svg::Color>().min_dimensions(200, 200).build(),
+        Err(e) => {
+            eprintln!("[2fa] Erro ao gerar QR code: {}", e);
+            return erro_interno();
+        }
+    };
+    Json(json!({"secret": secret, "otpauth_uri": uri, "qr_svg": qr_svg})).into_response()
+}
+
+#[derive(Deserialize)]
+pub struct CodigoInput {
+    pub codigo: String,
+}
+
+// POST /api/me/2fa/enable: confirma o cadastro com um código do aplicativo e entrega os códigos de recuperação
+pub async fn enable_2fa(
+    State(app_state): State<AppState>,
+    Extension(user_id): Extension<i64>,
+    headers: HeaderMap,
+    Json(input): Json<CodigoInput>,
+) -> Response {
+    let row = match sqlx::query("SELECT totp_secret, totp_enabled FROM usuarios WHERE id = ?")
+        .bind(user_id)
+        .fetch_optional(&app_state.db)
+        .await
+    {
+        Ok(Some(r)) => r,
+        Ok(None) => return (StatusCode::UNAUTHORIZED, Json(json!({"erro": "não autenticado"}))).into_response(),
+        Err(e) => {
+            eprintln!("[2fa] Erro ao buscar usuário: {}", e);
+            return erro_interno();
+        }
+    };
+    if row.try_get::<i64, _>("totp_enabled").unwrap_or(0) == 1 {
+        return (StatusCode::CONFLICT, Json(json!({"erro": "Verificação em duas etapas já está ativa"}))).into_response();
+    }
+    let Some(secret) = row.try_get::<Option<String>, _>("totp_secret").unwrap_or(None) else {
+        return (StatusCode::BAD_REQUEST, Json(json!({"erro": "Inicie o cadastro em /api/me/2fa/setup"}))).into_response();
+    };
+    let Some(step) = verify_totp(&secret, &input.codigo, None) else {
+        return (StatusCode::UNPROCESSABLE_ENTITY, Json(json!({"erro": "Dados inválidos", "campos": {"codigo": "Código inválido"}}))).into_response();
+    };
+
+    if let Err(e) = sqlx::query("UPDATE usuarios SET totp_enabled = 1, totp_last_step = ? WHERE id = ?")
+        .bind(step as i64)
+        .bind(user_id)
+        .execute(&app_state.db)
+        .await
+    {
+        eprintln!("[2fa] Erro ao ativar 2FA: {}", e);
+        return erro_interno();
+    }
+    match replace_recovery_codes(&app_state.db, user_id).await {
+        Ok(codes) => {
+            println!("[2fa] Verificação em duas etapas ativada para usuário {}", user_id);
+            let resp = Json(json!({"status": "ok", "recovery_codes": codes})).into_response();
+            sessions::rotate_in_response(&app_state, &headers, user_id, resp).await
+        }
+        Err(e) => {
+            eprintln!("[2fa] Erro ao gerar códigos de recuperação: {}", e);
+            erro_interno()
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct DisableInput {
+    pub senha: String,
+    pub codigo: String,
+}
+
+// POST /api/me/2fa/disable: exige senha e um código (TOTP ou recuperação)
+pub async fn disable_2fa(
+    State(app_state): State<AppState>,
+    Extension(user_id): Extension<i64>,
+    headers: HeaderMap,
+    Json(input): Json<DisableInput>,
+) -> Response {
+    let row = match sqlx::query("SELECT senha_hash, papel FROM usuarios WHERE id = ?")
+        .bind(user_id)
+        .fetch_optional(&app_state.db)
+        .await
+    {
+        Ok(Some(r)) => r,
+        Ok(None) => return (StatusCode::UNAUTHORIZED, Json(json!({"erro": "não autenticado"}))).into_response(),
+        Err(e) => {
+            eprintln!("[2fa] Erro ao buscar usuário: {}", e);
+            return erro_interno();
+        }
+    };
+    let papel: String = row.try_get("papel").unwrap_or_default();
+    if app_state.config.admin_require_2fa && papel == "admin" {
+        return (StatusCode::FORBIDDEN, Json(json!({"erro": "Verificação em duas etapas é obrigatória para administradores"}))).into_response();
+    }
+    let senha_hash: String = row.try_get("senha_hash").unwrap_or_default();
+    if !bcrypt::verify(&input.senha, &senha_hash).unwrap_or(false) {
+        return (StatusCode::UNAUTHORIZED, Json(json!({"erro": "Senha ou código inválidos"}))).into_response();
+    }
+    match check_second_factor(&app_state.db, user_id, &input.codigo).await {
+        Ok(true) => {}
+        Ok(false) => return (StatusCode::UNAUTHORIZED, Json(json!({"erro": "Senha ou código inválidos"}))).into_response(),
+        Err(e) => {
+            eprintln!("[2fa] Erro ao verificar código: {}", e);
+            return erro_interno();
+        }
+    }
+
+    let resultado = async {
+        let mut tx = app_state.db.begin().await?;
+        sqlx::query("UPDATE usuarios SET totp_enabled = 0, totp_secret = NULL, totp_last_step = NULL WHERE id = ?")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM totp_recovery_codes WHERE user_id = ?").bind(user_id).execute(&mut *tx).await?;
+        tx.commit().await
+    }
+    .await;
+    match resultado {
+        Ok(()) => {
+            println!("[2fa] Verificação em duas etapas desativada para usuário {}", user_id);
+            sessions::rotate_in_response(&app_state, &headers, user_id, Json(json!({"status": "ok"})).into_response()).await
+        }
+        Err(e) => {
+            eprintln!("[2fa] Erro ao desativar 2FA: {}", e);
+            erro_interno()
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct SegundaEtapaInput {
+    pub desafio: String,
+    pub codigo: String,
+}
+
+// POST /api/login/2fa: conclui o login iniciado em /api/login com o código do aplicativo
+// (ou um código de recuperação). Falhas contam para o bloqueio de login da conta e do IP.
+pub async fn login_second_step(
+    State(app_state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Json(input): Json<SegundaEtapaInput>,
+) -> Response {
+    let ip = addr.ip().to_string();
+    let invalido = || (StatusCode::UNAUTHORIZED, Json(json!({"autenticado": false, "erro": "Código inválido ou expirado"}))).into_response();
+    let desafio_id = sha256_hex(input.desafio.trim());
+
+    // Cada código enviado consome uma tentativa do desafio numa única instrução (requisições
+    // simultâneas não passam do limite); o desafio só vale no IP que acertou a senha
+    let row = match sqlx::query(
+        r#"UPDATE login_challenges SET attempts = attempts + 1
+           WHERE id = ? AND ip = ? AND expires_at > CURRENT_TIMESTAMP AND attempts < ?
+           RETURNING user_id,
+               (SELECT nome FROM usuarios WHERE id = login_challenges.user_id) AS nome,
+               (SELECT email FROM usuarios WHERE id = login_challenges.user_id) AS email,
+               (SELECT papel FROM usuarios WHERE id = login_challenges.user_id) AS papel"#,
+    )
+    .bind(&desafio_id)
+    .bind(&ip)
+    .bind(CHALLENGE_MAX_ATTEMPTS)
+    .fetch_optional(&app_state.db)
+    .await
+    {
+        Ok(Some(r)) => r,
+        Ok(None) => return invalido(),
+        Err(e) => {
+            eprintln!("[2fa] Erro ao buscar desafio: {}", e);
+            return erro_interno();
+        }
+    };
+    let usuario = Usuario {
+        id: row.try_get("user_id").unwrap_or(0),
+        nome: row.try_get("nome").unwrap_or_default(),
+        email: row.try_get("email").unwrap_or_default(),
+        papel: row.try_get("papel").unwrap_or_default(),
+    };
+
+    match login_guard::locked_for(&app_state.db, &app_state.config, &usuario.email, &ip).await {
+        Ok(Some(restante)) => return auth::muitas_tentativas(restante),
+        Ok(None) => {}
+        Err(e) => {
+            eprintln!("[auth] Erro ao verificar bloqueio: {}", e);
+            return erro_interno();
+        }
+    }
+
+    let ok = match check_second_factor(&app_state.db, usuario.id, &input.codigo).await {
+        Ok(ok) => ok,
+        Err(e) => {
+            eprintln!("[2fa] Erro ao verificar código: {}", e);
+            return erro_interno();
+        }
+    };
+    let bloqueio = login_guard::record_attempt(&app_state.db, &app_state.config, &usuario.email, &ip, ok).await;
+    if !ok {
+        return match bloqueio {
+            Ok(Some(segundos)) => auth::muitas_tentativas(segundos),
+            _ => invalido(),
+        };
+    }
+
+    let _ = sqlx::query("DELETE FROM login_challenges WHERE id = ?").bind(&desafio_id).execute(&app_state.db).await;
+    auth::iniciar_sessao(&app_state, &headers, &ip, usuario, true).await
+}
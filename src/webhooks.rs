@@ -0,0 +1,564 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::Sha256;
+use sqlx::{Row, SqliteConnection, SqlitePool};
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::notificacoes::backoff_secs;
+use crate::password_reset::new_token;
+use crate::{ApiError, AppState};
+
+// Webhooks de saída: cada evento gera uma entrega por assinatura interessada, gravada em
+// webhook_deliveries na mesma transação da mudança. O worker faz o POST assinado com
+// HMAC-SHA256 (X-Mercado-Signature: sha256=<hex> sobre "<timestamp>.<corpo>") e reenvia
+// com espera crescente. Entregas que falharam de vez podem ser reenviadas pelo admin.
+const LOTE: i64 = 20;
+const MAX_ASSINATURAS: i64 = 50;
+
+pub const EVENTOS: &[&str] = &["order.created", "order.status_changed", "product.low_stock"];
+
+type HmacSha256 = Hmac<Sha256>;
+
+pub async fn init_webhooks(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS webhook_subscriptions (
+            id TEXT PRIMARY KEY,
+            url TEXT NOT NULL,
+            eventos TEXT NOT NULL,
+            secret TEXT NOT NULL,
+            ativo INTEGER NOT NULL DEFAULT 1,
+            created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+        );
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS webhook_deliveries (
+            id TEXT PRIMARY KEY,
+            subscription_id TEXT NOT NULL,
+            evento_id TEXT NOT NULL,
+            evento TEXT NOT NULL,
+            payload TEXT NOT NULL,
+            status TEXT NOT NULL DEFAULT 'pendente',
+            tentativas INTEGER NOT NULL DEFAULT 0,
+            proxima_tentativa_em TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            ultimo_status_http INTEGER NULL,
+            ultimo_erro TEXT NULL,
+            created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            entregue_em TIMESTAMP NULL,
+            FOREIGN KEY(subscription_id) REFERENCES webhook_subscriptions(id)
+        );
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_fila ON webhook_deliveries (status, proxima_tentativa_em)")
+        .execute(pool)
+        .await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_assinatura ON webhook_deliveries (subscription_id, created_at)")
+        .execute(pool)
+        .await?;
+    // Entregas interrompidas no meio do envio (servidor reiniciado) voltam para a fila
+    sqlx::query("UPDATE webhook_deliveries SET status = 'pendente' WHERE status = 'enviando'")
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+// Registra o evento para todas as assinaturas ativas que o escutam (na transação de quem chama)
+pub async fn disparar(conn: &mut SqliteConnection, evento: &str, dados: Value) -> Result<(), sqlx::Error> {
+    let assinaturas: Vec<String> = sqlx::query_scalar(
+        "SELECT id FROM webhook_subscriptions WHERE ativo = 1 AND (',' || eventos || ',') LIKE '%,' || ? || ',%'",
+    )
+    .bind(evento)
+    .fetch_all(&mut *conn)
+    .await?;
+    if assinaturas.is_empty() {
+        return Ok(());
+    }
+    let evento_id = Uuid::new_v4().to_string();
+    let criado_em: String = sqlx::query_scalar("SELECT strftime('%Y-%m-%dT%H:%M:%SZ', 'now')")
+        .fetch_one(&mut *conn)
+        .await?;
+    let payload = json!({ "id": evento_id, "evento": evento, "criado_em": criado_em, "dados": dados }).to_string();
+    for assinatura in assinaturas {
+        sqlx::query("INSERT INTO webhook_deliveries (id, subscription_id, evento_id, evento, payload) VALUES (?, ?, ?, ?, ?)")
+            .bind(Uuid::new_v4().simple().to_string())
+            .bind(&assinatura)
+            .bind(&evento_id)
+            .bind(evento)
+            .bind(&payload)
+            .execute(&mut *conn)
+            .await?;
+    }
+    Ok(())
+}
+
+// Dados do pedido enviados em order.created (sem dados pessoais do comprador)
+pub async fn dados_pedido(conn: &mut SqliteConnection, pedido_id: &str) -> Result<Value, sqlx::Error> {
+    let p = sqlx::query(
+        r#"SELECT id, status, total_cents, total_with_interest_cents, payment_method, payment_installments, delivery_slot_id, created_at
+           FROM pedidos WHERE id = ?"#,
+    )
+    .bind(pedido_id)
+    .fetch_one(&mut *conn)
+    .await?;
+    let itens: Vec<Value> = sqlx::query("SELECT product_id, qty, unit_price_cents FROM itens_pedido WHERE pedido_id = ? ORDER BY id")
+        .bind(pedido_id)
+        .fetch_all(&mut *conn)
+        .await?
+        .iter()
+        .map(|i| {
+            json!({
+                "product_id": i.try_get::<i64, _>("product_id").unwrap_or(0),
+                "qty": i.try_get::<i64, _>("qty").unwrap_or(0),
+                "unit_price_cents": i.try_get::<i64, _>("unit_price_cents").unwrap_or(0),
+            })
+        })
+        .collect();
+    Ok(json!({
+        "order_id": p.try_get::<String, _>("id").unwrap_or_default(),
+        "status": p.try_get::<String, _>("status").unwrap_or_default(),
+        "total_cents": p.try_get::<i64, _>("total_cents").unwrap_or(0),
+        "total_with_interest_cents": p.try_get::<i64, _>("total_with_interest_cents").unwrap_or(0),
+        "payment_method": p.try_get::<String, _>("payment_method").unwrap_or_default(),
+        "installments": p.try_get::<Option<i64>, _>("payment_installments").unwrap_or(None),
+        "delivery_slot_id": p.try_get::<Option<String>, _>("delivery_slot_id").unwrap_or(None),
+        "created_at": p.try_get::<String, _>("created_at").unwrap_or_default(),
+        "items": itens,
+    }))
+}
+
+// Assinatura enviada em X-Mercado-Signature
+pub fn assinar(secret: &str, timestamp: i64, corpo: &str) -> String {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC aceita chave de qualquer tamanho");
+    mac.update(format!("{}.{}", timestamp, corpo).as_bytes());
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+// Endereço fora das faixas internas (loopback, redes privadas, link-local, CGNAT, NAT64, multicast...)
+fn ip_publico(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => {
+            let [a, b, ..] = v4.octets();
+            !(v4.is_private()
+                || v4.is_loopback()
+                || v4.is_link_local()
+                || v4.is_unspecified()
+                || v4.is_broadcast()
+                || v4.is_documentation()
+                || v4.is_multicast()
+                || a == 0
+                || (a == 100 && (64..128).contains(&b))
+                || (a == 198 && (18..20).contains(&b)))
+        }
+        IpAddr::V6(v6) => match v6.to_ipv4_mapped() {
+            Some(v4) => ip_publico(IpAddr::V4(v4)),
+            None => {
+                let s = v6.segments();
+                // 64:ff9b::/96 (NAT64) carrega um IPv4 qualquer, inclusive interno
+                let nat64 = s[..6] == [0x64, 0xff9b, 0, 0, 0, 0];
+                !(v6.is_loopback() || v6.is_unspecified() || v6.is_multicast() || nat64 || (s[0] & 0xfe00) == 0xfc00 || (s[0] & 0xffc0) == 0xfe80)
+            }
+        },
+    }
+}
+
+// Destino de um webhook depois de resolvido o host
+pub enum Destino {
+    // Host liberado por configuração (WEBHOOK_ALLOW_PRIVATE_HOSTS): não é conferido
+    Liberado,
+    // Todos os endereços são públicos; o envio conecta neste, sem resolver o host de novo
+    Publico(SocketAddr),
+    // Primeiro endereço interno encontrado
+    Interno(IpAddr),
+}
+
+// Resolve o host da URL e confere os endereços, para o webhook não servir de ponte até a rede
+// do servidor (SSRF). Erro se a URL for inválida ou o host não resolver.
+pub async fn conferir_destino(url: &str, liberados: &[String]) -> Result<Destino, String> {
+    let url = reqwest::Url::parse(url).map_err(|_| "URL inválida".to_string())?;
+    let host = url.host_str().unwrap_or_default().to_lowercase();
+    if liberados.contains(&host) {
+        return Ok(Destino::Liberado);
+    }
+    if host.is_empty() {
+        return Err("URL sem host".to_string());
+    }
+    let porta = url.port_or_known_default().unwrap_or(80);
+    // IPv6 literal vem entre colchetes em host_str
+    let enderecos: Vec<SocketAddr> = match host.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>() {
+        Ok(ip) => vec![SocketAddr::new(ip, porta)],
+        Err(_) => tokio::net::lookup_host((host.as_str(), porta))
+            .await
+            .map_err(|e| format!("Não foi possível resolver {}: {}", host, e))?
+            .collect(),
+    };
+    if let Some(interno) = enderecos.iter().map(SocketAddr::ip).find(|ip| !ip_publico(*ip)) {
+        return Ok(Destino::Interno(interno));
+    }
+    enderecos
+        .first()
+        .map(|a| Destino::Publico(*a))
+        .ok_or_else(|| format!("Não foi possível resolver {}", host))
+}
+
+// Cliente HTTP de uma entrega. Com `fixo`, o host da URL aponta para o endereço já conferido:
+// uma nova consulta de DNS (TTL curto, rebinding) não consegue trocar o destino por um interno.
+fn cliente_entrega(url: &str, timeout: Duration, fixo: Option<SocketAddr>) -> reqwest::Result<reqwest::Client> {
+    let mut builder = reqwest::Client::builder()
+        .timeout(timeout)
+        .redirect(reqwest::redirect::Policy::none())
+        .user_agent("mercado-backend-webhooks");
+    if let (Some(addr), Some(host)) = (fixo, reqwest::Url::parse(url).ok().and_then(|u| u.host_str().map(str::to_string))) {
+        builder = builder.resolve(&host, addr);
+    }
+    builder.build()
+}
+
+// Resposta 4xx (fora 408 e 429) indica que o destino recusou o conteúdo: não adianta repetir
+fn definitivo(status: u16) -> bool {
+    (400..500).contains(&status) && status != 408 && status != 429
+}
+
+// Envia um lote de entregas vencidas; devolve quantas foram processadas. O destino é conferido
+// de novo antes de cada envio (o DNS pode ter mudado desde o cadastro) e o envio vai para o
+// endereço conferido.
+pub async fn processar_fila(
+    db: &SqlitePool,
+    timeout: Duration,
+    max_tentativas: i64,
+    backoff_base: i64,
+    hosts_liberados: &[String],
+) -> Result<usize, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"SELECT d.id, d.evento, d.payload, d.tentativas, s.url, s.secret
+           FROM webhook_deliveries d JOIN webhook_subscriptions s ON s.id = d.subscription_id
+           WHERE d.status = 'pendente' AND d.proxima_tentativa_em <= CURRENT_TIMESTAMP
+           ORDER BY d.created_at LIMIT ?"#,
+    )
+    .bind(LOTE)
+    .fetch_all(db)
+    .await?;
+
+    let mut processadas = 0;
+    for row in rows {
+        let id: String = row.try_get("id").unwrap_or_default();
+        let reservada = sqlx::query("UPDATE webhook_deliveries SET status = 'enviando' WHERE id = ? AND status = 'pendente'")
+            .bind(&id)
+            .execute(db)
+            .await?;
+        if reservada.rows_affected() == 0 {
+            continue;
+        }
+        let payload: String = row.try_get("payload").unwrap_or_default();
+        let secret: String = row.try_get("secret").unwrap_or_default();
+        let url: String = row.try_get("url").unwrap_or_default();
+        let evento: String = row.try_get("evento").unwrap_or_default();
+        let tentativas: i64 = row.try_get::<i64, _>("tentativas").unwrap_or(0) + 1;
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+
+        let fixo = match conferir_destino(&url, hosts_liberados).await {
+            Ok(Destino::Interno(ip)) => Err((Some(format!("Destino bloqueado: {} é um endereço interno", ip)), true)),
+            Ok(Destino::Publico(addr)) => Ok(Some(addr)),
+            Ok(Destino::Liberado) => Ok(None),
+            Err(e) => Err((Some(e), false)),
+        };
+        let (http, erro, bloqueado) = match fixo.and_then(|f| cliente_entrega(&url, timeout, f).map_err(|e| (Some(e.to_string()), false))) {
+            Err((erro, bloqueado)) => (None, erro, bloqueado),
+            Ok(client) => {
+                let resposta = client
+                    .post(&url)
+                    .header("content-type", "application/json")
+                    .header("x-mercado-event", &evento)
+                    .header("x-mercado-delivery", &id)
+                    .header("x-mercado-timestamp", timestamp.to_string())
+                    .header("x-mercado-signature", assinar(&secret, timestamp, &payload))
+                    .body(payload)
+                    .send()
+                    .await;
+                match resposta {
+                    Ok(r) if r.status().is_success() => (Some(r.status().as_u16()), None, false),
+                    Ok(r) => (Some(r.status().as_u16()), Some(format!("HTTP {}", r.status().as_u16())), false),
+                    Err(e) => (None, Some(e.to_string()), false),
+                }
+            }
+        };
+
+        match erro {
+            None => {
+                sqlx::query(
+                    "UPDATE webhook_deliveries SET status = 'entregue', tentativas = ?, ultimo_status_http = ?, ultimo_erro = NULL, entregue_em = CURRENT_TIMESTAMP WHERE id = ?",
+                )
+                .bind(tentativas)
+                .bind(http.map(i64::from))
+                .bind(&id)
+                .execute(db)
+                .await?;
+            }
+            Some(e) => {
+                let (status, espera) = if bloqueado || tentativas >= max_tentativas || http.is_some_and(definitivo) {
+                    ("falhou", 0)
+                } else {
+                    ("pendente", backoff_secs(backoff_base, tentativas))
+                };
+                eprintln!("[webhooks] Entrega {} para {} falhou (tentativa {}): {}", id, url, tentativas, e);
+                sqlx::query(
+                    r#"UPDATE webhook_deliveries SET status = ?, tentativas = ?, ultimo_status_http = ?, ultimo_erro = ?,
+                           proxima_tentativa_em = datetime('now', ?) WHERE id = ?"#,
+                )
+                .bind(status)
+                .bind(tentativas)
+                .bind(http.map(i64::from))
+                .bind(&e)
+                .bind(format!("+{} seconds", espera))
+                .bind(&id)
+                .execute(db)
+                .await?;
+            }
+        }
+        processadas += 1;
+    }
+    Ok(processadas)
+}
+
+// Worker em segundo plano (mesmo intervalo da fila de e-mails)
+pub fn iniciar_worker(db: SqlitePool, poll_secs: u64, timeout_secs: u64, max_tentativas: i64, backoff_base: i64, hosts_liberados: Vec<String>) {
+    let timeout = Duration::from_secs(timeout_secs);
+    tokio::spawn(async move {
+        loop {
+            match processar_fila(&db, timeout, max_tentativas, backoff_base, &hosts_liberados).await {
+                Ok(n) if n as i64 >= LOTE => continue,
+                Ok(_) => {}
+                Err(e) => eprintln!("[webhooks] Erro ao ler a fila de entregas: {}", e),
+            }
+            tokio::time::sleep(Duration::from_secs(poll_secs)).await;
+        }
+    });
+}
+
+#[derive(Serialize)]
+pub struct AssinaturaRow {
+    id: String,
+    url: String,
+    eventos: Vec<String>,
+    ativo: bool,
+    created_at: String,
+    pendentes: i64,
+    falhas: i64,
+}
+
+fn split_eventos(raw: &str) -> Vec<String> {
+    raw.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect()
+}
+
+// GET /api/admin/webhooks: assinaturas (sem o segredo) com o tamanho da fila de cada uma
+pub async fn list_webhooks(State(app_state): State<AppState>) -> Result<Json<Vec<AssinaturaRow>>, ApiError> {
+    let rows = sqlx::query(
+        r#"SELECT s.id, s.url, s.eventos, s.ativo, s.created_at,
+                  (SELECT COUNT(*) FROM webhook_deliveries d WHERE d.subscription_id = s.id AND d.status IN ('pendente', 'enviando')) AS pendentes,
+                  (SELECT COUNT(*) FROM webhook_deliveries d WHERE d.subscription_id = s.id AND d.status = 'falhou') AS falhas
+           FROM webhook_subscriptions s ORDER BY s.created_at"#,
+    )
+    .fetch_all(&app_state.db)
+    .await
+    .map_err(|e| ApiError::internal_server_error(&format!("Erro ao listar webhooks: {}", e)))?;
+
+    let mut result = Vec::new();
+    for row in rows {
+        let eventos: String = row.try_get("eventos").unwrap_or_default();
+        result.push(AssinaturaRow {
+            id: row.try_get("id").unwrap_or_default(),
+            url: row.try_get("url").unwrap_or_default(),
+            eventos: split_eventos(&eventos),
+            ativo: row.try_get::<i64, _>("ativo").unwrap_or(0) == 1,
+            created_at: row.try_get("created_at").unwrap_or_default(),
+            pendentes: row.try_get("pendentes").unwrap_or(0),
+            falhas: row.try_get("falhas").unwrap_or(0),
+        });
+    }
+    Ok(Json(result))
+}
+
+#[derive(Deserialize)]
+pub struct NovaAssinaturaInput {
+    pub url: String,
+    pub eventos: Vec<String>,
+    // Opcional: sem segredo informado, um aleatório é gerado
+    pub secret: Option<String>,
+}
+
+// POST /api/admin/webhooks: cria a assinatura; o segredo só aparece nesta resposta
+pub async fn create_webhook(State(app_state): State<AppState>, Json(input): Json<NovaAssinaturaInput>) -> Result<Response, ApiError> {
+    let db_err = |e: sqlx::Error| ApiError::internal_server_error(&format!("Erro ao criar webhook: {}", e));
+    let url = input.url.trim();
+    let valida = reqwest::Url::parse(url)
+        .map(|u| matches!(u.scheme(), "http" | "https") && u.host_str().is_some())
+        .unwrap_or(false);
+    if !valida || url.len() > 2000 {
+        return Err(ApiError::validation_error("url", "Informe uma URL http(s) válida"));
+    }
+    match conferir_destino(url, &app_state.config.webhook_allow_private_hosts).await {
+        Ok(Destino::Liberado | Destino::Publico(_)) => {}
+        Ok(Destino::Interno(_)) => return Err(ApiError::validation_error("url", "A URL aponta para um endereço interno")),
+        Err(_) => return Err(ApiError::validation_error("url", "Não foi possível resolver o host da URL")),
+    }
+    let mut eventos: Vec<&str> = Vec::new();
+    for e in &input.eventos {
+        let Some(evento) = EVENTOS.iter().find(|nome| **nome == e.trim()) else {
+            return Err(ApiError::validation_error("eventos", &format!("Evento desconhecido: {}", e)));
+        };
+        if !eventos.contains(evento) {
+            eventos.push(evento);
+        }
+    }
+    if eventos.is_empty() {
+        return Err(ApiError::validation_error("eventos", "Informe ao menos um evento"));
+    }
+    let secret = match input.secret.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+        Some(s) if s.len() < 16 => return Err(ApiError::validation_error("secret", "O segredo deve ter ao menos 16 caracteres")),
+        Some(s) => s.to_string(),
+        None => format!("whsec_{}", new_token()),
+    };
+
+    let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM webhook_subscriptions")
+        .fetch_one(&app_state.db)
+        .await
+        .map_err(db_err)?;
+    if total >= MAX_ASSINATURAS {
+        return Err(ApiError::new(409, "Conflict", "Limite de assinaturas atingido"));
+    }
+
+    let id = Uuid::new_v4().simple().to_string();
+    let eventos = eventos.join(",");
+    let created_at: String = sqlx::query_scalar("INSERT INTO webhook_subscriptions (id, url, eventos, secret) VALUES (?, ?, ?, ?) RETURNING created_at")
+        .bind(&id)
+        .bind(url)
+        .bind(&eventos)
+        .bind(&secret)
+        .fetch_one(&app_state.db)
+        .await
+        .map_err(db_err)?;
+    println!("[webhooks] Assinatura {} criada para {} ({})", id, url, eventos);
+    Ok((
+        StatusCode::CREATED,
+        Json(json!({ "id": id, "url": url, "eventos": split_eventos(&eventos), "secret": secret, "ativo": true, "created_at": created_at })),
+    )
+        .into_response())
+}
+
+// DELETE /api/admin/webhooks/:id: remove a assinatura e o histórico de entregas
+pub async fn delete_webhook(State(app_state): State<AppState>, Path(id): Path<String>) -> Result<Json<Value>, ApiError> {
+    let db_err = |e: sqlx::Error| ApiError::internal_server_error(&format!("Erro ao remover webhook: {}", e));
+    let mut tx = app_state.db.begin().await.map_err(db_err)?;
+    sqlx::query("DELETE FROM webhook_deliveries WHERE subscription_id = ?")
+        .bind(&id)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_err)?;
+    let res = sqlx::query("DELETE FROM webhook_subscriptions WHERE id = ?")
+        .bind(&id)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_err)?;
+    if res.rows_affected() == 0 {
+        return Err(ApiError::not_found("Webhook não encontrado"));
+    }
+    tx.commit().await.map_err(db_err)?;
+    Ok(Json(json!({"status": "ok", "mensagem": "Webhook removido"})))
+}
+
+#[derive(Deserialize)]
+pub struct EntregasQuery {
+    pub status: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct EntregaRow {
+    id: String,
+    evento_id: String,
+    evento: String,
+    status: String,
+    tentativas: i64,
+    proxima_tentativa_em: String,
+    ultimo_status_http: Option<i64>,
+    ultimo_erro: Option<String>,
+    created_at: String,
+    entregue_em: Option<String>,
+    payload: Value,
+}
+
+// GET /api/admin/webhooks/:id/deliveries?status=: registro das entregas (últimas 200)
+pub async fn list_deliveries(
+    State(app_state): State<AppState>,
+    Path(id): Path<String>,
+    Query(q): Query<EntregasQuery>,
+) -> Result<Json<Vec<EntregaRow>>, ApiError> {
+    let rows = sqlx::query(
+        r#"SELECT id, evento_id, evento, status, tentativas, proxima_tentativa_em, ultimo_status_http, ultimo_erro, created_at, entregue_em, payload
+           FROM webhook_deliveries
+           WHERE subscription_id = ?1 AND (?2 IS NULL OR status = ?2)
+           ORDER BY created_at DESC, rowid DESC LIMIT 200"#,
+    )
+    .bind(&id)
+    .bind(q.status.as_deref().map(str::trim).filter(|s| !s.is_empty()))
+    .fetch_all(&app_state.db)
+    .await
+    .map_err(|e| ApiError::internal_server_error(&format!("Erro ao listar entregas: {}", e)))?;
+
+    let mut result = Vec::new();
+    for row in rows {
+        let payload: String = row.try_get("payload").unwrap_or_default();
+        result.push(EntregaRow {
+            id: row.try_get("id").unwrap_or_default(),
+            evento_id: row.try_get("evento_id").unwrap_or_default(),
+            evento: row.try_get("evento").unwrap_or_default(),
+            status: row.try_get("status").unwrap_or_default(),
+            tentativas: row.try_get("tentativas").unwrap_or(0),
+            proxima_tentativa_em: row.try_get("proxima_tentativa_em").unwrap_or_default(),
+            ultimo_status_http: row.try_get("ultimo_status_http").unwrap_or(None),
+            ultimo_erro: row.try_get("ultimo_erro").unwrap_or(None),
+            created_at: row.try_get("created_at").unwrap_or_default(),
+            entregue_em: row.try_get("entregue_em").unwrap_or(None),
+            payload: serde_json::from_str(&payload).unwrap_or(Value::Null),
+        });
+    }
+    Ok(Json(result))
+}
+
+// POST /api/admin/webhook-deliveries/:id/replay: devolve à fila uma entrega que falhou
+pub async fn replay_delivery(State(app_state): State<AppState>, Path(id): Path<String>) -> Result<Response, ApiError> {
+    let db_err = |e: sqlx::Error| ApiError::internal_server_error(&format!("Erro ao reenviar entrega: {}", e));
+    let status: String = sqlx::query_scalar("SELECT status FROM webhook_deliveries WHERE id = ?")
+        .bind(&id)
+        .fetch_optional(&app_state.db)
+        .await
+        .map_err(db_err)?
+        .ok_or_else(|| ApiError::not_found("Entrega não encontrada"))?;
+    if status != "falhou" {
+        return Err(ApiError::new(409, "Conflict", &format!("Só entregas com falha podem ser reenviadas (status atual: {})", status)));
+    }
+    sqlx::query(
+        "UPDATE webhook_deliveries SET status = 'pendente', tentativas = 0, proxima_tentativa_em = CURRENT_TIMESTAMP WHERE id = ? AND status = 'falhou'",
+    )
+    .bind(&id)
+    .execute(&app_state.db)
+    .await
+    .map_err(db_err)?;
+    println!("[webhooks] Entrega {} devolvida à fila", id);
+    Ok((StatusCode::ACCEPTED, Json(json!({"id": id, "status": "pendente"}))).into_response())
+}
@@ -0,0 +1,154 @@
+use serde_json::Value;
+
+mod common;
+
+// CPF válido a partir de 9 dígitos (calcula os verificadores)
+fn gerar_cpf(base: u64) -> String {
+    let mut d: Vec<u32> = format!("{:09}", base % 1_000_000_000).chars().map(|c| c.to_digit(10).unwrap()).collect();
+    for n in [9usize, 10] {
+        let soma: u32 = (0..n).map(|i| d[i] * (n as u32 + 1 - i as u32)).sum();
+        d.push((soma * 10 % 11) % 10);
+    }
+    d.iter().map(|x| x.to_string()).collect()
+}
+
+async fn me_status(client: &reqwest::Client, cookie: &str) -> u16 {
+    client
+        .get(format!("{}/api/me", common::BASE_URL))
+        .header("cookie", cookie)
+        .send()
+        .await
+        .expect("Falha em /api/me")
+        .status()
+        .as_u16()
+}
+
+#[tokio::test]
+async fn account_profile() {
+    let _server = common::spawn_server().await;
+    let client = reqwest::Client::new();
+
+    let ts = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_millis();
+    let email = format!("perfil{}@teste.com", ts);
+    let senha = "Mercado#2025forte";
+    let (cookie, csrf) = common::verified_user_session(&client, "Perfil", &email, senha).await;
+    let (outra, _) = common::login(&client, &email, senha).await.expect("login deve funcionar");
+
+    let patch = |body: Value| {
+        client
+            .patch(format!("{}/api/me", common::BASE_URL))
+            .header("cookie", &cookie)
+            .header("x-csrf-token", &csrf)
+            .json(&body)
+            .send()
+    };
+
+    // 1) Perfil: CPF inválido recusado; dados válidos normalizados
+    let resp = patch(serde_json::json!({ "cpf": "111.111.111-11" })).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 422);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["field"].as_str(), Some("cpf"));
+    assert!(body["error"].as_str().is_some());
+
+    let cpf = gerar_cpf(ts as u64);
+    let cpf_formatado = format!("{}.{}.{}-{}", &cpf[0..3], &cpf[3..6], &cpf[6..9], &cpf[9..]);
+    let resp = patch(serde_json::json!({ "nome": "Perfil Atualizado", "telefone": "(11) 98765-4321", "cpf": cpf_formatado })).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["nome"].as_str(), Some("Perfil Atualizado"));
+    assert_eq!(body["telefone"].as_str(), Some("11987654321"));
+    assert_eq!(body["cpf"].as_str(), Some(cpf.as_str()));
+
+    // 2) Troca de senha: exige a atual e encerra as outras sessões e os tokens de API
+    let resp = client
+        .post(format!("{}/api/me/tokens", common::BASE_URL))
+        .header("cookie", &cookie)
+        .header("x-csrf-token", &csrf)
+        .json(&serde_json::json!({ "nome": "script", "scopes": ["orders:read"] }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 201);
+    let token = resp.json::<Value>().await.unwrap()["token"].as_str().unwrap().to_string();
+    let pedidos_com_token = || client.get(format!("{}/api/pedidos", common::BASE_URL)).bearer_auth(&token).send();
+    assert_eq!(pedidos_com_token().await.unwrap().status().as_u16(), 200);
+    let trocar = |atual: &str, nova: &str| {
+        client
+            .post(format!("{}/api/me/password", common::BASE_URL))
+            .header("cookie", &cookie)
+            .header("x-csrf-token", &csrf)
+            .json(&serde_json::json!({ "senha_atual": atual, "nova_senha": nova }))
+            .send()
+    };
+    let resp = trocar("errada", "Outra#Senha2025").await.unwrap();
+    assert_eq!(resp.status().as_u16(), 422);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["field"].as_str(), Some("senha_atual"));
+    assert!(body["error"].as_str().is_some());
+    let resp = trocar(senha, "123").await.unwrap();
+    assert_eq!(resp.status().as_u16(), 422);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["field"].as_str(), Some("nova_senha"));
+    let nova = "Outra#Senha2025";
+    let resp = trocar(senha, nova).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let set_cookie = resp.headers().get("set-cookie").and_then(|v| v.to_str().ok()).expect("Set-Cookie ausente");
+    let cookie = format!("session_id={}", common::extract_cookie(set_cookie, "session_id").unwrap());
+    assert_eq!(me_status(&client, &cookie).await, 200, "sessão atual continua válida com o novo identificador");
+    assert_eq!(me_status(&client, &outra).await, 401, "outras sessões são encerradas");
+    assert!(common::login(&client, &email, senha).await.is_none(), "senha antiga não vale mais");
+    assert_eq!(pedidos_com_token().await.unwrap().status().as_u16(), 401, "tokens de API são revogados");
+
+    // 3) Um pedido da conta
+    let products: Value = client.get(format!("{}/api/products", common::BASE_URL)).send().await.unwrap().json().await.unwrap();
+    let product_id = products["items"][0]["id"].as_u64().expect("Produto deve ter id");
+    client
+        .post(format!("{}/api/cart", common::BASE_URL))
+        .json(&serde_json::json!({ "product_id": product_id, "qty": 1 }))
+        .send()
+        .await
+        .unwrap();
+    let resp = client
+        .post(format!("{}/api/checkout", common::BASE_URL))
+        .header("cookie", &cookie)
+        .header("x-csrf-token", &csrf)
+        .json(&serde_json::json!({ "payment": { "method": "pix" }, "customer_email": email }))
+        .send()
+        .await
+        .unwrap();
+    assert!(resp.status().is_success(), "Checkout deve retornar 200, obtido {}", resp.status());
+    let body: Value = resp.json().await.unwrap();
+    let order_id = body["order_id"].as_str().unwrap().to_string();
+
+    // 4) Exclusão: exige a senha, encerra sessões e impede novo login
+    let excluir = |senha: &str| {
+        client
+            .delete(format!("{}/api/me", common::BASE_URL))
+            .header("cookie", &cookie)
+            .header("x-csrf-token", &csrf)
+            .json(&serde_json::json!({ "senha": senha }))
+            .send()
+    };
+    let resp = excluir("errada").await.unwrap();
+    assert_eq!(resp.status().as_u16(), 401);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["error"].as_str(), Some("Unauthorized"), "mesmo formato de erro do restante de /api/me");
+    assert!(body["message"].as_str().is_some());
+    let resp = excluir(nova).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    assert_eq!(me_status(&client, &cookie).await, 401);
+    assert!(common::login(&client, &email, nova).await.is_none());
+
+    // 5) O pedido continua no histórico
+    let (admin, _) = common::login(&client, "admin@teste.com", "123456").await.expect("login admin");
+    let pedidos: Vec<Value> = client
+        .get(format!("{}/api/pedidos", common::BASE_URL))
+        .header("cookie", &admin)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(pedidos.iter().any(|p| p["id"].as_str() == Some(order_id.as_str())));
+}
@@ -0,0 +1,102 @@
+use std::process::Command;
+
+mod common;
+
+fn admin_cli(args: &[&str], senha: &str) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_mercado-backend"))
+        .arg("admin")
+        .args(args)
+        .env("ADMIN_PASSWORD", senha)
+        .env_remove("APP_ENV")
+        .output()
+        .expect("Falha ao executar o subcomando admin")
+}
+
+#[tokio::test]
+async fn admin_bootstrap() {
+    let _server = common::spawn_server().await;
+    let client = reqwest::Client::new();
+
+    let ts = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_millis();
+    let email = format!("chefe{}@teste.com", ts);
+
+    // 1) Senha fraca é recusada pela política
+    let out = admin_cli(&["create", "--email", &email], "123456");
+    assert_eq!(out.status.code(), Some(1), "senha fraca deve falhar");
+
+    // 2) Uso incorreto
+    let out = admin_cli(&["create"], "Mercado#2025forte");
+    assert_eq!(out.status.code(), Some(2));
+
+    // 3) Cria o administrador e ele acessa a área administrativa
+    let senha = "Gerente#Forte2025";
+    let out = admin_cli(&["create", "--email", &email, "--nome", "Chefe"], senha);
+    assert_eq!(out.status.code(), Some(0), "stderr: {}", String::from_utf8_lossy(&out.stderr));
+
+    let resp = client
+        .post(format!("{}/api/login", common::BASE_URL))
+        .json(&serde_json::json!({ "email": email, "senha": senha }))
+        .send()
+        .await
+        .expect("Falha ao enviar login");
+    assert_eq!(resp.status().as_u16(), 200);
+    let body: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(body["usuario"]["papel"].as_str(), Some("admin"));
+
+    // 4) E-mail de cliente: recusado sem --promote (senha e papel intactos); com --promote, promovido
+    let cliente = format!("cliente{}@teste.com", ts);
+    let senha_cliente = "Cliente#Forte2025";
+    client
+        .post(format!("{}/api/register", common::BASE_URL))
+        .json(&serde_json::json!({ "nome": "Cliente", "email": cliente, "senha": senha_cliente }))
+        .send()
+        .await
+        .expect("Falha ao registrar");
+    let out = admin_cli(&["create", "--email", &cliente], senha);
+    assert_eq!(out.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&out.stderr).contains("--promote"));
+    let (cookie, _) = common::login(&client, &cliente, senha_cliente).await.expect("senha do cliente mantida");
+    let me: serde_json::Value = client.get(format!("{}/api/me", common::BASE_URL)).header("cookie", &cookie).send().await.unwrap().json().await.unwrap();
+    assert_ne!(me["papel"].as_str(), Some("admin"), "{}", me);
+
+    let out = admin_cli(&["create", "--email", &cliente, "--promote"], senha);
+    assert_eq!(out.status.code(), Some(0), "stderr: {}", String::from_utf8_lossy(&out.stderr));
+    assert!(String::from_utf8_lossy(&out.stdout).contains("promovida"));
+    assert!(common::login(&client, &cliente, senha_cliente).await.is_none(), "senha trocada na promoção");
+    let resp = client
+        .post(format!("{}/api/login", common::BASE_URL))
+        .json(&serde_json::json!({ "email": cliente, "senha": senha }))
+        .send()
+        .await
+        .unwrap();
+    let body: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(body["usuario"]["papel"].as_str(), Some("admin"));
+
+    // 5) Em produção o servidor não sobe enquanto a conta de demonstração tiver a senha padrão
+    let out = Command::new(env!("CARGO_BIN_EXE_mercado-backend"))
+        .env("APP_ENV", "production")
+        .output()
+        .expect("Falha ao executar o servidor");
+    assert_eq!(out.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&out.stderr).contains("credenciais padrão"));
+
+    // 6) Banco novo sem SEED_DEMO_ADMIN: nenhuma conta de demonstração
+    let dir = "target/tmp/admin-bootstrap";
+    let _ = std::fs::remove_dir_all(dir);
+    let db = format!("{}/mercado.db", dir);
+    let mercado_admin = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_mercado-admin"))
+            .arg("--database")
+            .arg(&db)
+            .args(args)
+            .env_remove("SEED_DEMO_ADMIN")
+            .env_remove("APP_ENV")
+            .output()
+            .expect("Falha ao executar mercado-admin")
+    };
+    assert!(mercado_admin(&["db", "migrate"]).status.success());
+    let out = mercado_admin(&["users", "list"]);
+    assert!(out.status.success());
+    let lista = String::from_utf8_lossy(&out.stdout).to_string();
+    assert!(!lista.contains("admin@teste.com"), "conta de demonstração só com SEED_DEMO_ADMIN: {}", lista);
+}
@@ -0,0 +1,140 @@
+use serde_json::Value;
+use std::process::{Command, Output};
+
+mod common;
+
+// Executa o binário mercado-admin com os argumentos dados
+fn admin(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_mercado-admin"))
+        .args(args)
+        .output()
+        .expect("Falha ao executar mercado-admin")
+}
+
+fn stdout(out: &Output) -> String {
+    String::from_utf8_lossy(&out.stdout).to_string()
+}
+
+async fn login_status(client: &reqwest::Client, email: &str, senha: &str) -> (u16, Option<String>) {
+    let resp = client
+        .post(format!("{}/api/login", common::BASE_URL))
+        .json(&serde_json::json!({ "email": email, "senha": senha }))
+        .send()
+        .await
+        .expect("Falha ao enviar login");
+    let cookie = resp
+        .headers()
+        .get("set-cookie")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|c| c.split(';').next())
+        .map(str::to_string);
+    (resp.status().as_u16(), cookie)
+}
+
+#[tokio::test]
+async fn admin_cli() {
+    // 1) Banco separado via --database: criação, catálogo, backup
+    let dir = "target/tmp/admin-cli";
+    let _ = std::fs::remove_dir_all(dir);
+    let db = format!("{}/mercado.db", dir);
+
+    let out = admin(&["--database", &db, "pedidos", "list"]);
+    assert_eq!(out.status.code(), Some(1), "banco inexistente só é criado pelo db migrate");
+    // db migrate só cria o esquema: nenhuma conta, mesmo com as variáveis de seed do servidor
+    let out = Command::new(env!("CARGO_BIN_EXE_mercado-admin"))
+        .args(["--database", &db, "db", "migrate"])
+        .env("SEED_DEMO_ADMIN", "1")
+        .env("ADMIN_EMAIL", "seed@teste.com")
+        .env("ADMIN_PASSWORD", "Mercado#2025forte")
+        .env_remove("APP_ENV")
+        .output()
+        .expect("Falha ao executar mercado-admin");
+    assert!(out.status.success());
+    let usuarios = stdout(&admin(&["--database", &db, "users", "list"]));
+    assert_eq!(usuarios.lines().count(), 1, "db migrate não cria contas: {}", usuarios);
+    let out = admin(&["--database", &db, "pedidos", "list"]);
+    assert!(out.status.success());
+    assert_eq!(stdout(&out).lines().count(), 1, "banco novo sem pedidos: só o cabeçalho");
+
+    // Administrador criado no banco de --database (senha pela política, via ADMIN_PASSWORD)
+    let criar_admin = |senha: &str| {
+        Command::new(env!("CARGO_BIN_EXE_mercado-admin"))
+            .args(["--database", &db, "users", "create-admin", "--email", "Gerente@Teste.com", "--nome", "Gerente"])
+            .env("ADMIN_PASSWORD", senha)
+            .output()
+            .expect("Falha ao executar mercado-admin")
+    };
+    assert_eq!(criar_admin("123").status.code(), Some(1), "senha fraca é recusada");
+    let out = criar_admin("Mercado#2025forte");
+    assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+    let usuarios = stdout(&admin(&["--database", &db, "users", "list"]));
+    assert!(usuarios.lines().any(|l| l.contains("gerente@teste.com") && l.contains("\tadmin\t")), "{}", usuarios);
+    assert_eq!(admin(&["--database", &db, "users", "create-admin", "--nome", "Sem Email"]).status.code(), Some(2));
+
+    // Pedido antigo (sem total com juros gravado): list e show mostram o total do pedido
+    let pool = sqlx::SqlitePool::connect(&format!("sqlite://{}", db)).await.unwrap();
+    sqlx::query("INSERT INTO pedidos (id, total_cents, payment_method, total_with_interest_cents) VALUES ('legado-1', 1590, 'pix', 0)")
+        .execute(&pool)
+        .await
+        .unwrap();
+    pool.close().await;
+    let lista = stdout(&admin(&["--database", &db, "pedidos", "list"]));
+    assert!(lista.lines().any(|l| l.starts_with("legado-1\tpaid\tR$ 15,90\tR$ 15,90\t")), "{}", lista);
+    assert!(stdout(&admin(&["--database", &db, "pedidos", "show", "legado-1"])).contains("total=R$ 15,90"));
+
+    let out = admin(&["--database", &db, "products", "export", "--format", "json"]);
+    assert!(out.status.success());
+    let mut produtos: Vec<Value> = serde_json::from_str(&stdout(&out)).expect("export em JSON");
+    assert!(!produtos.is_empty());
+
+    produtos[0]["price_cents"] = serde_json::json!(1234);
+    let mut novo = produtos[0].clone();
+    novo["id"] = Value::Null;
+    novo["sku"] = serde_json::json!("CLI-NOVO");
+    novo["name"] = serde_json::json!("Produto Importado CLI");
+    produtos.push(novo);
+    let arquivo = format!("{}/produtos.json", dir);
+    std::fs::write(&arquivo, serde_json::to_string(&produtos).unwrap()).unwrap();
+    let out = admin(&["--database", &db, "products", "import", &arquivo]);
+    assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+    assert!(stdout(&out).contains(&format!("1 produtos criados, {} atualizados", produtos.len() - 1)));
+
+    let exportado: Vec<Value> = serde_json::from_str(&stdout(&admin(&["--database", &db, "products", "export", "--format", "json"]))).unwrap();
+    assert_eq!(exportado.len(), produtos.len());
+    assert_eq!(exportado[0]["price_cents"], 1234);
+    assert!(exportado.iter().any(|p| p["name"] == "Produto Importado CLI"));
+
+    let backup = format!("{}/backup.db", dir);
+    assert!(admin(&["--database", &db, "db", "backup", &backup]).status.success());
+    assert!(std::fs::metadata(&backup).map(|m| m.len() > 0).unwrap_or(false));
+    assert_eq!(admin(&["--database", &db, "db", "backup", &backup]).status.code(), Some(1), "não sobrescreve backup");
+    assert_eq!(admin(&["--database", &db, "pedidos"]).status.code(), Some(2));
+
+    // 2) Banco do servidor: desativar encerra a sessão e bloqueia o login até reativar
+    let _server = common::spawn_server().await;
+    let client = reqwest::Client::new();
+    let ts = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_millis();
+    let email = format!("cli{}@teste.com", ts);
+    let senha = "Mercado#2025forte";
+    client
+        .post(format!("{}/api/register", common::BASE_URL))
+        .json(&serde_json::json!({ "nome": "Conta CLI", "email": email, "senha": senha }))
+        .send()
+        .await
+        .expect("Falha ao registrar");
+    let (status, cookie) = login_status(&client, &email, senha).await;
+    assert_eq!(status, 200);
+    let cookie = cookie.expect("cookie de sessão");
+
+    let out = admin(&["--database", "data/mercado.db", "users", "disable", &email]);
+    assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+    let lista = stdout(&admin(&["--database", "data/mercado.db", "users", "list"]));
+    assert!(lista.lines().any(|l| l.contains(&email) && l.ends_with("desativada")), "{}", lista);
+
+    let resp = client.get(format!("{}/api/auth/me", common::BASE_URL)).header("cookie", &cookie).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 401, "sessão encerrada ao desativar");
+    assert_eq!(login_status(&client, &email, senha).await.0, 403);
+
+    assert!(admin(&["--database", "data/mercado.db", "users", "enable", &email]).status.success());
+    assert_eq!(login_status(&client, &email, senha).await.0, 200);
+}
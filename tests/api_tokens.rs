@@ -0,0 +1,105 @@
+use serde_json::Value;
+
+mod common;
+
+#[tokio::test]
+async fn api_tokens() {
+    let _server = common::spawn_server().await;
+    let client = reqwest::Client::new();
+
+    let ts = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_millis();
+    let email = format!("tokens{}@teste.com", ts);
+    let senha = "Mercado#2025forte";
+    client
+        .post(format!("{}/api/register", common::BASE_URL))
+        .json(&serde_json::json!({ "nome": "Tokens", "email": email, "senha": senha }))
+        .send()
+        .await
+        .expect("Falha ao registrar");
+    let resp = client
+        .post(format!("{}/api/login", common::BASE_URL))
+        .json(&serde_json::json!({ "email": email, "senha": senha }))
+        .send()
+        .await
+        .expect("Falha ao enviar login");
+    assert!(resp.status().is_success());
+    let set_cookie = resp.headers().get("set-cookie").and_then(|v| v.to_str().ok()).expect("Set-Cookie ausente");
+    let cookie = format!("session_id={}", common::extract_cookie(set_cookie, "session_id").expect("session_id não encontrado"));
+    let body: Value = resp.json().await.unwrap();
+    let csrf = body["csrf_token"].as_str().expect("csrf_token ausente").to_string();
+
+    let criar = |payload: Value| {
+        client
+            .post(format!("{}/api/me/tokens", common::BASE_URL))
+            .header("cookie", &cookie)
+            .header("x-csrf-token", &csrf)
+            .json(&payload)
+            .send()
+    };
+
+    // 1) Escopo desconhecido é recusado
+    let resp = criar(serde_json::json!({ "nome": "x", "scopes": ["tudo"] })).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 422);
+
+    // 2) Token de leitura de pedidos: o valor só aparece na criação
+    let resp = criar(serde_json::json!({ "nome": "monitor", "scopes": ["orders:read"], "expira_em_dias": 30 })).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 201);
+    let body: Value = resp.json().await.unwrap();
+    let token = body["token"].as_str().expect("token ausente").to_string();
+    let token_id = body["id"].as_str().unwrap().to_string();
+    assert!(token.starts_with("mkt_"));
+
+    let bearer = |metodo: reqwest::Method, path: &str, token: &str| {
+        client
+            .request(metodo, format!("{}{}", common::BASE_URL, path))
+            .header("authorization", format!("Bearer {}", token))
+            .send()
+    };
+
+    let resp = bearer(reqwest::Method::GET, "/api/pedidos", &token).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200, "orders:read deve listar pedidos");
+
+    // 3) Fora do escopo → 403; rotas de conta não aceitam token
+    let resp = bearer(reqwest::Method::GET, "/api/reports/daily", &token).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["escopo_necessario"].as_str(), Some("reports:read"));
+    let resp = bearer(reqwest::Method::GET, "/api/me/tokens", &token).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    // 4) Token com escrita dispensa CSRF (pedido inexistente → 404, não 403)
+    let resp = criar(serde_json::json!({ "nome": "integração", "scopes": ["orders:write"] })).await.unwrap();
+    let escrita: Value = resp.json().await.unwrap();
+    let escrita = escrita["token"].as_str().unwrap().to_string();
+    let resp = bearer(reqwest::Method::POST, "/api/pedidos/nao-existe/cancelar", &escrita).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 404);
+
+    // 5) Listagem não expõe o valor e registra o último uso
+    let lista: Vec<Value> = client
+        .get(format!("{}/api/me/tokens", common::BASE_URL))
+        .header("cookie", &cookie)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let item = lista.iter().find(|t| t["id"].as_str() == Some(&token_id)).expect("token não listado");
+    assert!(item.get("token").is_none());
+    assert!(item["last_used_at"].as_str().is_some());
+    assert_eq!(item["scopes"], serde_json::json!(["orders:read"]));
+
+    // 6) Token inválido e token revogado → 401
+    let resp = bearer(reqwest::Method::GET, "/api/pedidos", "mkt_invalido").await.unwrap();
+    assert_eq!(resp.status().as_u16(), 401);
+    let resp = client
+        .delete(format!("{}/api/me/tokens/{}", common::BASE_URL, token_id))
+        .header("cookie", &cookie)
+        .header("x-csrf-token", &csrf)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let resp = bearer(reqwest::Method::GET, "/api/pedidos", &token).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 401);
+}
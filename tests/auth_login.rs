@@ -22,7 +22,7 @@ async fn auth_login() {
     assert_eq!(ok_body["autenticado"], Value::Bool(true));
     assert!(ok_body["usuario"]["email"].as_str().is_some());
 
-    // 2) Usuário inexistente
+    // 2) Usuário inexistente: mesma resposta 401 da senha incorreta (sem enumeração)
     let nf_resp = client
         .post(format!("{}/api/login", common::BASE_URL))
         .json(&serde_json::json!({
@@ -32,7 +32,7 @@ async fn auth_login() {
         .send()
         .await
         .expect("Falha ao chamar /api/login (inexistente)");
-    assert_eq!(nf_resp.status(), reqwest::StatusCode::NOT_FOUND);
+    assert_eq!(nf_resp.status(), reqwest::StatusCode::UNAUTHORIZED);
     let nf_body: Value = nf_resp.json().await.expect("Falha ao parsear JSON (inexistente)");
     assert!(nf_body["erro"].as_str().is_some());
 
@@ -49,5 +49,5 @@ async fn auth_login() {
     assert_eq!(bad_resp.status(), reqwest::StatusCode::UNAUTHORIZED);
     let bad_body: Value = bad_resp.json().await.expect("Falha ao parsear JSON (senha incorreta)");
     assert_eq!(bad_body["autenticado"], Value::Bool(false));
-    assert!(bad_body["erro"].as_str().is_some());
+    assert_eq!(bad_body["erro"], nf_body["erro"], "Mensagens devem ser idênticas");
 }
\ No newline at end of file
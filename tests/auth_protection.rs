@@ -2,16 +2,6 @@ mod common;
 use common::spawn_server;
 use common::BASE_URL;
 
-fn extract_cookie(header: &str, name: &str) -> Option<String> {
-    for part in header.split(';') {
-        let kv = part.trim();
-        if let Some((k, v)) = kv.split_once('=') {
-            if k == name { return Some(v.to_string()); }
-        }
-    }
-    None
-}
-
 #[tokio::test]
 async fn users_requires_auth() {
     let _guard = spawn_server().await;
@@ -37,7 +27,7 @@ async fn users_requires_auth() {
         .expect("Falha ao enviar login");
     assert!(login.status().is_success(), "Login deve 200, veio {}", login.status());
     let set_cookie = login.headers().get("set-cookie").and_then(|v| v.to_str().ok()).expect("Set-Cookie ausente");
-    let sid = extract_cookie(set_cookie, "session_id").expect("session_id não encontrado");
+    let sid = common::extract_cookie(set_cookie, "session_id").expect("session_id não encontrado");
 
     // Com cookie: deve 200
     let r2 = client
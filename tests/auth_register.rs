@@ -19,7 +19,7 @@ async fn auth_register() {
         .json(&serde_json::json!({
             "nome": "Usuário Teste",
             "email": email,
-            "senha": "123456"
+            "senha": "Mercado#2025forte"
         }))
         .send()
         .await
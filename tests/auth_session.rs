@@ -2,16 +2,6 @@ mod common;
 use common::spawn_server;
 use common::BASE_URL;
 
-fn extract_cookie(header: &str, name: &str) -> Option<String> {
-    for part in header.split(';') {
-        let kv = part.trim();
-        if let Some((k, v)) = kv.split_once('=') {
-            if k == name { return Some(v.to_string()); }
-        }
-    }
-    None
-}
-
 #[tokio::test]
 async fn auth_session_flow() {
     let _guard = spawn_server().await;
@@ -29,7 +19,7 @@ async fn auth_session_flow() {
         .expect("Falha ao enviar login");
     assert!(resp.status().is_success(), "Login deve retornar 200, veio {}", resp.status());
     let set_cookie = resp.headers().get("set-cookie").and_then(|v| v.to_str().ok()).expect("Header Set-Cookie ausente");
-    let sid = extract_cookie(set_cookie, "session_id").expect("session_id não encontrado no cookie");
+    let sid = common::extract_cookie(set_cookie, "session_id").expect("session_id não encontrado no cookie");
 
     // /api/auth/me com cookie
     let me_resp = client
@@ -39,11 +29,14 @@ async fn auth_session_flow() {
         .await
         .expect("Falha ao chamar /api/auth/me");
     assert!(me_resp.status().is_success(), "auth/me deve retornar 200, veio {}", me_resp.status());
+    let me: serde_json::Value = me_resp.json().await.expect("Falha ao parsear /api/auth/me");
+    let csrf = me["csrf_token"].as_str().expect("csrf_token ausente").to_string();
 
     // Logout
     let lo_resp = client
         .post(format!("{}/api/logout", BASE_URL))
         .header("cookie", format!("session_id={}", sid))
+        .header("x-csrf-token", &csrf)
         .send()
         .await
         .expect("Falha ao chamar /api/logout");
@@ -15,7 +15,8 @@ async fn cart_flow() {
         .await
         .expect("Falha ao obter /api/products");
     assert!(products_resp.status().is_success());
-    let products: Vec<Value> = products_resp.json().await.expect("Falha ao parsear produtos");
+    let body: Value = products_resp.json().await.expect("Falha ao parsear produtos");
+    let products = body["items"].as_array().cloned().unwrap_or_default();
     assert!(!products.is_empty(), "Catálogo deve conter ao menos 1 produto");
     let first = &products[0];
     let product_id = first["id"].as_u64().expect("Produto deve ter id");
@@ -15,7 +15,8 @@ async fn checkout() {
         .await
         .expect("Falha ao obter /api/products");
     assert!(products_resp.status().is_success());
-    let products: Vec<Value> = products_resp.json().await.expect("Falha ao parsear produtos");
+    let body: Value = products_resp.json().await.expect("Falha ao parsear produtos");
+    let products = body["items"].as_array().cloned().unwrap_or_default();
     assert!(!products.is_empty(), "Catálogo deve conter ao menos 1 produto");
     let product_id = products[0]["id"].as_u64().expect("Produto deve ter id");
 
@@ -28,6 +29,18 @@ async fn checkout() {
         .expect("Falha ao adicionar ao carrinho");
     assert!(add_resp.status().is_success());
 
+    // Checkout exige sessão de conta verificada (admin semeado já é verificado)
+    let login = client
+        .post(format!("{}/api/login", common::BASE_URL))
+        .json(&serde_json::json!({ "email": "admin@teste.com", "senha": "123456" }))
+        .send()
+        .await
+        .expect("Falha ao enviar login");
+    let set_cookie = login.headers().get("set-cookie").and_then(|v| v.to_str().ok()).expect("Set-Cookie ausente");
+    let sid = common::extract_cookie(set_cookie, "session_id").expect("session_id não encontrado");
+    let login_body: Value = login.json().await.expect("Falha ao parsear login");
+    let csrf = login_body["csrf_token"].as_str().expect("csrf_token ausente").to_string();
+
     // Checkout (PIX, com termos aceitos)
     let payload = serde_json::json!({
         "payment": { "method": "pix" },
@@ -36,6 +49,8 @@ async fn checkout() {
 
     let checkout_resp = client
         .post(format!("{}/api/checkout", common::BASE_URL))
+        .header("cookie", format!("session_id={}", sid))
+        .header("x-csrf-token", &csrf)
         .json(&payload)
         .send()
         .await
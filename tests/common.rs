@@ -16,6 +16,36 @@ impl Drop for ServerGuard {
 
 pub const BASE_URL: &str = "http://127.0.0.1:8080";
 
+/// Valor do cookie `name` num cabeçalho Set-Cookie.
+#[allow(dead_code)]
+pub fn extract_cookie(header: &str, name: &str) -> Option<String> {
+    for part in header.split(';') {
+        let kv = part.trim();
+        if let Some((k, v)) = kv.split_once('=') {
+            if k == name { return Some(v.to_string()); }
+        }
+    }
+    None
+}
+
+/// Faz login e retorna (cabeçalho cookie, csrf_token), ou None se o login falhar.
+#[allow(dead_code)]
+pub async fn login(client: &reqwest::Client, email: &str, senha: &str) -> Option<(String, String)> {
+    let resp = client
+        .post(format!("{}/api/login", BASE_URL))
+        .json(&serde_json::json!({ "email": email, "senha": senha }))
+        .send()
+        .await
+        .expect("Falha ao enviar login");
+    if !resp.status().is_success() {
+        return None;
+    }
+    let set_cookie = resp.headers().get("set-cookie").and_then(|v| v.to_str().ok())?;
+    let cookie = format!("session_id={}", extract_cookie(set_cookie, "session_id")?);
+    let body: serde_json::Value = resp.json().await.ok()?;
+    Some((cookie, body["csrf_token"].as_str()?.to_string()))
+}
+
 /// Tenta chamar GET /health e retorna true se a API respondeu 200 { ok: true }.
 async fn is_server_up() -> bool {
     match reqwest::Client::new()
@@ -39,11 +69,88 @@ async fn wait_until_ready(max_attempts: usize) -> bool {
     false
 }
 
+/// Cliente que sai de um endereço de loopback próprio (127.x.y.z), para testes de limites por IP
+/// não atingirem os demais testes, que saem de 127.0.0.1. Só o Linux atende toda a faixa
+/// 127.0.0.0/8 no loopback; nos demais sistemas o cliente sai de 127.0.0.1.
+#[allow(dead_code)]
+pub fn own_ip_client() -> reqwest::Client {
+    if !cfg!(target_os = "linux") {
+        return reqwest::Client::new();
+    }
+    let n = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_nanos();
+    let origem = std::net::IpAddr::from([127, 1 + (n % 200) as u8, (n >> 8) as u8, 1 + ((n >> 16) % 250) as u8]);
+    reqwest::Client::builder().local_address(origem).build().expect("Falha ao criar cliente")
+}
+
+/// Token do link mais recente enviado a `email` (e-mails gravados em `data/mail`).
+#[allow(dead_code)]
+pub fn token_from_mail(email: &str) -> Option<String> {
+    let mut files: Vec<_> = std::fs::read_dir("data/mail").ok()?.filter_map(|e| e.ok()).map(|e| e.path()).collect();
+    files.sort();
+    files.iter().rev().find_map(|p| {
+        let content = std::fs::read_to_string(p).ok()?;
+        if !content.contains(&format!("To: {}\r\n", email)) { return None; }
+        // Redefinição de senha usa ?token=, verificação de e-mail usa ?verify=
+        let start = ["token=", "verify="].iter().find_map(|p| content.find(p).map(|i| i + p.len()))?;
+        Some(content[start..].chars().take_while(|c| c.is_ascii_alphanumeric()).collect())
+    })
+}
+
+/// Cadastra a conta, confirma o e-mail pelo link enviado e faz login; retorna (cookie, csrf_token).
+#[allow(dead_code)]
+pub async fn verified_user_session(client: &reqwest::Client, nome: &str, email: &str, senha: &str) -> (String, String) {
+    client
+        .post(format!("{}/api/register", BASE_URL))
+        .json(&serde_json::json!({ "nome": nome, "email": email, "senha": senha }))
+        .send()
+        .await
+        .expect("Falha ao registrar");
+    let token = token_from_mail(email).expect("e-mail de verificação não encontrado");
+    let resp = client.get(format!("{}/api/verify-email?token={}", BASE_URL, token)).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200, "verificação de e-mail");
+    login(client, email, senha).await.expect("login deve funcionar")
+}
+
+/// Os checkouts dos testes baixam o estoque do banco compartilhado: produtos com menos de
+/// 20 unidades voltam a 50 pelo PATCH de estoque do admin, para execuções repetidas não
+/// esgotarem o catálogo. Sem login de admin (ex.: IP bloqueado), não repõe nada.
+async fn repor_estoque() {
+    let client = reqwest::Client::new();
+    let Some((cookie, csrf)) = login(&client, "admin@teste.com", "123456").await else {
+        return;
+    };
+    let mut pagina = 1;
+    loop {
+        let Ok(resp) = client.get(format!("{}/api/products?page={}&per_page=100", BASE_URL, pagina)).send().await else {
+            return;
+        };
+        let Ok(body) = resp.json::<serde_json::Value>().await else {
+            return;
+        };
+        for p in body["items"].as_array().into_iter().flatten() {
+            if p["stock"].as_i64().unwrap_or(0) < 20 {
+                let _ = client
+                    .patch(format!("{}/api/admin/products/{}/stock", BASE_URL, p["id"]))
+                    .header("cookie", &cookie)
+                    .header("x-csrf-token", &csrf)
+                    .json(&serde_json::json!({ "stock": 50 }))
+                    .send()
+                    .await;
+            }
+        }
+        if pagina >= body["total_pages"].as_i64().unwrap_or(1) {
+            return;
+        }
+        pagina += 1;
+    }
+}
+
 /// Inicia o servidor do backend (se ainda não estiver no ar) e retorna um guard.
 /// - Se já estiver rodando, não inicia novo processo e o guard não mata nada.
 /// - Se não estiver rodando, executa `cargo run` em background e aguarda `/health`.
 pub async fn spawn_server() -> ServerGuard {
     if wait_until_ready(10).await {
+        repor_estoque().await;
         return ServerGuard { child: None };
     }
 
@@ -76,7 +183,14 @@ pub async fn spawn_server() -> ServerGuard {
     let _ = std::fs::create_dir_all("target/tmp");
     let _ = std::fs::copy(bin_path, test_bin_path);
 
+    // Os testes entram com a conta de demonstração (admin@teste.com), criada só com SEED_DEMO_ADMIN.
+    // Todos saem de 127.0.0.1: isento do bloqueio por IP para as falhas de login somadas dos
+    // testes não travarem a suíte (login_lockout usa outro endereço de loopback).
     let mut child = Command::new(test_bin_path)
+        .env("SEED_DEMO_ADMIN", "1")
+        .env("LOGIN_IP_EXEMPT", "127.0.0.1")
+        // O receptor do teste de webhooks escuta em 127.0.0.1
+        .env("WEBHOOK_ALLOW_PRIVATE_HOSTS", "127.0.0.1")
         .stdout(Stdio::null())
         .stderr(Stdio::null())
         .spawn()
@@ -89,6 +203,7 @@ pub async fn spawn_server() -> ServerGuard {
         let _ = child.kill();
         panic!("Servidor não ficou pronto em tempo hábil");
     }
+    repor_estoque().await;
 
     ServerGuard { child: Some(child) }
 }
\ No newline at end of file
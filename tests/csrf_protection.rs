@@ -0,0 +1,66 @@
+use serde_json::Value;
+
+mod common;
+
+#[tokio::test]
+async fn csrf_protection() {
+    let _server = common::spawn_server().await;
+    let client = reqwest::Client::new();
+
+    let login = client
+        .post(format!("{}/api/login", common::BASE_URL))
+        .json(&serde_json::json!({ "email": "admin@teste.com", "senha": "123456" }))
+        .send()
+        .await
+        .expect("Falha ao enviar login");
+    let set_cookie = login.headers().get("set-cookie").and_then(|v| v.to_str().ok()).expect("Set-Cookie ausente");
+    let cookie = format!("session_id={}", common::extract_cookie(set_cookie, "session_id").expect("session_id não encontrado"));
+    let body: Value = login.json().await.expect("Falha ao parsear login");
+    let csrf = body["csrf_token"].as_str().expect("csrf_token ausente").to_string();
+
+    // 1) /api/auth/me devolve o mesmo token da sessão
+    let me: Value = client
+        .get(format!("{}/api/auth/me", common::BASE_URL))
+        .header("cookie", &cookie)
+        .send()
+        .await
+        .expect("Falha em /api/auth/me")
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(me["csrf_token"].as_str(), Some(csrf.as_str()));
+
+    let logout = |token: Option<&str>, origin: Option<&str>| {
+        let mut req = client.post(format!("{}/api/logout", common::BASE_URL)).header("cookie", &cookie);
+        if let Some(t) = token { req = req.header("x-csrf-token", t); }
+        if let Some(o) = origin { req = req.header("origin", o); }
+        req.send()
+    };
+
+    // 2) Sem token, com token errado ou de outra origem: 403 (sessão continua ativa)
+    assert_eq!(logout(None, None).await.unwrap().status().as_u16(), 403);
+    assert_eq!(logout(Some("token-errado"), None).await.unwrap().status().as_u16(), 403);
+    assert_eq!(logout(Some(&csrf), Some("https://site-malicioso.example")).await.unwrap().status().as_u16(), 403);
+    let referer = client
+        .post(format!("{}/api/logout", common::BASE_URL))
+        .header("cookie", &cookie)
+        .header("x-csrf-token", &csrf)
+        .header("referer", "https://site-malicioso.example/pagina")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(referer.status().as_u16(), 403);
+
+    // 3) GET não exige token
+    let pedidos = client
+        .get(format!("{}/api/pedidos", common::BASE_URL))
+        .header("cookie", &cookie)
+        .send()
+        .await
+        .unwrap();
+    assert!(pedidos.status().is_success());
+
+    // 4) Mesma origem e token correto: logout aceito
+    let ok = logout(Some(&csrf), Some(common::BASE_URL)).await.unwrap();
+    assert!(ok.status().is_success(), "Logout deve retornar 200, veio {}", ok.status());
+}
@@ -0,0 +1,111 @@
+use serde_json::Value;
+
+mod common;
+
+async fn available_for(client: &reqwest::Client, slot_id: &str) -> i64 {
+    let slots: Vec<Value> = client
+        .get(format!("{}/api/delivery-slots", common::BASE_URL))
+        .send()
+        .await
+        .expect("Falha ao obter /api/delivery-slots")
+        .json()
+        .await
+        .expect("Falha ao parsear janelas");
+    slots
+        .iter()
+        .find(|s| s["id"].as_str() == Some(slot_id))
+        .and_then(|s| s["available"].as_i64())
+        .unwrap_or(0)
+}
+
+#[tokio::test]
+async fn delivery_slots() {
+    let _server = common::spawn_server().await;
+    let client = reqwest::Client::new();
+
+    // 1) Listar janelas disponíveis
+    let resp = client
+        .get(format!("{}/api/delivery-slots", common::BASE_URL))
+        .send()
+        .await
+        .expect("Falha ao obter /api/delivery-slots");
+    assert!(resp.status().is_success());
+    let slots: Vec<Value> = resp.json().await.expect("Falha ao parsear janelas");
+    assert!(!slots.is_empty(), "Deve existir ao menos uma janela de entrega");
+    let slot_id = slots[0]["id"].as_str().expect("Janela deve ter id").to_string();
+    let before = slots[0]["available"].as_i64().expect("Janela deve ter available");
+
+    // Checkout exige sessão de conta verificada
+    let login = client
+        .post(format!("{}/api/login", common::BASE_URL))
+        .json(&serde_json::json!({ "email": "admin@teste.com", "senha": "123456" }))
+        .send()
+        .await
+        .expect("Falha ao enviar login");
+    let set_cookie = login.headers().get("set-cookie").and_then(|v| v.to_str().ok()).expect("Set-Cookie ausente");
+    let sid = common::extract_cookie(set_cookie, "session_id").expect("session_id não encontrado");
+    let login_body: Value = login.json().await.expect("Falha ao parsear login");
+    let csrf = login_body["csrf_token"].as_str().expect("csrf_token ausente").to_string();
+
+    // 2) Checkout com janela inexistente deve falhar com 422
+    client
+        .post(format!("{}/api/cart", common::BASE_URL))
+        .json(&serde_json::json!({ "product_id": 1, "qty": 1 }))
+        .send()
+        .await
+        .expect("Falha ao adicionar ao carrinho");
+    let bad = client
+        .post(format!("{}/api/checkout", common::BASE_URL))
+        .header("cookie", format!("session_id={}", sid))
+        .header("x-csrf-token", &csrf)
+        .json(&serde_json::json!({
+            "payment": { "method": "pix" },
+            "customer_email": "teste@exemplo.com",
+            "delivery_slot_id": "1999-01-01T08:00"
+        }))
+        .send()
+        .await
+        .expect("Falha ao chamar checkout");
+    assert_eq!(bad.status().as_u16(), 422, "Janela inexistente deve retornar 422");
+
+    // 3) Checkout reservando a janela
+    let ok = client
+        .post(format!("{}/api/checkout", common::BASE_URL))
+        .header("cookie", format!("session_id={}", sid))
+        .header("x-csrf-token", &csrf)
+        .json(&serde_json::json!({
+            "payment": { "method": "pix" },
+            "customer_email": "teste@exemplo.com",
+            "delivery_slot_id": slot_id
+        }))
+        .send()
+        .await
+        .expect("Falha ao chamar checkout");
+    assert!(ok.status().is_success(), "Checkout deve retornar 200, obtido {}", ok.status());
+    let body: Value = ok.json().await.expect("Falha ao parsear checkout");
+    assert_eq!(body["delivery_slot_id"].as_str(), Some(slot_id.as_str()));
+    let order_id = body["order_id"].as_str().expect("order_id deve existir").to_string();
+    assert_eq!(available_for(&client, &slot_id).await, before - 1, "Reserva deve ocupar uma vaga");
+
+    // 4) Cancelar o pedido devolve a vaga
+    let cancel = client
+        .post(format!("{}/api/pedidos/{}/cancelar", common::BASE_URL, order_id))
+        .header("cookie", format!("session_id={}", sid))
+        .header("x-csrf-token", &csrf)
+        .send()
+        .await
+        .expect("Falha ao cancelar pedido");
+    assert!(cancel.status().is_success(), "Cancelamento deve retornar 200, obtido {}", cancel.status());
+    assert_eq!(available_for(&client, &slot_id).await, before, "Cancelamento deve liberar a vaga");
+
+    // 5) Cancelar novamente não libera outra vaga
+    let again = client
+        .post(format!("{}/api/pedidos/{}/cancelar", common::BASE_URL, order_id))
+        .header("cookie", format!("session_id={}", sid))
+        .header("x-csrf-token", &csrf)
+        .send()
+        .await
+        .expect("Falha ao cancelar pedido");
+    assert_eq!(again.status().as_u16(), 409);
+    assert_eq!(available_for(&client, &slot_id).await, before);
+}
@@ -0,0 +1,75 @@
+use serde_json::Value;
+
+mod common;
+
+#[tokio::test]
+async fn documento_fiscal() {
+    let _server = common::spawn_server().await;
+    let client = reqwest::Client::new();
+
+    let ts = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_millis();
+    let email = format!("empresa{}@teste.com", ts);
+    let senha = "Mercado#2025forte";
+    let (cookie, csrf) = common::verified_user_session(&client, "Empresa", &email, senha).await;
+
+    let patch = |body: Value| {
+        client
+            .patch(format!("{}/api/me", common::BASE_URL))
+            .header("cookie", &cookie)
+            .header("x-csrf-token", &csrf)
+            .json(&body)
+            .send()
+    };
+
+    // 1) CNPJ no cadastro: dígito verificador errado recusado; numérico e alfanumérico aceitos sem máscara
+    let resp = patch(serde_json::json!({ "cnpj": "11.222.333/0001-82" })).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 422);
+    let resp = patch(serde_json::json!({ "cnpj": "12.abc.345/01de-35" })).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["cnpj"].as_str(), Some("12ABC34501DE35"));
+    let resp = patch(serde_json::json!({ "cnpj": "11.222.333/0001-81" })).await.unwrap();
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["cnpj"].as_str(), Some("11222333000181"));
+
+    let products: Value = client.get(format!("{}/api/products", common::BASE_URL)).send().await.unwrap().json().await.unwrap();
+    let product_id = products["items"][0]["id"].as_u64().expect("Produto deve ter id");
+    let add = || {
+        client
+            .post(format!("{}/api/cart", common::BASE_URL))
+            .json(&serde_json::json!({ "product_id": product_id, "qty": 1 }))
+            .send()
+    };
+    let checkout = |documento: Option<&str>| {
+        let mut payload = serde_json::json!({ "payment": { "method": "pix" }, "customer_email": email });
+        if let Some(d) = documento {
+            payload["documento"] = Value::String(d.to_string());
+        }
+        client
+            .post(format!("{}/api/checkout", common::BASE_URL))
+            .header("cookie", &cookie)
+            .header("x-csrf-token", &csrf)
+            .json(&payload)
+            .send()
+    };
+
+    // 2) Documento inválido no pedido → 422 com o campo
+    add().await.unwrap();
+    let resp = checkout(Some("529.982.247-26")).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 422);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["field"].as_str(), Some("documento"));
+
+    // 3) CPF informado no pedido, devolvido formatado
+    let resp = checkout(Some("52998224725")).await.unwrap();
+    assert!(resp.status().is_success(), "Checkout deve retornar 200, obtido {}", resp.status());
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["documento"].as_str(), Some("529.982.247-25"));
+
+    // 4) Sem documento no pedido: usa o do cadastro
+    add().await.unwrap();
+    let resp = checkout(None).await.unwrap();
+    assert!(resp.status().is_success());
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["documento"].as_str(), Some("11.222.333/0001-81"));
+}
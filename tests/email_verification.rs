@@ -0,0 +1,111 @@
+use serde_json::Value;
+
+mod common;
+
+#[tokio::test]
+async fn email_verification() {
+    let _server = common::spawn_server().await;
+    let client = reqwest::Client::new();
+
+    let ts = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_millis();
+    let email = format!("verifica{}@teste.com", ts);
+    let senha = "Mercado#2025forte";
+    let reg = client
+        .post(format!("{}/api/register", common::BASE_URL))
+        .json(&serde_json::json!({ "nome": "Verifica", "email": email, "senha": senha }))
+        .send()
+        .await
+        .expect("Falha ao registrar");
+    assert!(reg.status().is_success());
+
+    let login = client
+        .post(format!("{}/api/login", common::BASE_URL))
+        .json(&serde_json::json!({ "email": email, "senha": senha }))
+        .send()
+        .await
+        .expect("Falha ao enviar login");
+    assert!(login.status().is_success(), "Conta não verificada ainda pode entrar");
+    let set_cookie = login.headers().get("set-cookie").and_then(|v| v.to_str().ok()).expect("Set-Cookie ausente");
+    let cookie = format!("session_id={}", common::extract_cookie(set_cookie, "session_id").expect("session_id não encontrado"));
+    let login_body: Value = login.json().await.expect("Falha ao parsear login");
+    let csrf = login_body["csrf_token"].as_str().expect("csrf_token ausente").to_string();
+
+    let me: Value = client
+        .get(format!("{}/api/auth/me", common::BASE_URL))
+        .header("cookie", &cookie)
+        .send()
+        .await
+        .expect("Falha em /api/auth/me")
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(me["verificado"], Value::Bool(false));
+
+    // 1) Sem verificação o checkout é recusado
+    client
+        .post(format!("{}/api/cart", common::BASE_URL))
+        .json(&serde_json::json!({ "product_id": 1, "qty": 1 }))
+        .send()
+        .await
+        .expect("Falha ao adicionar ao carrinho");
+    let checkout = serde_json::json!({ "payment": { "method": "pix" }, "customer_email": email });
+    let blocked = client
+        .post(format!("{}/api/checkout", common::BASE_URL))
+        .header("cookie", &cookie)
+        .header("x-csrf-token", &csrf)
+        .json(&checkout)
+        .send()
+        .await
+        .expect("Falha no checkout");
+    assert_eq!(blocked.status().as_u16(), 403);
+
+    // 2) Reenvio logo após o cadastro é limitado
+    let resend = client
+        .post(format!("{}/api/verify-email/resend", common::BASE_URL))
+        .json(&serde_json::json!({ "email": email }))
+        .send()
+        .await
+        .expect("Falha no reenvio");
+    assert_eq!(resend.status().as_u16(), 429);
+    assert!(resend.headers().get("retry-after").is_some());
+
+    // 3) Token inválido e token válido
+    let bad = client
+        .get(format!("{}/api/verify-email?token=invalido", common::BASE_URL))
+        .send()
+        .await
+        .expect("Falha ao verificar");
+    assert_eq!(bad.status().as_u16(), 400);
+    let token = common::token_from_mail(&email).expect("E-mail de verificação não encontrado");
+    // O link do e-mail abre a página de login (quem confirma é o fetch da página)
+    let pagina = client
+        .get(format!("{}/login?verify={}", common::BASE_URL, token))
+        .send()
+        .await
+        .expect("Falha ao abrir a página");
+    assert_eq!(pagina.status().as_u16(), 200);
+    assert!(pagina.text().await.unwrap().contains("/api/verify-email?token="));
+    let ok = client
+        .get(format!("{}/api/verify-email?token={}", common::BASE_URL, token))
+        .send()
+        .await
+        .expect("Falha ao verificar");
+    assert_eq!(ok.status().as_u16(), 200);
+    let reused = client
+        .get(format!("{}/api/verify-email?token={}", common::BASE_URL, token))
+        .send()
+        .await
+        .expect("Falha ao verificar");
+    assert_eq!(reused.status().as_u16(), 400);
+
+    // 4) Verificada, a conta finaliza o pedido
+    let done = client
+        .post(format!("{}/api/checkout", common::BASE_URL))
+        .header("cookie", &cookie)
+        .header("x-csrf-token", &csrf)
+        .json(&checkout)
+        .send()
+        .await
+        .expect("Falha no checkout");
+    assert!(done.status().is_success(), "Checkout deve retornar 200, obtido {}", done.status());
+}
@@ -0,0 +1,140 @@
+use serde_json::Value;
+
+mod common;
+
+async fn estoque(client: &reqwest::Client, product_id: u64) -> i64 {
+    let products: Value = client
+        .get(format!("{}/api/products?per_page=100", common::BASE_URL))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    products["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|p| p["id"].as_u64() == Some(product_id))
+        .and_then(|p| p["stock"].as_i64())
+        .expect("produto deve existir")
+}
+
+#[tokio::test]
+async fn estoque_checkout() {
+    let _server = common::spawn_server().await;
+    let client = reqwest::Client::new();
+    let (admin, csrf) = common::login(&client, "admin@teste.com", "123456").await.expect("login admin");
+
+    // Assinatura de product.low_stock (o destino não precisa responder: basta a entrega entrar na fila)
+    let resp = client
+        .post(format!("{}/api/admin/webhooks", common::BASE_URL))
+        .header("cookie", &admin)
+        .header("x-csrf-token", &csrf)
+        .json(&serde_json::json!({ "url": "http://127.0.0.1:9/estoque", "eventos": ["product.low_stock"] }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 201);
+    let sub_id = resp.json::<Value>().await.unwrap()["id"].as_str().unwrap().to_string();
+
+    let product_id = 2;
+    let estoque_original = estoque(&client, product_id).await;
+    let ajustar = |stock: i64| {
+        client
+            .patch(format!("{}/api/admin/products/{}/stock", common::BASE_URL, product_id))
+            .header("cookie", &admin)
+            .header("x-csrf-token", &csrf)
+            .json(&serde_json::json!({ "stock": stock }))
+            .send()
+    };
+    let adicionar = |qty: u32| {
+        client
+            .post(format!("{}/api/cart", common::BASE_URL))
+            .json(&serde_json::json!({ "product_id": product_id, "qty": qty }))
+            .send()
+    };
+    let finalizar = || {
+        client
+            .post(format!("{}/api/checkout", common::BASE_URL))
+            .header("cookie", &admin)
+            .header("x-csrf-token", &csrf)
+            .json(&serde_json::json!({ "payment": { "method": "pix" }, "customer_email": "admin@teste.com" }))
+            .send()
+    };
+
+    // 1) Checkout que leva o estoque de 11 para 9 (limite padrão 10): baixa e product.low_stock na fila
+    assert_eq!(ajustar(11).await.unwrap().status().as_u16(), 200);
+    assert!(adicionar(2).await.unwrap().status().is_success());
+    let resp = finalizar().await.unwrap();
+    assert!(resp.status().is_success(), "Checkout deve retornar 200, obtido {}", resp.status());
+    let order_id = resp.json::<Value>().await.unwrap()["order_id"].as_str().unwrap().to_string();
+    assert_eq!(estoque(&client, product_id).await, 9);
+
+    let entregas: Vec<Value> = client
+        .get(format!("{}/api/admin/webhooks/{}/deliveries", common::BASE_URL, sub_id))
+        .header("cookie", &admin)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let entrega = entregas
+        .iter()
+        .find(|d| d["evento"] == "product.low_stock" && d["payload"]["dados"]["product_id"].as_u64() == Some(product_id))
+        .expect("product.low_stock deve entrar na fila");
+    assert_eq!(entrega["payload"]["dados"]["stock"].as_i64(), Some(9));
+    assert_eq!(entrega["payload"]["dados"]["stock_anterior"].as_i64(), Some(11));
+
+    // 2) Estoque baixou depois de o item entrar no carrinho: 409, nada é gravado e o carrinho fica
+    assert!(adicionar(3).await.unwrap().status().is_success());
+    assert_eq!(ajustar(2).await.unwrap().status().as_u16(), 200);
+    let resp = finalizar().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 409);
+    assert_eq!(estoque(&client, product_id).await, 2);
+    let carrinho: Value = client.get(format!("{}/api/cart", common::BASE_URL)).send().await.unwrap().json().await.unwrap();
+    let no_carrinho = carrinho["items"].as_array().unwrap().iter().any(|i| i["product_id"].as_u64() == Some(product_id) && i["qty"] == 3);
+    assert!(no_carrinho, "carrinho mantido: {}", carrinho);
+    client.delete(format!("{}/api/cart/clear", common::BASE_URL)).send().await.unwrap();
+
+    // 3) Cancelar o primeiro pedido devolve as unidades ao estoque
+    let resp = client
+        .post(format!("{}/api/pedidos/{}/cancelar", common::BASE_URL, order_id))
+        .header("cookie", &admin)
+        .header("x-csrf-token", &csrf)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    assert_eq!(estoque(&client, product_id).await, 4);
+
+    // 4) Pedido anterior à baixa no checkout (estoque_baixado = 0): cancelar não repõe nada
+    assert!(adicionar(1).await.unwrap().status().is_success());
+    let resp = finalizar().await.unwrap();
+    assert!(resp.status().is_success());
+    let legado = resp.json::<Value>().await.unwrap()["order_id"].as_str().unwrap().to_string();
+    assert_eq!(estoque(&client, product_id).await, 3);
+    let db = sqlx::SqlitePool::connect("sqlite://data/mercado.db").await.expect("banco dos testes");
+    sqlx::query("UPDATE pedidos SET estoque_baixado = 0 WHERE id = ?").bind(&legado).execute(&db).await.unwrap();
+    db.close().await;
+    let resp = client
+        .post(format!("{}/api/pedidos/{}/cancelar", common::BASE_URL, legado))
+        .header("cookie", &admin)
+        .header("x-csrf-token", &csrf)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    assert_eq!(estoque(&client, product_id).await, 3);
+
+    assert_eq!(ajustar(estoque_original).await.unwrap().status().as_u16(), 200);
+    let resp = client
+        .delete(format!("{}/api/admin/webhooks/{}", common::BASE_URL, sub_id))
+        .header("cookie", &admin)
+        .header("x-csrf-token", &csrf)
+        .send()
+        .await
+        .unwrap();
+    assert!(resp.status().is_success());
+}
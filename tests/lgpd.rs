@@ -0,0 +1,122 @@
+use serde_json::Value;
+
+mod common;
+
+#[tokio::test]
+async fn lgpd() {
+    let _server = common::spawn_server().await;
+    let client = reqwest::Client::new();
+
+    let ts = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_millis();
+    let email = format!("titular{}@teste.com", ts);
+    let senha = "Mercado#2025forte";
+    let (cookie, csrf) = common::verified_user_session(&client, "Titular, Dados", &email, senha).await;
+
+    let me: Value = client.get(format!("{}/api/me", common::BASE_URL)).header("cookie", &cookie).send().await.unwrap().json().await.unwrap();
+    let user_id = me["id"].as_i64().expect("id do usuário");
+
+    // 1) Um pedido da conta
+    let products: Value = client.get(format!("{}/api/products", common::BASE_URL)).send().await.unwrap().json().await.unwrap();
+    let product_id = products["items"][0]["id"].as_u64().expect("Produto deve ter id");
+    client
+        .post(format!("{}/api/cart", common::BASE_URL))
+        .json(&serde_json::json!({ "product_id": product_id, "qty": 1 }))
+        .send()
+        .await
+        .unwrap();
+    let resp = client
+        .post(format!("{}/api/checkout", common::BASE_URL))
+        .header("cookie", &cookie)
+        .header("x-csrf-token", &csrf)
+        .json(&serde_json::json!({ "payment": { "method": "pix" }, "customer_email": email }))
+        .send()
+        .await
+        .unwrap();
+    assert!(resp.status().is_success(), "Checkout deve retornar 200, obtido {}", resp.status());
+    let body: Value = resp.json().await.unwrap();
+    let order_id = body["order_id"].as_str().unwrap().to_string();
+
+    // 2) Exportação: JSON com perfil, sessões, pedidos, itens e as mesmas tabelas em CSV
+    let resp = client.get(format!("{}/api/me/export", common::BASE_URL)).header("cookie", &cookie).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let disposition = resp.headers().get("content-disposition").and_then(|v| v.to_str().ok()).unwrap_or("").to_string();
+    assert!(disposition.contains("attachment"));
+    let pacote: Value = resp.json().await.unwrap();
+    assert_eq!(pacote["perfil"]["email"].as_str(), Some(email.as_str()));
+    assert!(!pacote["sessoes"].as_array().unwrap().is_empty());
+    assert!(pacote["enderecos"].is_array());
+    assert_eq!(pacote["pedidos"][0]["id"].as_str(), Some(order_id.as_str()));
+    assert_eq!(pacote["itens_pedido"][0]["pedido_id"].as_str(), Some(order_id.as_str()));
+    assert!(pacote["csv"]["pedidos.csv"].as_str().unwrap().contains(&order_id));
+    assert!(pacote["csv"]["perfil.csv"].as_str().unwrap().contains("\"Titular, Dados\""), "campos com vírgula vão entre aspas");
+
+    // 3) Eliminação só por administrador
+    let resp = client
+        .post(format!("{}/api/admin/users/{}/erase", common::BASE_URL, user_id))
+        .header("cookie", &cookie)
+        .header("x-csrf-token", &csrf)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    let (admin, admin_csrf) = common::login(&client, "admin@teste.com", "123456").await.expect("login admin");
+    // Falhas de login deixam um bloqueio da conta, com o e-mail como chave
+    for _ in 0..5 {
+        assert!(common::login(&client, &email, "errada-errada").await.is_none());
+    }
+    let bloqueios_com_email = || async {
+        let bloqueios: Vec<Value> = client
+            .get(format!("{}/api/admin/login-lockouts", common::BASE_URL))
+            .header("cookie", &admin)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        bloqueios.iter().filter(|b| b["chave"].as_str() == Some(email.as_str())).count()
+    };
+    assert!(bloqueios_com_email().await > 0);
+    let erase = || {
+        client
+            .post(format!("{}/api/admin/users/{}/erase", common::BASE_URL, user_id))
+            .header("cookie", &admin)
+            .header("x-csrf-token", &admin_csrf)
+            .send()
+    };
+    let resp = erase().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["pedidos_mantidos"].as_i64(), Some(1));
+    assert_eq!(erase().await.unwrap().status().as_u16(), 404);
+    assert_eq!(bloqueios_com_email().await, 0, "nenhum bloqueio guarda o e-mail eliminado");
+
+    // 4) Conta inacessível; o pedido e seus valores continuam
+    assert!(common::login(&client, &email, senha).await.is_none());
+    let pedidos: Vec<Value> = client
+        .get(format!("{}/api/pedidos", common::BASE_URL))
+        .header("cookie", &admin)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let pedido = pedidos.iter().find(|p| p["id"].as_str() == Some(order_id.as_str())).expect("pedido mantido");
+    assert!(pedido["total_cents"].as_i64().unwrap_or(0) > 0);
+
+    // 5) Auditoria registra exportação e eliminação
+    let registros: Vec<Value> = client
+        .get(format!("{}/api/admin/lgpd-requests", common::BASE_URL))
+        .header("cookie", &admin)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let tipos: Vec<&str> = registros.iter().filter(|r| r["user_id"].as_i64() == Some(user_id)).filter_map(|r| r["tipo"].as_str()).collect();
+    assert!(tipos.contains(&"exportacao"));
+    assert!(tipos.contains(&"eliminacao"));
+}
@@ -0,0 +1,89 @@
+use serde_json::Value;
+
+mod common;
+
+async fn login(client: &reqwest::Client, email: &str, senha: &str) -> reqwest::Response {
+    client
+        .post(format!("{}/api/login", common::BASE_URL))
+        .json(&serde_json::json!({ "email": email, "senha": senha }))
+        .send()
+        .await
+        .expect("Falha ao chamar /api/login")
+}
+
+#[tokio::test]
+async fn login_lockout() {
+    let _server = common::spawn_server().await;
+    let client = reqwest::Client::new();
+
+    let ts = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_millis();
+    let email = format!("bloqueio{}@teste.com", ts);
+    let senha = "Mercado#2025forte";
+    let reg = client
+        .post(format!("{}/api/register", common::BASE_URL))
+        .json(&serde_json::json!({ "nome": "Bloqueio", "email": email, "senha": senha }))
+        .send()
+        .await
+        .expect("Falha ao registrar");
+    assert!(reg.status().is_success());
+
+    // 1) Quatro falhas: 401 comum
+    for _ in 0..4 {
+        let r = login(&client, &email, "errada-errada").await;
+        assert_eq!(r.status().as_u16(), 401);
+    }
+
+    // 2) Quinta falha atinge o limite (padrão 5) e já responde 429 com Retry-After
+    let r = login(&client, &email, "errada-errada").await;
+    assert_eq!(r.status().as_u16(), 429);
+    let retry: i64 = r.headers().get("retry-after").and_then(|v| v.to_str().ok()).and_then(|v| v.parse().ok()).expect("Retry-After ausente");
+    assert!(retry > 0);
+
+    // 3) Durante o bloqueio nem a senha correta entra
+    let r = login(&client, &email, senha).await;
+    assert_eq!(r.status().as_u16(), 429);
+    let body: Value = r.json().await.expect("Falha ao parsear JSON");
+    assert_eq!(body["autenticado"], Value::Bool(false));
+
+    // 4) Outras contas seguem funcionando e o bloqueio fica registrado para auditoria
+    let admin = login(&client, "admin@teste.com", "123456").await;
+    assert!(admin.status().is_success(), "Admin deve continuar entrando, veio {}", admin.status());
+    let cookie = admin.headers().get("set-cookie").and_then(|v| v.to_str().ok()).expect("Set-Cookie ausente");
+    let sid = cookie.split(';').next().unwrap().to_string();
+    let lockouts: Vec<Value> = client
+        .get(format!("{}/api/admin/login-lockouts", common::BASE_URL))
+        .header("cookie", sid)
+        .send()
+        .await
+        .expect("Falha ao listar bloqueios")
+        .json()
+        .await
+        .expect("Falha ao parsear bloqueios");
+    assert!(lockouts.iter().any(|l| l["scope"] == "conta" && l["chave"].as_str() == Some(email.as_str())));
+
+    // 5) Um login bem-sucedido no meio não zera a contagem do IP (padrão 20). Precisa de um IP de
+    // origem próprio: 127.0.0.1 é isento do bloqueio por IP no servidor de teste, e só o Linux tem
+    // os demais endereços 127.x.y.z no loopback
+    #[cfg(target_os = "linux")]
+    {
+        let outro_ip = common::own_ip_client();
+        let email_ip = format!("bloqueioip{}@teste.com", ts);
+        let reg = outro_ip
+            .post(format!("{}/api/register", common::BASE_URL))
+            .json(&serde_json::json!({ "nome": "Bloqueio IP", "email": email_ip, "senha": senha }))
+            .send()
+            .await
+            .expect("Falha ao registrar");
+        assert!(reg.status().is_success());
+        for i in 0..19 {
+            let r = login(&outro_ip, &format!("alvo{}-{}@teste.com", i, ts), "errada-errada").await;
+            assert_eq!(r.status().as_u16(), 401);
+        }
+        let r = login(&outro_ip, &email_ip, senha).await;
+        assert!(r.status().is_success(), "Conta válida deve entrar, veio {}", r.status());
+        let r = login(&outro_ip, &format!("alvo19-{}@teste.com", ts), "errada-errada").await;
+        assert_eq!(r.status().as_u16(), 429, "20ª falha do IP deve bloquear mesmo após um sucesso");
+        let r = login(&outro_ip, &email_ip, senha).await;
+        assert_eq!(r.status().as_u16(), 429);
+    }
+}
@@ -0,0 +1,143 @@
+use mercado_backend::{digito_chave, montar_chave};
+use serde_json::Value;
+
+mod common;
+
+#[test]
+fn chave_de_acesso() {
+    // Exemplo do Manual de Orientação do Contribuinte da NF-e (soma das ponderações 644, resto 6, DV 5)
+    let exemplo = "52060433009911002506550120000007800267301615";
+    assert_eq!(digito_chave(&exemplo[..43]), 5);
+    // Resto 0 ou 1 dá DV 0
+    assert_eq!(digito_chave("0000000000000000000000000000000000000000000"), 0);
+
+    // cUF 35, AAMM 2410, CNPJ, modelo 65, série 1, número 42, tpEmis 1, cNF 12345678, DV 3
+    assert_eq!(
+        montar_chave("35", "2410", "12345678000195", 1, 42, 12345678),
+        "35241012345678000195650010000000421123456783"
+    );
+}
+
+#[tokio::test]
+async fn nfce() {
+    let _server = common::spawn_server().await;
+    let client = reqwest::Client::new();
+    let (admin, csrf) = common::login(&client, "admin@teste.com", "123456").await.expect("login admin");
+
+    let products: Value = client.get(format!("{}/api/products", common::BASE_URL)).send().await.unwrap().json().await.unwrap();
+    let product_id = products["items"][0]["id"].as_u64().expect("Produto deve ter id");
+    let fiscal = |body: Value| {
+        client
+            .patch(format!("{}/api/admin/products/{}/fiscal", common::BASE_URL, product_id))
+            .header("cookie", &admin)
+            .header("x-csrf-token", &csrf)
+            .json(&body)
+            .send()
+    };
+
+    // 1) Dados fiscais do produto: NCM inválido recusado, válido gravado
+    let resp = fiscal(serde_json::json!({ "ncm": "1905", "cfop": "5102", "icms_cst": "102" })).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 422);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["field"].as_str(), Some("ncm"));
+    let resp = fiscal(serde_json::json!({ "ncm": "1905.90.90", "cfop": "5102", "icms_cst": "102" })).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    // 2) Pedido com CPF do comprador
+    client
+        .post(format!("{}/api/cart", common::BASE_URL))
+        .json(&serde_json::json!({ "product_id": product_id, "qty": 2 }))
+        .send()
+        .await
+        .unwrap();
+    let resp = client
+        .post(format!("{}/api/checkout", common::BASE_URL))
+        .header("cookie", &admin)
+        .header("x-csrf-token", &csrf)
+        .json(&serde_json::json!({ "payment": { "method": "pix" }, "customer_email": "admin@teste.com", "documento": "529.982.247-25" }))
+        .send()
+        .await
+        .unwrap();
+    assert!(resp.status().is_success(), "Checkout deve retornar 200, obtido {}", resp.status());
+    let body: Value = resp.json().await.unwrap();
+    let order_id = body["order_id"].as_str().unwrap().to_string();
+
+    // 3) Emissão: chave de 44 dígitos com DV correto, modelo 65
+    let emitir = || {
+        client
+            .post(format!("{}/api/admin/pedidos/{}/nfce", common::BASE_URL, order_id))
+            .header("cookie", &admin)
+            .header("x-csrf-token", &csrf)
+            .send()
+    };
+    let resp = emitir().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 201);
+    let nota: Value = resp.json().await.unwrap();
+    let chave = nota["chave"].as_str().unwrap().to_string();
+    assert_eq!(chave.len(), 44);
+    assert!(chave.bytes().all(|b| b.is_ascii_digit()));
+    assert_eq!(&chave[20..22], "65");
+    assert_eq!(chave[43..].parse::<u32>().unwrap(), digito_chave(&chave[..43]));
+    assert_eq!(nota["status"].as_str(), Some("simulada"));
+
+    // 4) XML armazenado com NCM, CFOP, ICMS e destinatário
+    let resp = client
+        .get(format!("{}/api/admin/pedidos/{}/nfce", common::BASE_URL, order_id))
+        .header("cookie", &admin)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let xml = resp.text().await.unwrap();
+    assert!(xml.contains(&format!("Id=\"NFe{}\"", chave)));
+    assert!(xml.contains("<NCM>19059090</NCM>"));
+    assert!(xml.contains("<CFOP>5102</CFOP>"));
+    assert!(xml.contains("<CSOSN>102</CSOSN>"));
+    assert!(xml.contains("<CPF>52998224725</CPF>"));
+    assert!(xml.contains("<qCom>2.0000</qCom>"));
+    // Pedido sem janela de entrega: venda presencial
+    assert!(xml.contains("<indPres>1</indPres>"));
+
+    // 5) Reemissão devolve a mesma nota
+    let resp = emitir().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let de_novo: Value = resp.json().await.unwrap();
+    assert_eq!(de_novo["chave"].as_str(), Some(chave.as_str()));
+
+    // 6) Nota que falhou na assinatura ou no envio é reenviada com a mesma chave e número
+    let db = sqlx::SqlitePool::connect("sqlite://data/mercado.db").await.expect("banco dos testes");
+    for falha in ["erro_assinatura", "erro_transmissao"] {
+        sqlx::query("UPDATE nfce_documentos SET status = ?, protocolo = NULL WHERE pedido_id = ?")
+            .bind(falha)
+            .bind(&order_id)
+            .execute(&db)
+            .await
+            .unwrap();
+        let resp = emitir().await.unwrap();
+        assert_eq!(resp.status().as_u16(), 200);
+        let reenviada: Value = resp.json().await.unwrap();
+        assert_eq!(reenviada["status"].as_str(), Some("simulada"), "reenvio após {}", falha);
+        assert_eq!(reenviada["chave"].as_str(), Some(chave.as_str()));
+        assert_eq!(reenviada["numero"], nota["numero"]);
+        assert!(reenviada["protocolo"].as_str().is_some());
+    }
+
+    // 7) Nota presa em "gerada": recente está em emissão (409); depois do prazo é reenviada
+    sqlx::query("UPDATE nfce_documentos SET status = 'gerada', protocolo = NULL, updated_at = CURRENT_TIMESTAMP WHERE pedido_id = ?")
+        .bind(&order_id)
+        .execute(&db)
+        .await
+        .unwrap();
+    assert_eq!(emitir().await.unwrap().status().as_u16(), 409);
+    sqlx::query("UPDATE nfce_documentos SET updated_at = datetime('now', '-10 minutes') WHERE pedido_id = ?")
+        .bind(&order_id)
+        .execute(&db)
+        .await
+        .unwrap();
+    let resp = emitir().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let reenviada: Value = resp.json().await.unwrap();
+    assert_eq!(reenviada["status"].as_str(), Some("simulada"));
+    assert_eq!(reenviada["chave"].as_str(), Some(chave.as_str()));
+    db.close().await;
+}
@@ -0,0 +1,110 @@
+use serde_json::Value;
+
+mod common;
+
+// Aguarda (até ~15s) um e-mail para o destinatário cujo assunto contenha o trecho
+async fn esperar_email(email: &str, assunto: &str) -> Option<String> {
+    for _ in 0..50 {
+        let mut files: Vec<_> = std::fs::read_dir("data/mail").ok()?.filter_map(|e| e.ok()).map(|e| e.path()).collect();
+        files.sort();
+        let achado = files.iter().rev().find_map(|p| {
+            let content = std::fs::read_to_string(p).ok()?;
+            let subject = content.lines().find(|l| l.starts_with("Subject: "))?;
+            (content.contains(&format!("To: {}\r\n", email)) && subject.contains(assunto)).then_some(content)
+        });
+        if achado.is_some() {
+            return achado;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+    }
+    None
+}
+
+#[tokio::test]
+async fn order_notifications() {
+    let _server = common::spawn_server().await;
+    let client = reqwest::Client::new();
+
+    let ts = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_millis();
+    let email = format!("avisos{}@teste.com", ts);
+    let senha = "Mercado#2025forte";
+    let (cookie, csrf) = common::verified_user_session(&client, "Cliente Avisos", &email, senha).await;
+
+    let products: Value = client.get(format!("{}/api/products", common::BASE_URL)).send().await.unwrap().json().await.unwrap();
+    let product_id = products["items"][0]["id"].as_u64().expect("Produto deve ter id");
+    let comprar = || async {
+        client
+            .post(format!("{}/api/cart", common::BASE_URL))
+            .json(&serde_json::json!({ "product_id": product_id, "qty": 1 }))
+            .send()
+            .await
+            .unwrap();
+        let resp = client
+            .post(format!("{}/api/checkout", common::BASE_URL))
+            .header("cookie", &cookie)
+            .header("x-csrf-token", &csrf)
+            .json(&serde_json::json!({ "payment": { "method": "pix" }, "customer_email": email }))
+            .send()
+            .await
+            .unwrap();
+        assert!(resp.status().is_success(), "Checkout deve retornar 200, obtido {}", resp.status());
+        let body: Value = resp.json().await.unwrap();
+        body["order_id"].as_str().unwrap().to_string()
+    };
+
+    // 1) Checkout: confirmação e pagamento chegam pelo worker
+    let order_id = comprar().await;
+    let curto: String = order_id.chars().take(8).collect();
+    let confirmacao = esperar_email(&email, &format!("Pedido {} confirmado", curto)).await.expect("e-mail de confirmação");
+    assert!(confirmacao.contains(&order_id));
+    assert!(confirmacao.contains("Cliente Avisos"));
+    let pagamento = esperar_email(&email, &format!("Pagamento do pedido {} recebido", curto)).await.expect("e-mail de pagamento");
+    assert!(pagamento.contains("PIX"));
+
+    // 2) Despacho pelo administrador: e-mail de entrega; pedido despachado não é cancelado
+    let (admin, admin_csrf) = common::login(&client, "admin@teste.com", "123456").await.expect("login admin");
+    let despachar = || {
+        client
+            .post(format!("{}/api/admin/pedidos/{}/enviar", common::BASE_URL, order_id))
+            .header("cookie", &admin)
+            .header("x-csrf-token", &admin_csrf)
+            .send()
+    };
+    assert_eq!(despachar().await.unwrap().status().as_u16(), 200);
+    assert_eq!(despachar().await.unwrap().status().as_u16(), 409);
+    esperar_email(&email, &format!("Pedido {} saiu para entrega", curto)).await.expect("e-mail de entrega");
+    let resp = client
+        .post(format!("{}/api/pedidos/{}/cancelar", common::BASE_URL, order_id))
+        .header("cookie", &cookie)
+        .header("x-csrf-token", &csrf)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 409);
+
+    // 3) Cancelamento de outro pedido
+    let outro = comprar().await;
+    let resp = client
+        .post(format!("{}/api/pedidos/{}/cancelar", common::BASE_URL, outro))
+        .header("cookie", &cookie)
+        .header("x-csrf-token", &csrf)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let curto_outro: String = outro.chars().take(8).collect();
+    esperar_email(&email, &format!("Pedido {} cancelado", curto_outro)).await.expect("e-mail de cancelamento");
+
+    // 4) Fila registra as três mensagens do primeiro pedido como enviadas, sem duplicar
+    let fila: Vec<Value> = client
+        .get(format!("{}/api/admin/email-outbox?pedido_id={}", common::BASE_URL, order_id))
+        .header("cookie", &admin)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(fila.len(), 3);
+    assert!(fila.iter().all(|m| m["status"].as_str() == Some("enviado")));
+}
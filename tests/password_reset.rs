@@ -0,0 +1,102 @@
+use serde_json::Value;
+
+mod common;
+
+async fn post(client: &reqwest::Client, path: &str, body: Value) -> reqwest::Response {
+    client
+        .post(format!("{}{}", common::BASE_URL, path))
+        .json(&body)
+        .send()
+        .await
+        .expect("Falha na requisição")
+}
+
+#[tokio::test]
+async fn password_reset() {
+    let _server = common::spawn_server().await;
+    // Pedidos de redefinição são limitados por IP
+    let client = common::own_ip_client();
+
+    let ts = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_millis();
+    let email = format!("reset{}@teste.com", ts);
+    let antiga = "Mercado#2025forte";
+    let nova = "Nova#Senha2025segura";
+    let reg = post(&client, "/api/register", serde_json::json!({ "nome": "Reset", "email": email, "senha": antiga })).await;
+    assert!(reg.status().is_success());
+
+    // Sessão aberta antes da redefinição
+    let login = post(&client, "/api/login", serde_json::json!({ "email": email, "senha": antiga })).await;
+    let set_cookie = login.headers().get("set-cookie").and_then(|v| v.to_str().ok()).expect("Set-Cookie ausente");
+    let sid = common::extract_cookie(set_cookie, "session_id").expect("session_id não encontrado");
+    let csrf = login.json::<Value>().await.unwrap()["csrf_token"].as_str().expect("csrf_token ausente").to_string();
+
+    // Token de API criado antes da redefinição
+    let resp = client
+        .post(format!("{}/api/me/tokens", common::BASE_URL))
+        .header("cookie", format!("session_id={}", sid))
+        .header("x-csrf-token", &csrf)
+        .json(&serde_json::json!({ "nome": "script", "scopes": ["orders:read"] }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 201);
+    let api_token = resp.json::<Value>().await.unwrap()["token"].as_str().unwrap().to_string();
+
+    // Link de verificação do cadastro, o último e-mail da conta até aqui
+    let verificacao = common::token_from_mail(&email);
+
+    // 1) E-mail inexistente recebe a mesma resposta
+    let unknown = post(&client, "/api/password/forgot", serde_json::json!({ "email": format!("ninguem{}@teste.com", ts) })).await;
+    assert_eq!(unknown.status().as_u16(), 200);
+    let unknown_body: Value = unknown.json().await.unwrap();
+    let forgot = post(&client, "/api/password/forgot", serde_json::json!({ "email": email.to_uppercase() })).await;
+    assert_eq!(forgot.status().as_u16(), 200);
+    let forgot_body: Value = forgot.json().await.unwrap();
+    assert_eq!(unknown_body, forgot_body);
+
+    // 2) Novo pedido para o mesmo e-mail logo em seguida é limitado
+    let again = post(&client, "/api/password/forgot", serde_json::json!({ "email": email })).await;
+    assert_eq!(again.status().as_u16(), 429);
+    assert!(again.headers().get("retry-after").is_some());
+
+    // 3) Token chega por e-mail (enviado fora da requisição)
+    let mut token = None;
+    for _ in 0..50 {
+        token = common::token_from_mail(&email).filter(|t| Some(t) != verificacao.as_ref());
+        if token.is_some() {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+    let token = token.expect("E-mail de redefinição não encontrado");
+    assert!(token.len() >= 32);
+
+    // 4) Token inválido e senha fraca são recusados
+    let bad = post(&client, "/api/password/reset", serde_json::json!({ "token": "invalido", "senha": nova })).await;
+    assert_eq!(bad.status().as_u16(), 400);
+    let weak = post(&client, "/api/password/reset", serde_json::json!({ "token": token, "senha": "123" })).await;
+    assert_eq!(weak.status().as_u16(), 422);
+
+    // 5) Redefinição válida encerra as sessões existentes e revoga os tokens de API
+    let ok = post(&client, "/api/password/reset", serde_json::json!({ "token": token, "senha": nova })).await;
+    assert_eq!(ok.status().as_u16(), 200);
+    let me = client
+        .get(format!("{}/api/auth/me", common::BASE_URL))
+        .header("cookie", format!("session_id={}", sid))
+        .send()
+        .await
+        .expect("Falha em /api/auth/me");
+    assert_eq!(me.status().as_u16(), 401);
+    let pedidos = client.get(format!("{}/api/pedidos", common::BASE_URL)).bearer_auth(&api_token).send().await.unwrap();
+    assert_eq!(pedidos.status().as_u16(), 401);
+
+    // 6) Token não pode ser reutilizado
+    let again = post(&client, "/api/password/reset", serde_json::json!({ "token": token, "senha": "Outra#Senha2025" })).await;
+    assert_eq!(again.status().as_u16(), 400);
+
+    // 7) Login só com a nova senha
+    let old_login = post(&client, "/api/login", serde_json::json!({ "email": email, "senha": antiga })).await;
+    assert_eq!(old_login.status().as_u16(), 401);
+    let new_login = post(&client, "/api/login", serde_json::json!({ "email": email, "senha": nova })).await;
+    assert!(new_login.status().is_success());
+}
@@ -0,0 +1,93 @@
+use serde_json::Value;
+
+mod common;
+
+async fn status_do_pedido(client: &reqwest::Client, cookie: &str, order_id: &str) -> Option<String> {
+    let pedidos: Vec<Value> = client
+        .get(format!("{}/api/pedidos", common::BASE_URL))
+        .header("cookie", cookie)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    pedidos.iter().find(|p| p["id"] == order_id).and_then(|p| p["status"].as_str()).map(str::to_string)
+}
+
+#[tokio::test]
+async fn pedido_acesso() {
+    let _server = common::spawn_server().await;
+    let client = reqwest::Client::new();
+
+    let ts = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_millis();
+    let senha = "Mercado#2025forte";
+    let email_a = format!("pedido-a{}@teste.com", ts);
+    let (cookie_a, csrf_a) = common::verified_user_session(&client, "Cliente Pedido", &email_a, senha).await;
+    let (cookie_b, csrf_b) = common::verified_user_session(&client, "Cliente Pedido", &format!("pedido-b{}@teste.com", ts), senha).await;
+
+    // 1) Cliente A faz um pedido
+    client
+        .post(format!("{}/api/cart", common::BASE_URL))
+        .json(&serde_json::json!({ "product_id": 1, "qty": 1 }))
+        .send()
+        .await
+        .unwrap();
+    let resp = client
+        .post(format!("{}/api/checkout", common::BASE_URL))
+        .header("cookie", &cookie_a)
+        .header("x-csrf-token", &csrf_a)
+        .json(&serde_json::json!({ "payment": { "method": "pix" }, "customer_email": email_a }))
+        .send()
+        .await
+        .unwrap();
+    assert!(resp.status().is_success(), "Checkout deve retornar 200, obtido {}", resp.status());
+    let body: Value = resp.json().await.unwrap();
+    let order_id = body["order_id"].as_str().unwrap().to_string();
+
+    // 2) A listagem de B não inclui o pedido de A; o admin vê todos. Relatório é só do admin
+    assert_eq!(status_do_pedido(&client, &cookie_b, &order_id).await, None);
+    let (admin, _) = common::login(&client, "admin@teste.com", "123456").await.expect("login admin");
+    assert_eq!(status_do_pedido(&client, &admin, &order_id).await.as_deref(), Some("paid"));
+    let relatorio = |cookie: &str| client.get(format!("{}/api/reports/daily", common::BASE_URL)).header("cookie", cookie.to_string()).send();
+    assert_eq!(relatorio(&cookie_b).await.unwrap().status().as_u16(), 403);
+    assert_eq!(relatorio(&admin).await.unwrap().status().as_u16(), 200);
+
+    // 3) Cliente B não vê os itens nem cancela o pedido de A: 404, pedido continua pago
+    let resp = client
+        .get(format!("{}/api/pedidos/{}/itens", common::BASE_URL, order_id))
+        .header("cookie", &cookie_b)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 404);
+    let resp = client
+        .post(format!("{}/api/pedidos/{}/cancelar", common::BASE_URL, order_id))
+        .header("cookie", &cookie_b)
+        .header("x-csrf-token", &csrf_b)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 404);
+    assert_eq!(status_do_pedido(&client, &cookie_a, &order_id).await.as_deref(), Some("paid"));
+
+    // 4) O dono vê os itens e cancela
+    let resp = client
+        .get(format!("{}/api/pedidos/{}/itens", common::BASE_URL, order_id))
+        .header("cookie", &cookie_a)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let itens: Vec<Value> = resp.json().await.unwrap();
+    assert_eq!(itens.len(), 1);
+    let resp = client
+        .post(format!("{}/api/pedidos/{}/cancelar", common::BASE_URL, order_id))
+        .header("cookie", &cookie_a)
+        .header("x-csrf-token", &csrf_a)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    assert_eq!(status_do_pedido(&client, &cookie_a, &order_id).await.as_deref(), Some("cancelled"));
+}
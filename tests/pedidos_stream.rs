@@ -0,0 +1,122 @@
+use serde_json::Value;
+use std::time::Duration;
+
+mod common;
+
+// Leitor mínimo de SSE: devolve (id, evento, data) conforme os blocos chegam
+struct Leitor {
+    resp: reqwest::Response,
+    buffer: String,
+}
+
+impl Leitor {
+    async fn proximo(&mut self) -> Option<(String, String, Value)> {
+        loop {
+            if let Some(fim) = self.buffer.find("\n\n") {
+                let bloco: String = self.buffer.drain(..fim + 2).collect();
+                let (mut id, mut evento, mut data) = (String::new(), String::new(), String::new());
+                for linha in bloco.lines() {
+                    if let Some(v) = linha.strip_prefix("id:") { id = v.trim().to_string(); }
+                    if let Some(v) = linha.strip_prefix("event:") { evento = v.trim().to_string(); }
+                    if let Some(v) = linha.strip_prefix("data:") { data.push_str(v.trim()); }
+                }
+                if data.is_empty() {
+                    continue; // keep-alive
+                }
+                return Some((id, evento, serde_json::from_str(&data).ok()?));
+            }
+            let chunk = tokio::time::timeout(Duration::from_secs(10), self.resp.chunk()).await.ok()?.ok()??;
+            self.buffer.push_str(&String::from_utf8_lossy(&chunk));
+        }
+    }
+
+    // Pula pedidos de outros testes até achar o esperado
+    async fn ate(&mut self, order_id: &str) -> Option<(String, String, Value)> {
+        loop {
+            let ev = self.proximo().await?;
+            if ev.2["order_id"].as_str() == Some(order_id) {
+                return Some(ev);
+            }
+        }
+    }
+}
+
+async fn abrir(client: &reqwest::Client, cookie: &str, last_event_id: Option<&str>) -> Leitor {
+    let mut req = client
+        .get(format!("{}/api/admin/pedidos/stream", common::BASE_URL))
+        .header("cookie", cookie);
+    if let Some(id) = last_event_id {
+        req = req.header("last-event-id", id);
+    }
+    let resp = req.send().await.expect("Falha ao abrir stream");
+    assert_eq!(resp.status().as_u16(), 200);
+    let tipo = resp.headers().get("content-type").and_then(|v| v.to_str().ok()).unwrap_or("").to_string();
+    assert!(tipo.starts_with("text/event-stream"), "content-type inesperado: {}", tipo);
+    Leitor { resp, buffer: String::new() }
+}
+
+async fn comprar(client: &reqwest::Client, cookie: &str, csrf: &str, product_id: u64) -> String {
+    client
+        .post(format!("{}/api/cart", common::BASE_URL))
+        .json(&serde_json::json!({ "product_id": product_id, "qty": 2 }))
+        .send()
+        .await
+        .unwrap();
+    let resp = client
+        .post(format!("{}/api/checkout", common::BASE_URL))
+        .header("cookie", cookie)
+        .header("x-csrf-token", csrf)
+        .json(&serde_json::json!({ "payment": { "method": "pix" }, "customer_email": "admin@teste.com" }))
+        .send()
+        .await
+        .unwrap();
+    assert!(resp.status().is_success(), "Checkout deve retornar 200, obtido {}", resp.status());
+    let body: Value = resp.json().await.unwrap();
+    body["order_id"].as_str().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn stream_de_pedidos() {
+    let _server = common::spawn_server().await;
+    let client = reqwest::Client::new();
+
+    // Sem sessão: 401
+    let resp = client.get(format!("{}/api/admin/pedidos/stream", common::BASE_URL)).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 401);
+
+    let (admin, csrf) = common::login(&client, "admin@teste.com", "123456").await.expect("login admin");
+    let produtos: Value = client
+        .get(format!("{}/api/products", common::BASE_URL))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let product_id = produtos["items"][0]["id"].as_u64().expect("Produto deve ter id");
+
+    // Stream aberto antes dos pedidos recebe os dois, com itens
+    let mut leitor = abrir(&client, &admin, None).await;
+    let primeiro = comprar(&client, &admin, &csrf, product_id).await;
+    let segundo = comprar(&client, &admin, &csrf, product_id).await;
+
+    let (id1, evento, dados) = leitor.ate(&primeiro).await.expect("evento do primeiro pedido");
+    assert_eq!(evento, "pedido");
+    assert_eq!(dados["status"], "paid");
+    let itens = dados["items"].as_array().cloned().unwrap_or_default();
+    assert!(itens.iter().any(|i| i["product_id"].as_u64() == Some(product_id) && i["qty"] == 2), "itens: {:?}", itens);
+    let (id2, _, _) = leitor.ate(&segundo).await.expect("evento do segundo pedido");
+    // id do evento é a coluna seq do pedido, consecutiva entre checkouts
+    assert_eq!(id2.parse::<i64>().unwrap(), id1.parse::<i64>().unwrap() + 1);
+    let db = sqlx::SqlitePool::connect("sqlite://data/mercado.db").await.expect("banco dos testes");
+    let seq: i64 = sqlx::query_scalar("SELECT seq FROM pedidos WHERE id = ?").bind(&primeiro).fetch_one(&db).await.unwrap();
+    assert_eq!(seq.to_string(), id1);
+    db.close().await;
+    drop(leitor);
+
+    // Retomada com Last-Event-ID: o segundo pedido é reenviado com o mesmo id
+    let mut retomado = abrir(&client, &admin, Some(&id1)).await;
+    let (id, _, dados) = retomado.ate(&segundo).await.expect("segundo pedido após retomada");
+    assert_eq!(id, id2);
+    assert_eq!(dados["order_id"].as_str(), Some(segundo.as_str()));
+}
@@ -0,0 +1,61 @@
+use serde_json::Value;
+
+mod common;
+
+async fn get_json(client: &reqwest::Client, query: &str) -> Value {
+    let resp = client
+        .get(format!("{}/api/products{}", common::BASE_URL, query))
+        .send()
+        .await
+        .expect("Falha ao obter /api/products");
+    assert!(resp.status().is_success(), "GET /api/products{} deve retornar 200, obtido {}", query, resp.status());
+    resp.json().await.expect("Falha ao parsear produtos")
+}
+
+fn facet_count(body: &Value, facet: &str, value: &str) -> i64 {
+    body["facets"][facet]
+        .as_array()
+        .and_then(|vs| vs.iter().find(|v| v["value"].as_str() == Some(value)))
+        .and_then(|v| v["count"].as_i64())
+        .unwrap_or(0)
+}
+
+#[tokio::test]
+async fn product_filters() {
+    let _server = common::spawn_server().await;
+    let client = reqwest::Client::new();
+
+    // 1) Sem filtros: todos os produtos com categoria, marca e unidade
+    let all = get_json(&client, "").await;
+    let items = all["items"].as_array().expect("items deve ser array");
+    assert!(!items.is_empty());
+    assert_eq!(all["total"].as_u64(), Some(items.len() as u64));
+    for p in items {
+        assert!(p["category"].as_str().is_some(), "Produto deve ter categoria");
+        assert!(p["brand"].as_str().is_some(), "Produto deve ter marca");
+        assert!(p["unit"].as_str().is_some(), "Produto deve ter unidade");
+    }
+    let bebidas = facet_count(&all, "category", "Bebidas");
+    assert!(bebidas > 0, "Faceta de categoria deve contar Bebidas");
+
+    // 2) Filtro por categoria retorna só a categoria, mas a faceta continua listando as demais
+    let filtered = get_json(&client, "?category=Bebidas").await;
+    let items = filtered["items"].as_array().expect("items deve ser array");
+    assert_eq!(items.len() as i64, bebidas);
+    assert!(items.iter().all(|p| p["category"] == "Bebidas"));
+    assert!(facet_count(&filtered, "category", "Mercearia") > 0, "Faceta ignora o próprio filtro");
+
+    // 3) Faixa de preço (centavos) e estoque
+    let cheap = get_json(&client, "?min_price=300&max_price=600&in_stock=true").await;
+    for p in cheap["items"].as_array().expect("items deve ser array") {
+        let price = p["price_cents"].as_u64().unwrap_or(0);
+        assert!((300..=600).contains(&price), "Preço fora da faixa: {}", price);
+        assert!(p["stock"].as_u64().unwrap_or(0) > 0);
+    }
+
+    // 4) Marca + categoria combinadas
+    let brand = get_json(&client, "?brand=bom%20gr%C3%A3o&category=Mercearia").await;
+    let items = brand["items"].as_array().expect("items deve ser array");
+    assert!(!items.is_empty());
+    assert!(items.iter().all(|p| p["brand"] == "Bom Grão"));
+}
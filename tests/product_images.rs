@@ -0,0 +1,108 @@
+use serde_json::Value;
+
+mod common;
+
+// Retorna (session_id, csrf_token)
+async fn login(client: &reqwest::Client, email: &str, senha: &str) -> (String, String) {
+    let resp = client
+        .post(format!("{}/api/login", common::BASE_URL))
+        .json(&serde_json::json!({ "email": email, "senha": senha }))
+        .send()
+        .await
+        .expect("Falha ao enviar login");
+    assert!(resp.status().is_success(), "Login deve retornar 200, veio {}", resp.status());
+    let set_cookie = resp.headers().get("set-cookie").and_then(|v| v.to_str().ok()).expect("Set-Cookie ausente");
+    let sid = common::extract_cookie(set_cookie, "session_id").expect("session_id não encontrado");
+    let body: Value = resp.json().await.expect("Falha ao parsear login");
+    (sid, body["csrf_token"].as_str().expect("csrf_token ausente").to_string())
+}
+
+fn image_form(bytes: Vec<u8>, mime: &str) -> reqwest::multipart::Form {
+    let part = reqwest::multipart::Part::bytes(bytes)
+        .file_name("foto de teste.png")
+        .mime_str(mime)
+        .unwrap();
+    reqwest::multipart::Form::new().part("image", part)
+}
+
+// Largura e altura lidas do cabeçalho IHDR de um PNG
+fn png_size(bytes: &[u8]) -> (u32, u32) {
+    let w = u32::from_be_bytes(bytes[16..20].try_into().unwrap());
+    let h = u32::from_be_bytes(bytes[20..24].try_into().unwrap());
+    (w, h)
+}
+
+#[tokio::test]
+async fn product_images() {
+    let _server = common::spawn_server().await;
+    let client = reqwest::Client::new();
+    let png = std::fs::read("images/arroz.png").expect("images/arroz.png deve existir");
+    let url = format!("{}/api/admin/products/1/image", common::BASE_URL);
+
+    // 1) Sem login: 401 (corpo pequeno: o servidor responde sem ler o upload)
+    let anon = client.post(&url).multipart(image_form(b"x".to_vec(), "image/png")).send().await.expect("Falha no upload");
+    assert_eq!(anon.status().as_u16(), 401);
+
+    // 2) Usuário comum: 403
+    let ts = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_millis();
+    let email = format!("cliente{}@teste.com", ts);
+    client
+        .post(format!("{}/api/register", common::BASE_URL))
+        .json(&serde_json::json!({ "nome": "Cliente", "email": email, "senha": "Mercado#2025forte" }))
+        .send()
+        .await
+        .expect("Falha ao registrar");
+    let (user_sid, user_csrf) = login(&client, &email, "Mercado#2025forte").await;
+    let forbidden = client
+        .post(&url)
+        .header("cookie", format!("session_id={}", user_sid))
+        .header("x-csrf-token", &user_csrf)
+        .multipart(image_form(b"x".to_vec(), "image/png"))
+        .send()
+        .await
+        .expect("Falha no upload");
+    assert_eq!(forbidden.status().as_u16(), 403);
+
+    // 3) Admin: conteúdo que não é imagem é recusado
+    let (sid, csrf) = login(&client, "admin@teste.com", "123456").await;
+    let not_image = client
+        .post(&url)
+        .header("cookie", format!("session_id={}", sid))
+        .header("x-csrf-token", &csrf)
+        .multipart(image_form(b"isto nao e uma imagem".to_vec(), "image/png"))
+        .send()
+        .await
+        .expect("Falha no upload");
+    assert_eq!(not_image.status().as_u16(), 415);
+
+    // 4) Admin: upload válido gera variantes com nomes seguros
+    let ok = client
+        .post(&url)
+        .header("cookie", format!("session_id={}", sid))
+        .header("x-csrf-token", &csrf)
+        .multipart(image_form(png, "image/png"))
+        .send()
+        .await
+        .expect("Falha no upload");
+    assert!(ok.status().is_success(), "Upload deve retornar 200, veio {}", ok.status());
+    let product: Value = ok.json().await.expect("Falha ao parsear produto");
+    let full = product["image_url"].as_str().expect("image_url ausente").to_string();
+    let thumb = product["thumbnail_url"].as_str().expect("thumbnail_url ausente").to_string();
+    assert_ne!(full, thumb);
+    for u in [&full, &thumb] {
+        assert!(u.chars().all(|c| c.is_ascii_alphanumeric() || "/-_.".contains(c)), "Nome inseguro: {}", u);
+    }
+
+    // 5) Miniatura servida e limitada a 200px
+    let thumb_bytes = client
+        .get(format!("{}/{}", common::BASE_URL, thumb))
+        .header("cookie", format!("session_id={}", sid))
+        .send()
+        .await
+        .expect("Falha ao baixar miniatura")
+        .bytes()
+        .await
+        .expect("Falha ao ler miniatura");
+    let (w, h) = png_size(&thumb_bytes);
+    assert!(w <= 200 && h <= 200, "Miniatura deve ter no máximo 200px, veio {}x{}", w, h);
+}
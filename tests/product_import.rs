@@ -0,0 +1,150 @@
+use serde_json::Value;
+
+mod common;
+
+// SKU e EAN fixos: execuções repetidas atualizam o mesmo produto em vez de crescer o catálogo
+const SKU: &str = "TESTE-IMP-0001";
+const EAN: &str = "7891000000014";
+
+#[tokio::test]
+async fn product_import() {
+    let _server = common::spawn_server().await;
+    let client = reqwest::Client::new();
+    let (admin, csrf) = common::login(&client, "admin@teste.com", "123456").await.expect("login admin");
+
+    let importar = |query: &'static str, tipo: &'static str, corpo: String| {
+        client
+            .post(format!("{}/api/admin/products/import{}", common::BASE_URL, query))
+            .header("cookie", &admin)
+            .header("x-csrf-token", &csrf)
+            .header("content-type", tipo)
+            .body(corpo)
+            .send()
+    };
+    let exportar = || async {
+        let resp = client
+            .get(format!("{}/api/admin/products/export?formato=json", common::BASE_URL))
+            .header("cookie", &admin)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status().as_u16(), 200);
+        let produtos: Vec<Value> = resp.json().await.expect("export JSON");
+        produtos.into_iter().find(|p| p["sku"] == SKU)
+    };
+
+    // Sem sessão: 401
+    let resp = client
+        .post(format!("{}/api/admin/products/import", common::BASE_URL))
+        .header("content-type", "text/csv")
+        .body("sku,name\n")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 401);
+
+    let ts = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_millis();
+    let preco = 1000 + (ts % 9000) as u64;
+    let csv = format!(
+        "sku,ean,name,price_cents,stock,category,brand,unit,unit_size\n\
+         {},{},Produto Importado,{},40,Mercearia,Marca Teste,kg,\"1,5\"\n",
+        SKU, EAN, preco
+    );
+
+    // 1) dry_run: mostra a ação sem gravar
+    let antes = exportar().await;
+    let resp = importar("?dry_run=true", "text/csv", csv.clone()).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let simulado: Value = resp.json().await.unwrap();
+    assert_eq!(simulado["dry_run"], true);
+    assert_eq!(simulado["total"], 1);
+    assert_eq!(simulado["itens"][0]["linha"], 2);
+    let acao = simulado["itens"][0]["acao"].as_str().unwrap().to_string();
+    assert_eq!(acao, if antes.is_some() { "atualizado" } else { "criado" });
+    assert_eq!(exportar().await.map(|p| p["price_cents"].clone()), antes.as_ref().map(|p| p["price_cents"].clone()));
+
+    // 2) Importação real: mesma ação, produto gravado
+    let resp = importar("", "text/csv; charset=utf-8", csv).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["dry_run"], false);
+    assert_eq!(body["itens"][0]["acao"], acao.as_str());
+    let produto = exportar().await.expect("produto importado");
+    let id = produto["id"].as_i64().unwrap();
+    assert_eq!(produto["price_cents"].as_u64(), Some(preco));
+    assert_eq!(produto["ean"], EAN);
+    assert_eq!(produto["unit_size"], 1.5);
+
+    // 3) Erros por linha (CSV com ponto e vírgula): nada é gravado
+    let csv_invalido = format!(
+        "sku;ean;name;price_cents;stock;category;brand;unit;unit_size\n\
+         {sku};7891000000015;Produto Importado;1;-3;Mercearia;Marca;kg;1\n\
+         ;;Sem Codigo;100;1;Mercearia;Marca;caixa;1\n\
+         {sku};;Repetido;100;1;Mercearia;Marca;kg;1\n",
+        sku = SKU
+    );
+    let resp = importar("", "text/csv", csv_invalido).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 422);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["field"], "linhas");
+    let erros = body["erros"].as_array().cloned().unwrap_or_default();
+    let tem = |linha: u64, campo: &str| erros.iter().any(|e| e["linha"].as_u64() == Some(linha) && e["campo"] == campo);
+    assert!(tem(2, "ean") && tem(2, "stock"), "erros: {:?}", erros);
+    assert!(tem(3, "sku") && tem(3, "unit"), "erros: {:?}", erros);
+    assert!(tem(4, "sku"), "SKU repetido no arquivo: {:?}", erros);
+    assert_eq!(exportar().await.unwrap()["price_cents"].as_u64(), Some(preco));
+
+    // 4) JSON casado pelo EAN atualiza o mesmo produto
+    let json = serde_json::json!([{
+        "ean": EAN, "name": "Produto Importado", "price_cents": preco + 1, "stock": 40,
+        "category": "Mercearia", "brand": "Marca Teste", "unit": "kg", "unit_size": 1.5
+    }]);
+    let resp = importar("", "application/json", json.to_string()).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["itens"][0]["acao"], "atualizado");
+    assert_eq!(body["itens"][0]["id"].as_i64(), Some(id));
+    assert_eq!(exportar().await.unwrap()["price_cents"].as_u64(), Some(preco + 1));
+
+    // 5) SKU de um produto com EAN de outro: conflito na linha
+    let json = serde_json::json!([{
+        "sku": "MER-0001", "ean": EAN, "name": "Arroz 1kg", "price_cents": 799, "stock": 50,
+        "category": "Mercearia", "brand": "Bom Grão", "unit": "kg", "unit_size": 1
+    }]);
+    let resp = importar("", "application/json", json.to_string()).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 422);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["erros"][0]["campo"], "ean");
+
+    // 6) SKU desconhecido com o EAN do produto: erro na linha, o SKU do produto não muda
+    let json = serde_json::json!([{
+        "sku": "TESTE-IMP-RENOMEADO", "ean": EAN, "name": "Produto Importado", "price_cents": preco, "stock": 40,
+        "category": "Mercearia", "brand": "Marca Teste", "unit": "kg", "unit_size": 1.5
+    }]);
+    let resp = importar("", "application/json", json.to_string()).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 422);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["erros"][0]["campo"], "sku");
+    assert_eq!(exportar().await.expect("SKU mantido")["id"].as_i64(), Some(id));
+
+    // 7) Coluna desconhecida e tipo de conteúdo ausente
+    let resp = importar("", "text/csv", "sku,nome\nX,Y\n".to_string()).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 422);
+    assert_eq!(resp.json::<Value>().await.unwrap()["field"], "arquivo");
+    let resp = importar("", "application/octet-stream", "x".to_string()).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 415);
+
+    // 8) Exportação CSV: cabeçalho com as colunas da importação e o produto importado
+    let resp = client
+        .get(format!("{}/api/admin/products/export", common::BASE_URL))
+        .header("cookie", &admin)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let tipo = resp.headers().get("content-type").and_then(|v| v.to_str().ok()).unwrap_or("").to_string();
+    assert!(tipo.starts_with("text/csv"), "content-type: {}", tipo);
+    let texto = resp.text().await.unwrap();
+    assert_eq!(texto.lines().next(), Some("id,sku,ean,name,price_cents,stock,category,brand,unit,unit_size,image_url"));
+    assert!(texto.lines().any(|l| l.starts_with(&format!("{},{},{},", id, SKU, EAN))), "produto ausente no CSV");
+}
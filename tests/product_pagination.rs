@@ -0,0 +1,78 @@
+use serde_json::Value;
+
+mod common;
+
+async fn get_json(client: &reqwest::Client, query: &str) -> Value {
+    let resp = client
+        .get(format!("{}/api/products{}", common::BASE_URL, query))
+        .send()
+        .await
+        .expect("Falha ao obter /api/products");
+    assert!(resp.status().is_success(), "GET /api/products{} deve retornar 200, obtido {}", query, resp.status());
+    resp.json().await.expect("Falha ao parsear produtos")
+}
+
+#[tokio::test]
+async fn product_pagination() {
+    let _server = common::spawn_server().await;
+    let client = reqwest::Client::new();
+
+    // 1) Paginação por offset
+    let first = get_json(&client, "?per_page=5&page=1").await;
+    let second = get_json(&client, "?per_page=5&page=2").await;
+    assert_eq!(first["items"].as_array().unwrap().len(), 5);
+    assert_eq!(first["per_page"].as_u64(), Some(5));
+    let total = first["total"].as_u64().unwrap();
+    assert!(total > 5, "Catálogo deve ter mais de 5 produtos");
+    assert_eq!(first["total_pages"].as_u64(), Some(total.div_ceil(5)));
+    assert_ne!(first["items"][0]["id"], second["items"][0]["id"], "Páginas devem ser diferentes");
+
+    // 2) Ordenação por preço (crescente) e por nome (decrescente)
+    let by_price = get_json(&client, "?sort=price&per_page=100").await;
+    let prices: Vec<u64> = by_price["items"].as_array().unwrap().iter().map(|p| p["price_cents"].as_u64().unwrap()).collect();
+    assert!(prices.windows(2).all(|w| w[0] <= w[1]), "Preços devem estar em ordem crescente");
+
+    let by_name = get_json(&client, "?sort=name&order=desc&per_page=100").await;
+    let names: Vec<String> = by_name["items"].as_array().unwrap().iter().map(|p| p["name"].as_str().unwrap().to_lowercase()).collect();
+    assert!(names.windows(2).all(|w| w[0] >= w[1]), "Nomes devem estar em ordem decrescente");
+
+    let popular = client
+        .get(format!("{}/api/products?sort=popularity", common::BASE_URL))
+        .send()
+        .await
+        .expect("Falha ao ordenar por popularidade");
+    assert!(popular.status().is_success());
+
+    let invalid = client
+        .get(format!("{}/api/products?sort=estoque", common::BASE_URL))
+        .send()
+        .await
+        .expect("Falha ao chamar /api/products");
+    assert_eq!(invalid.status().as_u16(), 422);
+
+    // 3) ETag: repetir a consulta com If-None-Match devolve 304 sem corpo
+    let resp = client
+        .get(format!("{}/api/products?sort=price", common::BASE_URL))
+        .send()
+        .await
+        .expect("Falha ao obter /api/products");
+    let etag = resp.headers().get("etag").and_then(|v| v.to_str().ok()).expect("ETag ausente").to_string();
+
+    let cached = client
+        .get(format!("{}/api/products?sort=price", common::BASE_URL))
+        .header("if-none-match", &etag)
+        .send()
+        .await
+        .expect("Falha ao revalidar /api/products");
+    assert_eq!(cached.status().as_u16(), 304);
+    assert_eq!(cached.headers().get("etag").and_then(|v| v.to_str().ok()), Some(etag.as_str()));
+    assert_eq!(cached.headers().get("cache-control").and_then(|v| v.to_str().ok()), Some("no-cache"));
+
+    let other = client
+        .get(format!("{}/api/products?sort=name", common::BASE_URL))
+        .header("if-none-match", &etag)
+        .send()
+        .await
+        .expect("Falha ao obter /api/products");
+    assert_eq!(other.status().as_u16(), 200, "Outra listagem tem outro ETag");
+}
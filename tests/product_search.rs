@@ -0,0 +1,79 @@
+use serde_json::Value;
+
+mod common;
+
+async fn search(client: &reqwest::Client, q: &str) -> (u16, Value) {
+    let resp = client
+        .get(format!("{}/api/products/search", common::BASE_URL))
+        .query(&[("q", q)])
+        .send()
+        .await
+        .expect("Falha ao chamar /api/products/search");
+    let status = resp.status().as_u16();
+    let body: Value = resp.json().await.expect("Falha ao parsear busca");
+    (status, body)
+}
+
+fn names(body: &Value) -> Vec<String> {
+    body["items"]
+        .as_array()
+        .map(|items| items.iter().filter_map(|p| p["name"].as_str().map(str::to_string)).collect())
+        .unwrap_or_default()
+}
+
+#[tokio::test]
+async fn product_search() {
+    let _server = common::spawn_server().await;
+    let client = reqwest::Client::new();
+
+    // 1) Sem acento encontra o nome acentuado, com destaque
+    let (status, body) = search(&client, "acucar").await;
+    assert_eq!(status, 200);
+    assert!(names(&body).contains(&"Açúcar 1kg".to_string()), "acucar deve encontrar Açúcar: {}", body);
+    let hit = body["items"].as_array().unwrap().iter().find(|p| p["name"] == "Açúcar 1kg").unwrap();
+    assert_eq!(hit["highlight"].as_str(), Some("<mark>Açúcar</mark> 1kg"));
+
+    let (_, body) = search(&client, "cafe").await;
+    assert!(names(&body).contains(&"Café 500g".to_string()), "cafe deve encontrar Café: {}", body);
+
+    // Qualquer diacrítico na consulta é removido, não só os do português
+    let (_, body) = search(&client, "AÇŪCAR").await;
+    assert!(names(&body).contains(&"Açúcar 1kg".to_string()), "AÇŪCAR deve encontrar Açúcar: {}", body);
+
+    // 2) Vários termos: todos precisam casar
+    let (_, body) = search(&client, "arroz integral").await;
+    assert_eq!(names(&body).first().map(String::as_str), Some("Arroz Integral 1kg"));
+    assert!(!names(&body).contains(&"Arroz 1kg".to_string()));
+
+    // 3) Erro de digitação: nenhum resultado, mas com sugestão
+    let (_, body) = search(&client, "acucr").await;
+    assert_eq!(body["total"].as_u64(), Some(0));
+    let suggestions: Vec<&str> = body["suggestions"].as_array().unwrap().iter().filter_map(|s| s.as_str()).collect();
+    assert!(suggestions.contains(&"acucar"), "Deve sugerir acucar, veio {:?}", suggestions);
+
+    // 4) Consulta vazia é inválida
+    let (status, _) = search(&client, "  ").await;
+    assert_eq!(status, 422);
+
+    // 5) Nome importado com HTML: o destaque vem escapado, só <mark> é marcação
+    let (admin, csrf) = common::login(&client, "admin@teste.com", "123456").await.expect("login admin");
+    let produto = serde_json::json!([{
+        "sku": "TESTE-BUSCA-0001", "name": "Biscoito <img src=x onerror=alert(1)> & Cia", "price_cents": 350, "stock": 10,
+        "category": "Mercearia", "brand": "Marca Teste", "unit": "un", "unit_size": 1
+    }]);
+    let resp = client
+        .post(format!("{}/api/admin/products/import", common::BASE_URL))
+        .header("cookie", &admin)
+        .header("x-csrf-token", &csrf)
+        .json(&produto)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let (_, body) = search(&client, "biscoito onerror").await;
+    let hit = body["items"].as_array().unwrap().iter().find(|p| p["sku"] == "TESTE-BUSCA-0001").expect("produto importado na busca");
+    assert_eq!(
+        hit["highlight"].as_str(),
+        Some("<mark>Biscoito</mark> &lt;img src=x <mark>onerror</mark>=alert(1)&gt; &amp; Cia")
+    );
+}
@@ -0,0 +1,105 @@
+use serde_json::Value;
+
+mod common;
+
+#[tokio::test]
+async fn recibo_pedido() {
+    let _server = common::spawn_server().await;
+    let client = reqwest::Client::new();
+
+    let ts = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_millis();
+    let senha = "Mercado#2025forte";
+    let email = format!("recibo{}@teste.com", ts);
+    let (cookie, csrf) = common::verified_user_session(&client, "Cliente Recibo", &email, senha).await;
+
+    // 1) Pedido no cartão em 4 parcelas (com juros)
+    let products: Value = client.get(format!("{}/api/products", common::BASE_URL)).send().await.unwrap().json().await.unwrap();
+    let produto = &products["items"][0];
+    let product_id = produto["id"].as_u64().expect("Produto deve ter id");
+    client
+        .post(format!("{}/api/cart", common::BASE_URL))
+        .json(&serde_json::json!({ "product_id": product_id, "qty": 3 }))
+        .send()
+        .await
+        .unwrap();
+    let resp = client
+        .post(format!("{}/api/checkout", common::BASE_URL))
+        .header("cookie", &cookie)
+        .header("x-csrf-token", &csrf)
+        .json(&serde_json::json!({ "payment": { "method": "credit", "installments": 4 }, "customer_email": email }))
+        .send()
+        .await
+        .unwrap();
+    assert!(resp.status().is_success(), "Checkout deve retornar 200, obtido {}", resp.status());
+    let body: Value = resp.json().await.unwrap();
+    let order_id = body["order_id"].as_str().unwrap().to_string();
+    assert!(body["interest_cents"].as_u64().unwrap_or(0) > 0);
+    let parcela = body["installment_value_cents"].as_u64().unwrap();
+
+    // 2) HTML: itens, total da linha, parcelas e juros
+    let resp = client
+        .get(format!("{}/api/pedidos/{}/recibo.html", common::BASE_URL, order_id))
+        .header("cookie", &cookie)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    assert!(resp.headers()["content-type"].to_str().unwrap().starts_with("text/html"));
+    let html = resp.text().await.unwrap();
+    assert!(html.contains(&order_id));
+    let unit = produto["price_cents"].as_i64().unwrap();
+    let total_linha = unit * 3;
+    assert!(html.contains(&format!("R$ {},{:02}", total_linha / 100, total_linha % 100)));
+    assert!(html.contains(&format!("x de R$ {},{:02}", parcela / 100, parcela % 100)));
+    assert!(html.contains("Juros:"));
+
+    // 2b) O recibo usa o nome gravado na compra, não o nome atual do produto
+    let nome = produto["name"].as_str().unwrap().to_string();
+    let db = sqlx::SqlitePool::connect("sqlite://data/mercado.db").await.expect("banco dos testes");
+    let renomear = |novo: String| sqlx::query("UPDATE produtos SET name = ? WHERE id = ?").bind(novo).bind(product_id as i64).execute(&db);
+    renomear(format!("{} Renomeado", nome)).await.unwrap();
+    let html = client
+        .get(format!("{}/api/pedidos/{}/recibo.html", common::BASE_URL, order_id))
+        .header("cookie", &cookie)
+        .send()
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    renomear(nome.clone()).await.unwrap();
+    db.close().await;
+    assert!(html.contains(&nome) && !html.contains("Renomeado"), "recibo deve mostrar o nome da compra");
+
+    // 3) PDF válido para download
+    let resp = client
+        .get(format!("{}/api/pedidos/{}/recibo.pdf", common::BASE_URL, order_id))
+        .header("cookie", &cookie)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    assert_eq!(resp.headers()["content-type"].to_str().unwrap(), "application/pdf");
+    assert!(resp.headers()["content-disposition"].to_str().unwrap().contains("recibo-"));
+    let pdf = resp.bytes().await.unwrap();
+    assert!(pdf.starts_with(b"%PDF-"));
+    assert!(pdf.ends_with(b"%%EOF\n"));
+
+    // 4) Outro cliente não vê o recibo; administrador (separação) vê
+    let (outro, _) = common::verified_user_session(&client, "Cliente Recibo", &format!("recibo-outro{}@teste.com", ts), senha).await;
+    let resp = client
+        .get(format!("{}/api/pedidos/{}/recibo.pdf", common::BASE_URL, order_id))
+        .header("cookie", &outro)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 404);
+    let (admin, _) = common::login(&client, "admin@teste.com", "123456").await.expect("login admin");
+    let resp = client
+        .get(format!("{}/api/pedidos/{}/recibo.html", common::BASE_URL, order_id))
+        .header("cookie", &admin)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+}
@@ -0,0 +1,61 @@
+use serde_json::Value;
+
+mod common;
+
+async fn register(client: &reqwest::Client, nome: &str, email: &str, senha: &str) -> (u16, Value) {
+    let resp = client
+        .post(format!("{}/api/register", common::BASE_URL))
+        .json(&serde_json::json!({ "nome": nome, "email": email, "senha": senha }))
+        .send()
+        .await
+        .expect("Falha ao chamar /api/register");
+    let status = resp.status().as_u16();
+    (status, resp.json().await.expect("Falha ao parsear JSON"))
+}
+
+#[tokio::test]
+async fn register_validation() {
+    let _server = common::spawn_server().await;
+    let client = reqwest::Client::new();
+    let ts = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_millis();
+
+    // 1) Senha curta, e-mail mal formado e nome vazio: 422 com erro por campo
+    let (status, body) = register(&client, "  ", "sem-arroba", "a").await;
+    assert_eq!(status, 422);
+    assert!(body["campos"]["nome"].as_str().is_some());
+    assert!(body["campos"]["email"].as_str().is_some());
+    assert!(body["campos"]["senha"].as_str().is_some());
+
+    // 2) Senha da lista de senhas comuns
+    let (status, body) = register(&client, "Fulano", &format!("comum{}@teste.com", ts), "password123").await;
+    assert_eq!(status, 422);
+    assert!(body["campos"]["senha"].as_str().is_some());
+    assert!(body["campos"].get("email").is_none(), "E-mail válido não deve ter erro");
+
+    // 3) E-mail armazenado em minúsculas: variação de caixa é a mesma conta
+    let upper = format!("Caixa{}@Teste.COM", ts);
+    let senha = "  Espaços contam 2025  ";
+    let (status, _) = register(&client, "Fulano", &upper, senha).await;
+    assert_eq!(status, 200, "Cadastro válido deve retornar 200");
+    let (status, _) = register(&client, "Outro", &upper.to_lowercase(), "Outra#Senha2025").await;
+    assert_eq!(status, 400, "Mesmo e-mail com outra caixa deve ser recusado");
+
+    // 4) Login com qualquer caixa e com a senha exatamente como digitada
+    let ok = client
+        .post(format!("{}/api/login", common::BASE_URL))
+        .json(&serde_json::json!({ "email": upper.to_uppercase(), "senha": senha }))
+        .send()
+        .await
+        .expect("Falha ao chamar /api/login");
+    assert!(ok.status().is_success(), "Login deve aceitar e-mail com outra caixa, veio {}", ok.status());
+    let body: Value = ok.json().await.expect("Falha ao parsear login");
+    assert_eq!(body["usuario"]["email"].as_str(), Some(upper.to_lowercase().as_str()));
+
+    let trimmed = client
+        .post(format!("{}/api/login", common::BASE_URL))
+        .json(&serde_json::json!({ "email": upper, "senha": senha.trim() }))
+        .send()
+        .await
+        .expect("Falha ao chamar /api/login");
+    assert_eq!(trimmed.status().as_u16(), 401, "Senha sem os espaços digitados não deve entrar");
+}
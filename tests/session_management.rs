@@ -0,0 +1,110 @@
+use serde_json::Value;
+
+mod common;
+
+// Retorna (cabeçalho cookie, csrf_token)
+async fn login(client: &reqwest::Client, email: &str, senha: &str, user_agent: &str) -> (String, String) {
+    let resp = client
+        .post(format!("{}/api/login", common::BASE_URL))
+        .header("user-agent", user_agent)
+        .json(&serde_json::json!({ "email": email, "senha": senha }))
+        .send()
+        .await
+        .expect("Falha ao enviar login");
+    assert!(resp.status().is_success(), "Login deve retornar 200, veio {}", resp.status());
+    let set_cookie = resp.headers().get("set-cookie").and_then(|v| v.to_str().ok()).expect("Set-Cookie ausente");
+    let cookie = format!("session_id={}", common::extract_cookie(set_cookie, "session_id").expect("session_id não encontrado"));
+    let body: Value = resp.json().await.expect("Falha ao parsear login");
+    (cookie, body["csrf_token"].as_str().expect("csrf_token ausente").to_string())
+}
+
+async fn me_status(client: &reqwest::Client, cookie: &str) -> u16 {
+    client
+        .get(format!("{}/api/auth/me", common::BASE_URL))
+        .header("cookie", cookie)
+        .send()
+        .await
+        .expect("Falha em /api/auth/me")
+        .status()
+        .as_u16()
+}
+
+async fn list(client: &reqwest::Client, cookie: &str) -> Vec<Value> {
+    client
+        .get(format!("{}/api/me/sessions", common::BASE_URL))
+        .header("cookie", cookie)
+        .send()
+        .await
+        .expect("Falha ao listar sessões")
+        .json()
+        .await
+        .expect("Falha ao parsear sessões")
+}
+
+#[tokio::test]
+async fn session_management() {
+    let _server = common::spawn_server().await;
+    let client = reqwest::Client::new();
+
+    let ts = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_millis();
+    let email = format!("sessoes{}@teste.com", ts);
+    let senha = "Mercado#2025forte";
+    client
+        .post(format!("{}/api/register", common::BASE_URL))
+        .json(&serde_json::json!({ "nome": "Sessões", "email": email, "senha": senha }))
+        .send()
+        .await
+        .expect("Falha ao registrar");
+
+    // 1) Duas sessões em "dispositivos" diferentes
+    let (desktop, desktop_csrf) = login(&client, &email, senha, "Teste/Desktop").await;
+    let (celular, _) = login(&client, &email, senha, "Teste/Celular").await;
+    let sessions = list(&client, &desktop).await;
+    assert_eq!(sessions.len(), 2);
+    let atual: Vec<_> = sessions.iter().filter(|s| s["atual"] == Value::Bool(true)).collect();
+    assert_eq!(atual.len(), 1);
+    assert_eq!(atual[0]["user_agent"].as_str(), Some("Teste/Desktop"));
+    assert!(atual[0]["ip"].as_str().is_some());
+    assert!(atual[0]["last_seen_at"].as_str().is_some());
+    let outra = sessions.iter().find(|s| s["atual"] == Value::Bool(false)).unwrap();
+    let outra_id = outra["id"].as_str().unwrap().to_string();
+    assert!(!celular.contains(&outra_id), "id público não deve ser o valor do cookie");
+
+    // 2) Sessões de outro usuário não podem ser encerradas
+    let (admin, admin_csrf) = login(&client, "admin@teste.com", "123456", "Teste/Admin").await;
+    let foreign = client
+        .delete(format!("{}/api/me/sessions/{}", common::BASE_URL, outra_id))
+        .header("cookie", &admin)
+        .header("x-csrf-token", &admin_csrf)
+        .send()
+        .await
+        .expect("Falha ao encerrar sessão");
+    assert_eq!(foreign.status().as_u16(), 404);
+    assert_eq!(me_status(&client, &celular).await, 200);
+
+    // 3) Encerrar a sessão do celular a partir do desktop
+    let revoke = client
+        .delete(format!("{}/api/me/sessions/{}", common::BASE_URL, outra_id))
+        .header("cookie", &desktop)
+        .header("x-csrf-token", &desktop_csrf)
+        .send()
+        .await
+        .expect("Falha ao encerrar sessão");
+    assert_eq!(revoke.status().as_u16(), 200);
+    assert_eq!(me_status(&client, &celular).await, 401);
+    assert_eq!(me_status(&client, &desktop).await, 200);
+
+    // 4) Sair de todos os dispositivos
+    let (tablet, _) = login(&client, &email, senha, "Teste/Tablet").await;
+    let all = client
+        .delete(format!("{}/api/me/sessions", common::BASE_URL))
+        .header("cookie", &desktop)
+        .header("x-csrf-token", &desktop_csrf)
+        .send()
+        .await
+        .expect("Falha ao encerrar sessões");
+    assert_eq!(all.status().as_u16(), 200);
+    assert_eq!(me_status(&client, &desktop).await, 401);
+    assert_eq!(me_status(&client, &tablet).await, 401);
+    assert_eq!(me_status(&client, &admin).await, 200);
+}
@@ -0,0 +1,87 @@
+mod common;
+
+async fn login(client: &reqwest::Client, email: &str, senha: &str, extra: &[(&str, String)]) -> String {
+    let mut req = client
+        .post(format!("{}/api/login", common::BASE_URL))
+        .json(&serde_json::json!({ "email": email, "senha": senha }));
+    for (k, v) in extra {
+        req = req.header(*k, v);
+    }
+    let resp = req.send().await.expect("Falha ao enviar login");
+    assert!(resp.status().is_success(), "Login deve retornar 200, veio {}", resp.status());
+    resp.headers().get("set-cookie").and_then(|v| v.to_str().ok()).expect("Set-Cookie ausente").to_string()
+}
+
+async fn me_status(client: &reqwest::Client, sid: &str) -> u16 {
+    client
+        .get(format!("{}/api/auth/me", common::BASE_URL))
+        .header("cookie", format!("session_id={}", sid))
+        .send()
+        .await
+        .expect("Falha em /api/auth/me")
+        .status()
+        .as_u16()
+}
+
+#[tokio::test]
+async fn session_security() {
+    let _server = common::spawn_server().await;
+    let client = reqwest::Client::new();
+
+    // 1) Cookie assinado: token.assinatura; sem Secure em HTTP puro
+    let set_cookie = login(&client, "admin@teste.com", "123456", &[]).await;
+    assert!(!set_cookie.contains("Secure"));
+    let sid = common::extract_cookie(&set_cookie, "session_id").unwrap();
+    let (token, assinatura) = sid.split_once('.').expect("Cookie deve conter assinatura");
+    assert_eq!(me_status(&client, &sid).await, 200);
+
+    // 2) Assinatura adulterada, token sem assinatura ou UUID qualquer são recusados
+    let last = assinatura.chars().last().unwrap();
+    let flipped = format!("{}.{}{}", token, &assinatura[..assinatura.len() - 1], if last == '0' { '1' } else { '0' });
+    assert_eq!(me_status(&client, &flipped).await, 401);
+    assert_eq!(me_status(&client, token).await, 401);
+    assert_eq!(me_status(&client, "4f1c1a52-8b1e-4c36-9a41-2d6f0c1e9b77").await, 401);
+
+    // 3) O banco guarda só o hash do token
+    for path in ["data/mercado.db", "data/mercado.db-wal"] {
+        if let Ok(bytes) = std::fs::read(path) {
+            assert!(!bytes.windows(token.len()).any(|w| w == token.as_bytes()), "Token em claro encontrado em {}", path);
+        }
+    }
+
+    // 4) Novo login no mesmo navegador rotaciona a sessão
+    let rotated = login(&client, "admin@teste.com", "123456", &[("cookie", format!("session_id={}", sid))]).await;
+    let new_sid = common::extract_cookie(&rotated, "session_id").unwrap();
+    assert_ne!(new_sid, sid);
+    assert_eq!(me_status(&client, &sid).await, 401);
+    assert_eq!(me_status(&client, &new_sid).await, 200);
+
+    // 5) Atrás de proxy HTTPS o cookie recebe Secure
+    let https = login(&client, "admin@teste.com", "123456", &[("x-forwarded-proto", "https".to_string())]).await;
+    assert!(https.contains("; Secure"));
+
+    // 6) Verificar o e-mail (mudança de privilégio) troca o identificador da sessão
+    let ts = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_millis();
+    let email = format!("rotacao{}@teste.com", ts);
+    let senha = "Mercado#2025forte";
+    client
+        .post(format!("{}/api/register", common::BASE_URL))
+        .json(&serde_json::json!({ "nome": "Rotação", "email": email, "senha": senha }))
+        .send()
+        .await
+        .expect("Falha ao registrar");
+    let before = common::extract_cookie(&login(&client, &email, senha, &[]).await, "session_id").unwrap();
+    let mail_token = common::token_from_mail(&email).expect("E-mail de verificação não encontrado");
+    let verify = client
+        .get(format!("{}/api/verify-email?token={}", common::BASE_URL, mail_token))
+        .header("cookie", format!("session_id={}", before))
+        .send()
+        .await
+        .expect("Falha ao verificar");
+    assert_eq!(verify.status().as_u16(), 200);
+    let after_header = verify.headers().get("set-cookie").and_then(|v| v.to_str().ok()).expect("Sessão deve ser rotacionada");
+    let after = common::extract_cookie(after_header, "session_id").unwrap();
+    assert_ne!(after, before);
+    assert_eq!(me_status(&client, &before).await, 401);
+    assert_eq!(me_status(&client, &after).await, 200);
+}
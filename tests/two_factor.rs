@@ -0,0 +1,145 @@
+use data_encoding::BASE32_NOPAD;
+use hmac::{Hmac, Mac};
+use serde_json::Value;
+use sha1::Sha1;
+
+mod common;
+
+// Código TOTP (RFC 6238) para o passo de 30s indicado
+fn totp(secret_b32: &str, step: u64) -> String {
+    let secret = BASE32_NOPAD.decode(secret_b32.as_bytes()).expect("segredo base32 inválido");
+    let mut mac = Hmac::<Sha1>::new_from_slice(&secret).unwrap();
+    mac.update(&step.to_be_bytes());
+    let hash = mac.finalize().into_bytes();
+    let offset = (hash[19] & 0x0f) as usize;
+    let bin = u32::from_be_bytes([hash[offset] & 0x7f, hash[offset + 1], hash[offset + 2], hash[offset + 3]]);
+    format!("{:06}", bin % 1_000_000)
+}
+
+fn current_step() -> u64 {
+    std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs() / 30
+}
+
+async fn post(client: &reqwest::Client, path: &str, auth: Option<(&str, &str)>, body: Value) -> reqwest::Response {
+    let mut req = client.post(format!("{}{}", common::BASE_URL, path)).json(&body);
+    if let Some((cookie, csrf)) = auth {
+        req = req.header("cookie", cookie).header("x-csrf-token", csrf);
+    }
+    req.send().await.expect("Falha na requisição")
+}
+
+// Conclui a segunda etapa e devolve o cookie da sessão
+async fn second_step(client: &reqwest::Client, desafio: &str, codigo: &str) -> reqwest::Response {
+    post(client, "/api/login/2fa", None, serde_json::json!({ "desafio": desafio, "codigo": codigo })).await
+}
+
+async fn start_login(client: &reqwest::Client, email: &str, senha: &str) -> Value {
+    let resp = post(client, "/api/login", None, serde_json::json!({ "email": email, "senha": senha })).await;
+    assert!(resp.status().is_success());
+    assert!(resp.headers().get("set-cookie").is_none(), "Sessão só deve ser criada após o segundo fator");
+    resp.json().await.unwrap()
+}
+
+#[tokio::test]
+async fn two_factor() {
+    let _server = common::spawn_server().await;
+    let client = reqwest::Client::new();
+
+    let ts = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_millis();
+    let email = format!("totp{}@teste.com", ts);
+    let senha = "Mercado#2025forte";
+    post(&client, "/api/register", None, serde_json::json!({ "nome": "TOTP", "email": email, "senha": senha })).await;
+    let login = post(&client, "/api/login", None, serde_json::json!({ "email": email, "senha": senha })).await;
+    let set_cookie = login.headers().get("set-cookie").and_then(|v| v.to_str().ok()).expect("Set-Cookie ausente");
+    let cookie = format!("session_id={}", common::extract_cookie(set_cookie, "session_id").unwrap());
+    let body: Value = login.json().await.unwrap();
+    let csrf = body["csrf_token"].as_str().unwrap().to_string();
+    let auth = Some((cookie.as_str(), csrf.as_str()));
+
+    // 1) Cadastro: URI otpauth e QR code
+    let setup: Value = post(&client, "/api/me/2fa/setup", auth, serde_json::json!({})).await.json().await.unwrap();
+    let secret = setup["secret"].as_str().expect("secret ausente").to_string();
+    let uri = setup["otpauth_uri"].as_str().expect("otpauth_uri ausente");
+    assert!(uri.starts_with("otpauth://totp/") && uri.contains(&format!("secret={}", secret)));
+    assert!(setup["qr_svg"].as_str().unwrap_or("").contains("<svg"));
+
+    // 2) Ativação exige código válido e devolve códigos de recuperação
+    let wrong = post(&client, "/api/me/2fa/enable", auth, serde_json::json!({ "codigo": "000000x" })).await;
+    assert_eq!(wrong.status().as_u16(), 422);
+    let step = current_step();
+    let enabled = post(&client, "/api/me/2fa/enable", auth, serde_json::json!({ "codigo": totp(&secret, step) })).await;
+    assert!(enabled.status().is_success(), "Ativação deve retornar 200, veio {}", enabled.status());
+    // Ativar a 2FA é mudança de privilégio: a sessão ganha novo identificador
+    let set_cookie = enabled.headers().get("set-cookie").and_then(|v| v.to_str().ok()).expect("Set-Cookie ausente");
+    let rotated = format!("session_id={}", common::extract_cookie(set_cookie, "session_id").unwrap());
+    assert_ne!(rotated, cookie);
+    let antigo = client.get(format!("{}/api/me", common::BASE_URL)).header("cookie", &cookie).send().await.unwrap();
+    assert_eq!(antigo.status().as_u16(), 401);
+    let cookie = rotated;
+    let auth = Some((cookie.as_str(), csrf.as_str()));
+    let enabled: Value = enabled.json().await.unwrap();
+    let recovery: Vec<String> = enabled["recovery_codes"].as_array().unwrap().iter().map(|c| c.as_str().unwrap().to_string()).collect();
+    assert_eq!(recovery.len(), 10);
+
+    // 3) Login passa a ter segunda etapa
+    let first = start_login(&client, &email, senha).await;
+    assert_eq!(first["requer_2fa"], Value::Bool(true));
+    let desafio = first["desafio"].as_str().unwrap().to_string();
+    assert_eq!(second_step(&client, &desafio, "123456").await.status().as_u16(), 401);
+    let code = totp(&secret, step + 1);
+    let ok = second_step(&client, &desafio, &code).await;
+    assert!(ok.status().is_success(), "Segunda etapa deve retornar 200, veio {}", ok.status());
+    assert!(ok.headers().get("set-cookie").is_some());
+
+    // 4) O mesmo código não vale duas vezes; desafio usado não serve mais
+    let again = start_login(&client, &email, senha).await;
+    let desafio2 = again["desafio"].as_str().unwrap().to_string();
+    assert_eq!(second_step(&client, &desafio2, &code).await.status().as_u16(), 401);
+    assert_eq!(second_step(&client, &desafio, &code).await.status().as_u16(), 401);
+
+    // 5) Código de recuperação funciona uma única vez
+    let rec = second_step(&client, &desafio2, &recovery[0].to_uppercase()).await;
+    assert!(rec.status().is_success(), "Código de recuperação deve ser aceito, veio {}", rec.status());
+    let third = start_login(&client, &email, senha).await;
+    let desafio3 = third["desafio"].as_str().unwrap().to_string();
+    assert_eq!(second_step(&client, &desafio3, &recovery[0]).await.status().as_u16(), 401);
+
+    // 6) Desativar exige senha e um código válido
+    let status: Value = client
+        .get(format!("{}/api/me/2fa", common::BASE_URL))
+        .header("cookie", &cookie)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(status["ativo"], Value::Bool(true));
+    assert_eq!(status["recovery_codes_restantes"].as_i64(), Some(9));
+    let bad = post(&client, "/api/me/2fa/disable", auth, serde_json::json!({ "senha": "errada", "codigo": recovery[1] })).await;
+    assert_eq!(bad.status().as_u16(), 401);
+    let off = post(&client, "/api/me/2fa/disable", auth, serde_json::json!({ "senha": senha, "codigo": recovery[1] })).await;
+    assert!(off.status().is_success());
+    assert!(off.headers().get("set-cookie").is_some(), "Desativar a 2FA também rotaciona a sessão");
+    let plain = post(&client, "/api/login", None, serde_json::json!({ "email": email, "senha": senha })).await;
+    let set_cookie = plain.headers().get("set-cookie").and_then(|v| v.to_str().ok()).expect("Set-Cookie ausente");
+    let cookie = format!("session_id={}", common::extract_cookie(set_cookie, "session_id").unwrap());
+    let plain: Value = plain.json().await.unwrap();
+    assert_eq!(plain["autenticado"], Value::Bool(true));
+    let csrf = plain["csrf_token"].as_str().unwrap().to_string();
+    let auth = Some((cookie.as_str(), csrf.as_str()));
+
+    // 7) Com 2FA, repetir a senha certa não zera as falhas do segundo fator: a 5ª bloqueia a conta
+    let setup: Value = post(&client, "/api/me/2fa/setup", auth, serde_json::json!({})).await.json().await.unwrap();
+    let secret = setup["secret"].as_str().unwrap().to_string();
+    let enabled = post(&client, "/api/me/2fa/enable", auth, serde_json::json!({ "codigo": totp(&secret, current_step()) })).await;
+    assert!(enabled.status().is_success());
+    let desafio = start_login(&client, &email, senha).await["desafio"].as_str().unwrap().to_string();
+    for _ in 0..4 {
+        assert_eq!(second_step(&client, &desafio, "000000").await.status().as_u16(), 401);
+    }
+    let desafio = start_login(&client, &email, senha).await["desafio"].as_str().unwrap().to_string();
+    assert_eq!(second_step(&client, &desafio, "000000").await.status().as_u16(), 429);
+    let bloqueado = post(&client, "/api/login", None, serde_json::json!({ "email": email, "senha": senha })).await;
+    assert_eq!(bloqueado.status().as_u16(), 429);
+}
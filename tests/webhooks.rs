@@ -0,0 +1,195 @@
+use axum::{extract::State, http::{HeaderMap, StatusCode}, routing::post, Router};
+use hmac::{Hmac, Mac};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use serde_json::Value;
+
+mod common;
+
+// Receptor local: guarda (cabeçalhos, corpo) e responde 200 ou 400 conforme `aceitar`
+#[derive(Clone, Default)]
+struct Receptor {
+    aceitar: Arc<AtomicBool>,
+    recebidos: Arc<Mutex<Vec<(HeaderMap, String)>>>,
+}
+
+async fn receber(State(r): State<Receptor>, headers: HeaderMap, corpo: String) -> StatusCode {
+    if !r.aceitar.load(Ordering::SeqCst) {
+        return StatusCode::BAD_REQUEST;
+    }
+    r.recebidos.lock().unwrap().push((headers, corpo));
+    StatusCode::OK
+}
+
+// Aguarda (até ~15s) uma entrega aceita do evento que satisfaça o filtro
+async fn esperar(r: &Receptor, evento: &str, filtro: impl Fn(&Value) -> bool) -> Option<(HeaderMap, String)> {
+    for _ in 0..50 {
+        let achado = r.recebidos.lock().unwrap().iter().find(|(h, corpo)| {
+            let v: Value = serde_json::from_str(corpo).unwrap_or(Value::Null);
+            h.get("x-mercado-event").and_then(|e| e.to_str().ok()) == Some(evento) && filtro(&v)
+        }).cloned();
+        if achado.is_some() {
+            return achado;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+    }
+    None
+}
+
+#[tokio::test]
+async fn webhooks() {
+    let _server = common::spawn_server().await;
+    let client = reqwest::Client::new();
+    let (admin, csrf) = common::login(&client, "admin@teste.com", "123456").await.expect("login admin");
+
+    let receptor = Receptor::default();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let porta = listener.local_addr().unwrap().port();
+    let app = Router::new().route("/hook", post(receber)).with_state(receptor.clone());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    // 1) Assinatura: evento desconhecido recusado; segredo exibido na criação
+    let criar = |eventos: Value| {
+        client
+            .post(format!("{}/api/admin/webhooks", common::BASE_URL))
+            .header("cookie", &admin)
+            .header("x-csrf-token", &csrf)
+            .json(&serde_json::json!({ "url": format!("http://127.0.0.1:{}/hook", porta), "eventos": eventos }))
+            .send()
+    };
+    assert_eq!(criar(serde_json::json!(["order.deleted"])).await.unwrap().status().as_u16(), 422);
+    // Destinos internos são recusados (só 127.0.0.1 está liberado no servidor de teste)
+    for interna in ["http://localhost:9/hook", "http://169.254.169.254/latest/meta-data", "http://10.0.0.5/hook", "http://[::1]:9/hook", "http://[64:ff9b::a9fe:a9fe]/latest"] {
+        let resp = client
+            .post(format!("{}/api/admin/webhooks", common::BASE_URL))
+            .header("cookie", &admin)
+            .header("x-csrf-token", &csrf)
+            .json(&serde_json::json!({ "url": interna, "eventos": ["order.created"] }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status().as_u16(), 422, "{} deveria ser recusada", interna);
+        let body: Value = resp.json().await.unwrap();
+        assert_eq!(body["field"].as_str(), Some("url"));
+    }
+    let resp = criar(serde_json::json!(["order.created", "order.status_changed", "product.low_stock"])).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 201);
+    let assinatura: Value = resp.json().await.unwrap();
+    let sub_id = assinatura["id"].as_str().unwrap().to_string();
+    let secret = assinatura["secret"].as_str().unwrap().to_string();
+
+    // 2) Estoque baixo com o destino recusando (400): entrega falha sem novas tentativas
+    let products: Value = client.get(format!("{}/api/products", common::BASE_URL)).send().await.unwrap().json().await.unwrap();
+    let produto = products["items"].as_array().unwrap().last().unwrap().clone();
+    let product_id = produto["id"].as_u64().unwrap();
+    let estoque_original = produto["stock"].as_i64().unwrap();
+    let estoque = |stock: i64| {
+        client
+            .patch(format!("{}/api/admin/products/{}/stock", common::BASE_URL, product_id))
+            .header("cookie", &admin)
+            .header("x-csrf-token", &csrf)
+            .json(&serde_json::json!({ "stock": stock }))
+            .send()
+    };
+    assert_eq!(estoque(100).await.unwrap().status().as_u16(), 200);
+    assert_eq!(estoque(2).await.unwrap().status().as_u16(), 200);
+    let entregas = || async {
+        let v: Vec<Value> = client
+            .get(format!("{}/api/admin/webhooks/{}/deliveries", common::BASE_URL, sub_id))
+            .header("cookie", &admin)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        v
+    };
+    let mut falha = None;
+    for _ in 0..50 {
+        falha = entregas().await.into_iter().find(|d| d["evento"] == "product.low_stock" && d["status"] == "falhou");
+        if falha.is_some() {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+    }
+    let falha = falha.expect("entrega de estoque baixo deve falhar");
+    assert_eq!(falha["ultimo_status_http"].as_i64(), Some(400));
+    assert_eq!(falha["tentativas"].as_i64(), Some(1));
+
+    // 3) Reenvio da entrega com falha, agora aceita e com assinatura HMAC válida
+    receptor.aceitar.store(true, Ordering::SeqCst);
+    let replay = || {
+        client
+            .post(format!("{}/api/admin/webhook-deliveries/{}/replay", common::BASE_URL, falha["id"].as_str().unwrap()))
+            .header("cookie", &admin)
+            .header("x-csrf-token", &csrf)
+            .send()
+    };
+    assert_eq!(replay().await.unwrap().status().as_u16(), 202);
+    let (headers, corpo) = esperar(&receptor, "product.low_stock", |v| v["dados"]["product_id"].as_u64() == Some(product_id))
+        .await
+        .expect("estoque baixo entregue após reenvio");
+    let ts = headers["x-mercado-timestamp"].to_str().unwrap();
+    let mut mac = Hmac::<sha2::Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+    mac.update(format!("{}.{}", ts, corpo).as_bytes());
+    let esperado = format!("sha256={}", hex::encode(mac.finalize().into_bytes()));
+    assert_eq!(headers["x-mercado-signature"].to_str().unwrap(), esperado);
+    let v: Value = serde_json::from_str(&corpo).unwrap();
+    assert_eq!(v["dados"]["stock"].as_i64(), Some(2));
+    for _ in 0..50 {
+        if entregas().await.iter().any(|d| d["id"] == falha["id"] && d["status"] == "entregue") {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+    }
+    assert_eq!(replay().await.unwrap().status().as_u16(), 409, "só entregas com falha são reenviadas");
+    assert_eq!(estoque(estoque_original).await.unwrap().status().as_u16(), 200);
+
+    // 4) Pedido criado e cancelado
+    client
+        .post(format!("{}/api/cart", common::BASE_URL))
+        .json(&serde_json::json!({ "product_id": product_id, "qty": 1 }))
+        .send()
+        .await
+        .unwrap();
+    let resp = client
+        .post(format!("{}/api/checkout", common::BASE_URL))
+        .header("cookie", &admin)
+        .header("x-csrf-token", &csrf)
+        .json(&serde_json::json!({ "payment": { "method": "pix" }, "customer_email": "admin@teste.com" }))
+        .send()
+        .await
+        .unwrap();
+    assert!(resp.status().is_success(), "Checkout deve retornar 200, obtido {}", resp.status());
+    let body: Value = resp.json().await.unwrap();
+    let order_id = body["order_id"].as_str().unwrap().to_string();
+    let (_, corpo) = esperar(&receptor, "order.created", |v| v["dados"]["order_id"].as_str() == Some(order_id.as_str()))
+        .await
+        .expect("order.created entregue");
+    let v: Value = serde_json::from_str(&corpo).unwrap();
+    assert_eq!(v["dados"]["items"][0]["product_id"].as_u64(), Some(product_id));
+    client
+        .post(format!("{}/api/pedidos/{}/cancelar", common::BASE_URL, order_id))
+        .header("cookie", &admin)
+        .header("x-csrf-token", &csrf)
+        .send()
+        .await
+        .unwrap();
+    let (_, corpo) = esperar(&receptor, "order.status_changed", |v| v["dados"]["order_id"].as_str() == Some(order_id.as_str()))
+        .await
+        .expect("order.status_changed entregue");
+    let v: Value = serde_json::from_str(&corpo).unwrap();
+    assert_eq!(v["dados"]["status"].as_str(), Some("cancelled"));
+    assert_eq!(v["dados"]["status_anterior"].as_str(), Some("paid"));
+
+    // 5) Remoção da assinatura
+    let resp = client
+        .delete(format!("{}/api/admin/webhooks/{}", common::BASE_URL, sub_id))
+        .header("cookie", &admin)
+        .header("x-csrf-token", &csrf)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+}
@@ -0,0 +1,54 @@
+import hashlib
+import hmac
+import json
+import os
+import time
+from http.server import BaseHTTPRequestHandler, HTTPServer
+
+# Receptor de exemplo para os webhooks (alternativa ao stream GET /api/admin/pedidos/stream e ao `mercado-admin pedidos watch`).
+# WEBHOOK_SECRET é o segredo devolvido na criação da assinatura; PORTA define a porta (padrão 9000).
+# Cadastre a URL http://<host>:9000/ em POST /api/admin/webhooks (na mesma máquina ou rede interna,
+# libere o host no servidor com WEBHOOK_ALLOW_PRIVATE_HOSTS).
+
+SECRET = os.environ['WEBHOOK_SECRET'].encode()
+TOLERANCIA_SEGUNDOS = 300
+
+
+class Receptor(BaseHTTPRequestHandler):
+    def do_POST(self):
+        corpo = self.rfile.read(int(self.headers.get('Content-Length', 0)))
+        ts = self.headers.get('X-Mercado-Timestamp', '0')
+        esperado = 'sha256=' + hmac.new(SECRET, ts.encode() + b'.' + corpo, hashlib.sha256).hexdigest()
+        assinatura_ok = hmac.compare_digest(esperado, self.headers.get('X-Mercado-Signature', ''))
+        if not assinatura_ok or abs(time.time() - int(ts)) > TOLERANCIA_SEGUNDOS:
+            # 4xx: o servidor não tenta de novo (a entrega fica como falhou e pode ser reenviada)
+            self.send_response(401)
+            self.end_headers()
+            return
+
+        evento = json.loads(corpo)
+        dados = evento['dados']
+        if evento['evento'] == 'order.created':
+            total_reais = dados['total_cents'] / 100.0
+            print(f"[novo pedido] {dados['created_at']} id={dados['order_id']} total=R$ {total_reais:.2f} metodo={dados['payment_method']}")
+            for it in dados['items']:
+                print(f"  - item product_id={it['product_id']} qty={it['qty']} unit=R$ {it['unit_price_cents'] / 100.0:.2f}")
+        elif evento['evento'] == 'order.status_changed':
+            print(f"[pedido] id={dados['order_id']} {dados['status_anterior']} -> {dados['status']}")
+        elif evento['evento'] == 'product.low_stock':
+            print(f"[estoque baixo] produto={dados['product_id']} {dados['name']} estoque={dados['stock']}")
+
+        self.send_response(204)
+        self.end_headers()
+
+    def log_message(self, *args):
+        pass
+
+
+if __name__ == '__main__':
+    porta = int(os.environ.get('PORTA', '9000'))
+    print(f'Recebendo webhooks na porta {porta} (Ctrl+C para sair)')
+    try:
+        HTTPServer(('0.0.0.0', porta), Receptor).serve_forever()
+    except KeyboardInterrupt:
+        pass